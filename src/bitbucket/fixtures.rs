@@ -0,0 +1,36 @@
+//! Clients and responses of Bitbucket shared by tests.
+
+use mockito::server_url;
+
+use super::client::BitbucketClient;
+use super::repository::BitbucketRepository;
+
+pub const PULL: &str = r#"{
+    "id": 1,
+    "version": 3,
+    "title": "add something",
+    "description": "description",
+    "state": "OPEN",
+    "createdDate": 1598918400000,
+    "updatedDate": 1599004800000,
+    "fromRef": {
+        "id": "refs/heads/feature",
+        "displayId": "feature",
+        "latestCommit": "abc",
+        "repository": { "slug": "repo", "project": { "key": "PROJ" } }
+    },
+    "toRef": {
+        "id": "refs/heads/master",
+        "displayId": "master",
+        "latestCommit": "def",
+        "repository": { "slug": "repo", "project": { "key": "PROJ" } }
+    },
+    "author": { "user": { "name": "someone" } },
+    "reviewers": [{ "user": { "name": "reviewer" } }],
+    "links": { "self": [{ "href": "https://bitbucket.example.com/projects/PROJ/repos/repo/pull-requests/1" }] }
+}"#;
+
+pub fn get_repo() -> BitbucketRepository {
+    let client = BitbucketClient::build(&server_url(), "token").unwrap();
+    BitbucketRepository::new(client, "PROJ", "repo")
+}
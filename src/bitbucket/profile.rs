@@ -0,0 +1,41 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_derive::*;
+
+use crate::{
+    profile::{Profile, ProfileConfig, Prompter},
+    utils,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BitbucketConfig {
+    pub host: String,
+    pub token: String,
+}
+
+impl ProfileConfig for BitbucketConfig {
+    fn fill_profile(&self, profile: &mut Profile) {
+        let mut default = vec![];
+        let configs = profile.bitbucket.as_mut().unwrap_or(&mut default);
+        configs.retain(|config| config.host != self.host);
+        configs.push(self.to_owned());
+        profile.bitbucket = Some(configs.to_owned());
+    }
+}
+
+#[derive(Default)]
+pub struct BitbucketPrompter;
+
+#[async_trait]
+impl Prompter for BitbucketPrompter {
+    fn display_name(&self) -> String {
+        "Bitbucket Server / Data Center".to_string()
+    }
+
+    async fn prompt(&self) -> Result<Box<dyn ProfileConfig>> {
+        Ok(Box::new(BitbucketConfig {
+            host: utils::user_input("host: ")?,
+            token: utils::user_input("HTTP access token: ")?,
+        }))
+    }
+}
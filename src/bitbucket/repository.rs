@@ -0,0 +1,801 @@
+use super::client::BitbucketClient;
+use super::structs::{
+    Activity, BitbucketResponse, BuildStatus, Change, Comment, Commit, Page, Participant, Pull,
+};
+use crate::profile::load_profile;
+use crate::repository::{
+    parse_head, CreateIssueOpt, CreatePullRequestOpt, ListIssueOpt, ListPullRequestOpt,
+    MergeMethod, MergePullRequestOpt, PullRequestSort, Repository, ReviewEvent, ReviewOpt,
+    UpdatePullRequestOpt,
+};
+use crate::structs::{
+    self, ChangedFile, Check, Discussion, Issue, PaginationResult, Pipeline, PullRequest,
+    PullRequestState, Repo,
+};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use git_url_parse::GitUrl;
+use log::debug;
+use reqwest::Method;
+use serde_json::json;
+
+/// Bitbucket Server relies on Jira to track issues.
+const ISSUES_NOT_SUPPORTED: &str = "issues are not supported by Bitbucket Server";
+
+/// Builds run on external CI servers which only report their statuses.
+const PIPELINES_NOT_SUPPORTED: &str = "pipelines are not supported by Bitbucket Server";
+
+pub struct BitbucketRepository {
+    project: String,
+    slug: String,
+    client: BitbucketClient,
+}
+
+impl BitbucketRepository {
+    pub async fn init(host: &str, remote_url: &GitUrl) -> Result<Self> {
+        let profile = load_profile().await?;
+        let config = profile.get_bitbucket_by_host(host).ok_or_else(|| {
+            anyhow!(
+                "no Bitbucket profile for {}: Try `yag profile add` first",
+                host
+            )
+        })?;
+        let client = BitbucketClient::build(&format!("https://{}", host), &config.token)?;
+        // clone urls are like `https://host/scm/PROJECT/repo.git`
+        let project = remote_url
+            .owner
+            .clone()
+            .ok_or_else(|| anyhow!("cannot resolve project of remote url"))?;
+        Ok(Self::new(client, &project, &remote_url.name))
+    }
+
+    pub(crate) fn new(client: BitbucketClient, project: &str, slug: &str) -> Self {
+        BitbucketRepository {
+            project: project.to_string(),
+            slug: slug.to_string(),
+            client,
+        }
+    }
+
+    fn get_pull_requests_uri(&self) -> String {
+        format!(
+            "/rest/api/1.0/projects/{}/repos/{}/pull-requests",
+            self.project, self.slug
+        )
+    }
+
+    /// Ref of the branch, which is in the fork of the owner's project for
+    /// `owner:branch`.
+    fn build_ref(&self, branch: &str) -> serde_json::Value {
+        let (owner, branch) = parse_head(branch);
+        json!({
+            "id": format!("refs/heads/{}", branch),
+            "repository": {
+                "slug": self.slug,
+                "project": { "key": owner.unwrap_or(&self.project) },
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl Repository for BitbucketRepository {
+    async fn get_pull_request(&self, id: usize) -> Result<PullRequest> {
+        self.get_pull(id).await.map(PullRequest::from)
+    }
+
+    async fn list_pull_requests(
+        &self,
+        opt: ListPullRequestOpt,
+    ) -> Result<PaginationResult<PullRequest>> {
+        debug!("opt: {:#?}", opt);
+
+        const SUPPORTED_FILTERS: &[&str] = &["reviewer", "base", "head", "search"];
+        if let Some(filter) = opt
+            .get_filters()
+            .into_iter()
+            .find(|f| !SUPPORTED_FILTERS.contains(f))
+        {
+            bail!(
+                "filtering pull requests by {} is not supported on Bitbucket",
+                filter
+            )
+        }
+
+        let state = match opt.state {
+            Some(PullRequestState::Open) => "OPEN",
+            Some(PullRequestState::Closed) => "DECLINED",
+            Some(PullRequestState::Merged) => "MERGED",
+            None => "ALL",
+        };
+        let order = match opt.sort {
+            Some(PullRequestSort::Created) | None if opt.ascending => "OLDEST",
+            Some(PullRequestSort::Created) | None => "NEWEST",
+            Some(_) => bail!("pull requests can only be sorted by creation time on Bitbucket"),
+        };
+        let page = opt.get_page().max(1);
+        let per_page = opt.get_per_page();
+        let start = (page - 1) * per_page;
+
+        let mut req = self
+            .client
+            .call(Method::GET, &self.get_pull_requests_uri())
+            .query(&[("state", state), ("order", order)])
+            .query(&[("start", start), ("limit", per_page)]);
+
+        let author = if opt.me {
+            Some(self.get_current_username().await?)
+        } else {
+            opt.author.clone()
+        };
+        if let Some(author) = author {
+            req = req.query(&[("role.1", "AUTHOR"), ("username.1", &author)]);
+        }
+        if let Some(reviewer) = opt.reviewer.as_ref() {
+            req = req.query(&[("role.2", "REVIEWER"), ("username.2", reviewer)]);
+        }
+        // pull requests can only be filtered by one of the branches
+        match (opt.base.as_ref(), opt.head.as_ref()) {
+            (Some(_), Some(_)) => {
+                bail!("filtering pull requests by both base and head is not supported on Bitbucket")
+            }
+            (Some(base), None) => {
+                req = req.query(&[
+                    ("direction", "INCOMING"),
+                    ("at", &format!("refs/heads/{}", base)),
+                ])
+            }
+            (None, Some(head)) => {
+                req = req.query(&[
+                    ("direction", "OUTGOING"),
+                    ("at", &format!("refs/heads/{}", head)),
+                ])
+            }
+            (None, None) => (),
+        }
+        if let Some(search) = opt.search.as_ref() {
+            req = req.query(&[("filterText", search)]);
+        }
+
+        let res = req.send().await?;
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<BitbucketResponse<Page<Pull>>>(&text)?.map(|pulls| {
+            let result = pulls
+                .values
+                .iter()
+                .map(|pr| PullRequest::from(pr.to_owned()))
+                .collect::<Vec<PullRequest>>();
+            // Bitbucket does not count all pull requests, so the total is only
+            // known on the last page
+            let total = Some(start as u64 + pulls.size).filter(|_| pulls.is_last_page);
+            let next_page = Some(page + 1).filter(|_| !pulls.is_last_page);
+            Ok(PaginationResult::new(result, total).with_page(page, next_page))
+        })
+    }
+
+    async fn create_pull_request(&self, opt: CreatePullRequestOpt) -> Result<PullRequest> {
+        if !opt.assignees.is_empty() || !opt.labels.is_empty() || opt.milestone.is_some() {
+            bail!("assignees, labels and milestones are not supported by Bitbucket Server")
+        }
+
+        let reviewers = opt
+            .reviewers
+            .iter()
+            .map(|name| json!({ "user": { "name": name } }))
+            .collect::<Vec<serde_json::Value>>();
+
+        let res = self
+            .client
+            .call(Method::POST, &self.get_pull_requests_uri())
+            .body(
+                json!({
+                    "title": opt.title,
+                    "description": opt.body,
+                    "draft": opt.draft,
+                    "fromRef": self.build_ref(&opt.source_branch),
+                    "toRef": self.build_ref(&opt.target_branch),
+                    "reviewers": reviewers,
+                })
+                .to_string(),
+            )
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<BitbucketResponse<Pull>>(&text)?
+            .map(|pr| Ok(PullRequest::from(pr.to_owned())))
+    }
+
+    async fn close_pull_request(&self, id: usize) -> Result<PullRequest> {
+        let pull = self.get_pull(id).await?;
+
+        let res = self
+            .client
+            .call(
+                Method::POST,
+                &format!("{}/{}/decline", self.get_pull_requests_uri(), id),
+            )
+            .query(&[("version", pull.version)])
+            .body("{}")
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<BitbucketResponse<Pull>>(&text)?
+            .map(|pr| Ok(PullRequest::from(pr.to_owned())))
+    }
+
+    async fn reopen_pull_request(&self, id: usize) -> Result<PullRequest> {
+        let pull = self.get_pull(id).await?;
+
+        let res = self
+            .client
+            .call(
+                Method::POST,
+                &format!("{}/{}/reopen", self.get_pull_requests_uri(), id),
+            )
+            .query(&[("version", pull.version)])
+            .body("{}")
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<BitbucketResponse<Pull>>(&text)?
+            .map(|pr| Ok(PullRequest::from(pr.to_owned())))
+    }
+
+    /// Bitbucket replaces the whole pull request, so that unchanged fields
+    /// are sent as they are.
+    async fn update_pull_request(
+        &self,
+        id: usize,
+        opt: UpdatePullRequestOpt,
+    ) -> Result<PullRequest> {
+        if opt.assignees.is_some() || opt.labels.is_some() || opt.milestone.is_some() {
+            bail!("assignees, labels and milestones are not supported by Bitbucket Server")
+        }
+
+        let pull = self.get_pull(id).await?;
+        let version = pull.version;
+        let pr = PullRequest::from(pull);
+
+        let reviewers = opt
+            .reviewers
+            .unwrap_or(pr.reviewers)
+            .iter()
+            .map(|name| json!({ "user": { "name": name } }))
+            .collect::<Vec<serde_json::Value>>();
+        let mut body = json!({
+            "version": version,
+            "title": opt.title.unwrap_or(pr.title),
+            "description": opt.body.or(pr.body),
+            "draft": opt.draft.unwrap_or(pr.draft),
+            "reviewers": reviewers,
+        });
+        if let Some(target_branch) = opt.target_branch.as_ref() {
+            body["toRef"] = self.build_ref(target_branch);
+        }
+
+        let res = self
+            .client
+            .call(
+                Method::PUT,
+                &format!("{}/{}", self.get_pull_requests_uri(), id),
+            )
+            .body(body.to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<BitbucketResponse<Pull>>(&text)?
+            .map(|pr| Ok(PullRequest::from(pr.to_owned())))
+    }
+
+    async fn merge_pull_request(&self, id: usize, opt: MergePullRequestOpt) -> Result<PullRequest> {
+        if opt.auto {
+            bail!("auto merge is not supported by Bitbucket Server")
+        }
+
+        let pull = self.get_pull(id).await?;
+        if opt.sha.is_some() && pull.from_ref.latest_commit != opt.sha {
+            bail!(
+                "head of pull request #{} does not match the expected sha",
+                id
+            )
+        }
+
+        let strategy = match opt.method {
+            MergeMethod::Merge => "no-ff",
+            MergeMethod::Squash => "squash",
+            MergeMethod::Rebase => "rebase-ff-only",
+        };
+        let mut body = json!({ "strategyId": strategy });
+        if let Some(message) = opt.get_commit_message() {
+            body["message"] = json!(message);
+        }
+
+        let res = self
+            .client
+            .call(
+                Method::POST,
+                &format!("{}/{}/merge", self.get_pull_requests_uri(), id),
+            )
+            .query(&[("version", pull.version)])
+            .body(body.to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        let merged =
+            serde_json::from_str::<BitbucketResponse<Pull>>(&text)?.map(|pr| Ok(pr.to_owned()))?;
+
+        if opt.delete_branch {
+            self.delete_branch(&pull).await?;
+        }
+
+        Ok(PullRequest::from(merged))
+    }
+
+    fn get_pull_request_ref(&self, id: usize) -> String {
+        format!("refs/pull-requests/{}/from", id)
+    }
+
+    async fn list_pull_request_discussions(&self, id: usize) -> Result<Vec<Discussion>> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("{}/{}/activities", self.get_pull_requests_uri(), id),
+            )
+            .query(&[("limit", "100")])
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        // activities are listed from the newest, with replies nested in comments
+        let mut discussions = serde_json::from_str::<BitbucketResponse<Page<Activity>>>(&text)?
+            .map(|page| {
+                Ok(page
+                    .values
+                    .iter()
+                    .filter(|activity| {
+                        activity.action == "COMMENTED"
+                            && activity.comment_action.as_deref() == Some("ADDED")
+                    })
+                    .filter_map(|activity| activity.to_owned().into_discussion())
+                    .collect::<Vec<Discussion>>())
+            })?;
+        discussions.reverse();
+        Ok(discussions)
+    }
+
+    async fn comment_pull_request(&self, id: usize, body: &str) -> Result<structs::Comment> {
+        let res = self
+            .client
+            .call(
+                Method::POST,
+                &format!("{}/{}/comments", self.get_pull_requests_uri(), id),
+            )
+            .body(json!({ "text": body }).to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<BitbucketResponse<Comment>>(&text)?
+            .map(|comment| Ok(structs::Comment::from(comment.to_owned())))
+    }
+
+    async fn get_pull_request_diff(&self, id: usize) -> Result<String> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("{}/{}.diff", self.get_pull_requests_uri(), id),
+            )
+            .send()
+            .await?;
+
+        let status = res.status();
+        let text = res.text().await?;
+        if !status.is_success() {
+            debug!("res: {}", text);
+            serde_json::from_str::<BitbucketResponse<Pull>>(&text)?.map(|_| Ok(()))?;
+        }
+        Ok(text)
+    }
+
+    async fn list_pull_request_files(&self, id: usize) -> Result<Vec<ChangedFile>> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("{}/{}/changes", self.get_pull_requests_uri(), id),
+            )
+            .query(&[("limit", "1000")])
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<BitbucketResponse<Page<Change>>>(&text)?.map(|page| {
+            Ok(page
+                .values
+                .iter()
+                .map(|change| ChangedFile::from(change.to_owned()))
+                .collect())
+        })
+    }
+
+    async fn review_pull_request(&self, id: usize, opt: ReviewOpt) -> Result<PullRequest> {
+        let mut comments = vec![];
+        if let Some(body) = opt.body.as_ref() {
+            comments.push(json!({ "text": body }));
+        }
+        // lines are assumed to be added ones in the new version of files
+        for comment in opt.comments.iter() {
+            comments.push(json!({
+                "text": comment.body,
+                "anchor": {
+                    "path": comment.path,
+                    "line": comment.line,
+                    "lineType": "ADDED",
+                    "fileType": "TO",
+                    "diffType": "EFFECTIVE",
+                },
+            }));
+        }
+
+        for comment in comments {
+            let res = self
+                .client
+                .call(
+                    Method::POST,
+                    &format!("{}/{}/comments", self.get_pull_requests_uri(), id),
+                )
+                .body(comment.to_string())
+                .send()
+                .await?;
+
+            let text = res.text().await?;
+            debug!("res: {}", text);
+
+            serde_json::from_str::<BitbucketResponse<Comment>>(&text)?.map(|_| Ok(()))?;
+        }
+
+        let status = match opt.event {
+            ReviewEvent::Approve => Some("APPROVED"),
+            ReviewEvent::RequestChanges => Some("NEEDS_WORK"),
+            ReviewEvent::Comment => None,
+        };
+        if let Some(status) = status {
+            let username = self.get_current_username().await?;
+            let res = self
+                .client
+                .call(
+                    Method::PUT,
+                    &format!(
+                        "{}/{}/participants/{}",
+                        self.get_pull_requests_uri(),
+                        id,
+                        username
+                    ),
+                )
+                .body(json!({ "status": status }).to_string())
+                .send()
+                .await?;
+
+            let text = res.text().await?;
+            debug!("res: {}", text);
+
+            serde_json::from_str::<BitbucketResponse<Participant>>(&text)?.map(|_| Ok(()))?;
+        }
+
+        self.get_pull_request(id).await
+    }
+
+    async fn get_issue(&self, _id: usize) -> Result<Issue> {
+        Err(anyhow!(ISSUES_NOT_SUPPORTED))
+    }
+
+    async fn list_issues(&self, _opt: ListIssueOpt) -> Result<PaginationResult<Issue>> {
+        Err(anyhow!(ISSUES_NOT_SUPPORTED))
+    }
+
+    async fn create_issue(&self, _opt: CreateIssueOpt) -> Result<Issue> {
+        Err(anyhow!(ISSUES_NOT_SUPPORTED))
+    }
+
+    async fn close_issue(&self, _id: usize) -> Result<Issue> {
+        Err(anyhow!(ISSUES_NOT_SUPPORTED))
+    }
+
+    async fn reopen_issue(&self, _id: usize) -> Result<Issue> {
+        Err(anyhow!(ISSUES_NOT_SUPPORTED))
+    }
+
+    async fn list_checks(&self, git_ref: &str) -> Result<Vec<Check>> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!(
+                    "/rest/api/1.0/projects/{}/repos/{}/commits",
+                    self.project, self.slug
+                ),
+            )
+            .query(&[("until", git_ref), ("limit", "1")])
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        let commit =
+            serde_json::from_str::<BitbucketResponse<Page<Commit>>>(&text)?.map(|page| {
+                page.values
+                    .first()
+                    .map(|commit| commit.id.to_owned())
+                    .ok_or_else(|| anyhow!("commit not found: {}", git_ref))
+            })?;
+
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/rest/build-status/1.0/commits/{}", commit),
+            )
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<BitbucketResponse<Page<BuildStatus>>>(&text)?.map(|page| {
+            Ok(page
+                .values
+                .iter()
+                .map(|status| Check::from(status.to_owned()))
+                .collect())
+        })
+    }
+
+    async fn list_pipelines(&self, _branch: Option<&str>) -> Result<Vec<Pipeline>> {
+        Err(anyhow!(PIPELINES_NOT_SUPPORTED))
+    }
+
+    async fn get_pipeline(&self, _id: u64) -> Result<Pipeline> {
+        Err(anyhow!(PIPELINES_NOT_SUPPORTED))
+    }
+
+    async fn retry_pipeline(&self, _id: u64, _failed_only: bool) -> Result<Pipeline> {
+        Err(anyhow!(PIPELINES_NOT_SUPPORTED))
+    }
+
+    async fn cancel_pipeline(&self, _id: u64) -> Result<Pipeline> {
+        Err(anyhow!(PIPELINES_NOT_SUPPORTED))
+    }
+
+    async fn get_job(&self, _id: u64) -> Result<Check> {
+        Err(anyhow!(PIPELINES_NOT_SUPPORTED))
+    }
+
+    async fn get_job_log(&self, _id: u64) -> Result<String> {
+        Err(anyhow!(PIPELINES_NOT_SUPPORTED))
+    }
+
+    /// Bitbucket has no permission info in repositories, so check whether
+    /// the repository is listed among writable ones.
+    async fn can_push(&self) -> Result<bool> {
+        let res = self
+            .client
+            .call(Method::GET, "/rest/api/1.0/repos")
+            .query(&[("name", self.slug.as_str()), ("permission", "REPO_WRITE")])
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<BitbucketResponse<Page<super::structs::Repository>>>(&text)?.map(
+            |repos| {
+                Ok(repos.values.iter().any(|repo| {
+                    repo.slug == self.slug && repo.project.key.eq_ignore_ascii_case(&self.project)
+                }))
+            },
+        )
+    }
+
+    /// Forks go to the personal project of the user unless `owner` is given.
+    async fn fork_repository(&self, owner: Option<&str>) -> Result<Repo> {
+        let mut body = json!({});
+        if let Some(owner) = owner {
+            body["project"] = json!({ "key": owner });
+        }
+
+        let res = self
+            .client
+            .call(
+                Method::POST,
+                &format!(
+                    "/rest/api/1.0/projects/{}/repos/{}",
+                    self.project, self.slug
+                ),
+            )
+            .body(body.to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<BitbucketResponse<super::structs::Repository>>(&text)?
+            .map(|repo| Ok(Repo::from(repo.to_owned())))
+    }
+}
+
+impl BitbucketRepository {
+    async fn get_pull(&self, id: usize) -> Result<Pull> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("{}/{}", self.get_pull_requests_uri(), id),
+            )
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<BitbucketResponse<Pull>>(&text)?.map(|pr| Ok(pr.to_owned()))
+    }
+
+    async fn get_current_username(&self) -> Result<String> {
+        let res = self
+            .client
+            .call(Method::GET, "/plugins/servlet/applinks/whoami")
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(res.text().await?.trim().to_string())
+    }
+
+    async fn delete_branch(&self, pull: &Pull) -> Result<()> {
+        let repository = &pull.from_ref.repository;
+        if repository.slug != self.slug || repository.project.key != self.project {
+            bail!(
+                "head branch {} belongs to another repository",
+                pull.from_ref.display_id
+            )
+        }
+
+        let res = self
+            .client
+            .call(
+                Method::DELETE,
+                &format!(
+                    "/rest/branch-utils/1.0/projects/{}/repos/{}/branches",
+                    self.project, self.slug
+                ),
+            )
+            .body(json!({ "name": pull.from_ref.id, "dryRun": false }).to_string())
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            debug!("res: {}", res.text().await?);
+            bail!("failed to delete branch {}", pull.from_ref.display_id)
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mockito::{mock, Matcher};
+
+    use crate::bitbucket::fixtures::{get_repo, PULL};
+
+    #[tokio::test]
+    async fn test_get_pull_request() -> Result<()> {
+        let _m = mock(
+            "GET",
+            "/rest/api/1.0/projects/PROJ/repos/repo/pull-requests/1",
+        )
+        .match_header("authorization", "Bearer token")
+        .with_body(PULL)
+        .create();
+
+        let pr = get_repo().get_pull_request(1).await?;
+        assert_eq!(pr.id, 1);
+        assert_eq!(pr.state, PullRequestState::Open);
+        assert_eq!(pr.head.unwrap(), "feature");
+        assert_eq!(pr.reviewers, vec!["reviewer"]);
+        assert_eq!(pr.created_at, "2020-09-01T00:00:00Z");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_pull_requests() -> Result<()> {
+        let _m = mock(
+            "GET",
+            "/rest/api/1.0/projects/PROJ/repos/repo/pull-requests",
+        )
+        .match_query(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("state".into(), "MERGED".into()),
+            Matcher::UrlEncoded("username.1".into(), "someone".into()),
+        ]))
+        .with_body(format!(
+            r#"{{ "values": [{}], "size": 1, "isLastPage": true, "start": 0 }}"#,
+            PULL
+        ))
+        .create();
+
+        let mut opt = ListPullRequestOpt::default();
+        opt.author = Some("someone".to_string());
+        opt.state = Some(PullRequestState::Merged);
+        let result = get_repo().list_pull_requests(opt).await?;
+        assert_eq!(result.total, Some(1));
+        assert!(result.result[0].url.ends_with("/pull-requests/1"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_pull_requests_with_next_page() -> Result<()> {
+        let _m = mock(
+            "GET",
+            "/rest/api/1.0/projects/PROJ/repos/repo/pull-requests",
+        )
+        .match_query(Matcher::UrlEncoded("start".into(), "0".into()))
+        .with_body(format!(
+            r#"{{ "values": [{}], "size": 1, "isLastPage": false, "start": 0 }}"#,
+            PULL
+        ))
+        .create();
+
+        let result = get_repo()
+            .list_pull_requests(ListPullRequestOpt::default())
+            .await?;
+        assert_eq!(result.total, None);
+        assert_eq!(result.next_page, Some(2));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_merge_pull_request_with_unexpected_sha() -> Result<()> {
+        let _m = mock(
+            "GET",
+            "/rest/api/1.0/projects/PROJ/repos/repo/pull-requests/1",
+        )
+        .with_body(PULL)
+        .create();
+
+        let opt = MergePullRequestOpt {
+            sha: Some("123".to_string()),
+            ..Default::default()
+        };
+        let err = get_repo().merge_pull_request(1, opt).await.unwrap_err();
+        assert!(err.to_string().contains("expected sha"));
+        Ok(())
+    }
+}
@@ -0,0 +1,344 @@
+use std::fmt;
+
+use anyhow::{bail, Result};
+use log::debug;
+use serde_derive::*;
+
+use crate::structs::{
+    self, ChangedFile, Check, CheckState, FileStatus, PullRequest, PullRequestState, Visibility,
+};
+use crate::utils::format_timestamp_millis;
+
+#[derive(Deserialize, Debug)]
+pub struct Error {
+    pub message: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum BitbucketResponse<T> {
+    Error { errors: Vec<Error> },
+    Ok(T),
+}
+
+impl<T> BitbucketResponse<T>
+where
+    T: fmt::Debug,
+{
+    #[inline]
+    pub fn map<R, F>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&T) -> Result<R>,
+    {
+        match self {
+            BitbucketResponse::Ok(data) => f(data),
+            BitbucketResponse::Error { errors } => {
+                debug!("found an error: {:#?}", self);
+                let message = errors
+                    .iter()
+                    .map(|error| error.message.to_owned())
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                bail!(message)
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    pub values: Vec<T>,
+    pub size: u64,
+    pub is_last_page: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Commit {
+    pub id: String,
+}
+
+/// Build result reported by CI servers like Bamboo or Jenkins.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildStatus {
+    state: String,
+    key: String,
+    name: Option<String>,
+    url: Option<String>,
+    date_added: u64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct User {
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Participant {
+    pub user: User,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Project {
+    pub key: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CloneLink {
+    pub href: String,
+    /// `http` or `ssh`.
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RepositoryLinks {
+    #[serde(default)]
+    pub clone: Vec<CloneLink>,
+    #[serde(rename = "self", default)]
+    pub this: Vec<Link>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Repository {
+    pub slug: String,
+    pub project: Project,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub public: bool,
+    /// Repository which it is forked from.
+    pub origin: Option<Box<Repository>>,
+    pub links: Option<RepositoryLinks>,
+}
+
+impl Repository {
+    fn get_full_name(&self) -> String {
+        format!("{}/{}", self.project.key, self.slug)
+    }
+}
+
+impl From<Repository> for structs::Repo {
+    fn from(repo: Repository) -> Self {
+        let links = repo.links.clone();
+        let get_clone_url = |name: &str| {
+            links
+                .as_ref()
+                .and_then(|links| links.clone.iter().find(|link| link.name == name))
+                .map(|link| link.href.to_owned())
+        };
+        Self {
+            name: repo.get_full_name(),
+            description: repo.description.clone(),
+            visibility: if repo.public {
+                Visibility::Public
+            } else {
+                Visibility::Private
+            },
+            parent: repo.origin.as_ref().map(|origin| origin.get_full_name()),
+            default_branch: None,
+            url: links
+                .as_ref()
+                .and_then(|links| links.this.first())
+                .map(|link| link.href.to_owned())
+                .unwrap_or_default(),
+            clone_url: get_clone_url("http").unwrap_or_default(),
+            ssh_url: get_clone_url("ssh"),
+            stars: None,
+            open_issues: None,
+            open_pull_requests: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Ref {
+    pub id: String,
+    pub display_id: String,
+    pub latest_commit: Option<String>,
+    pub repository: Repository,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Link {
+    pub href: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Links {
+    #[serde(rename = "self")]
+    pub this: Vec<Link>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Comment {
+    id: u64,
+    text: String,
+    author: User,
+    created_date: u64,
+    /// Replies of the comment.
+    #[serde(default)]
+    comments: Vec<Comment>,
+    thread_resolved: Option<bool>,
+}
+
+impl Comment {
+    /// The comment and all its replies, depth first.
+    fn flatten(self, comments: &mut Vec<structs::Comment>) {
+        comments.push(structs::Comment {
+            id: self.id,
+            author: self.author.name,
+            body: self.text,
+            created_at: format_timestamp_millis(self.created_date),
+            url: None,
+        });
+        for reply in self.comments {
+            reply.flatten(comments);
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CommentAnchor {
+    path: String,
+    line: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Activity {
+    pub action: String,
+    pub comment_action: Option<String>,
+    pub comment: Option<Comment>,
+    comment_anchor: Option<CommentAnchor>,
+}
+
+impl Activity {
+    pub fn into_discussion(self) -> Option<structs::Discussion> {
+        let comment = self.comment?;
+        let mut discussion = structs::Discussion {
+            id: comment.id.to_string(),
+            path: self.comment_anchor.as_ref().map(|a| a.path.to_owned()),
+            line: self.comment_anchor.as_ref().and_then(|a| a.line),
+            resolved: comment.thread_resolved,
+            comments: vec![],
+        };
+        comment.flatten(&mut discussion.comments);
+        Some(discussion)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Path {
+    to_string: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Change {
+    path: Path,
+    src_path: Option<Path>,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Pull {
+    pub id: u64,
+    pub version: u64,
+    title: String,
+    description: Option<String>,
+    state: String,
+    draft: Option<bool>,
+    created_date: u64,
+    updated_date: u64,
+    closed_date: Option<u64>,
+    pub from_ref: Ref,
+    to_ref: Ref,
+    author: Participant,
+    #[serde(default)]
+    reviewers: Vec<Participant>,
+    links: Links,
+}
+
+impl From<Pull> for PullRequest {
+    fn from(pr: Pull) -> Self {
+        let state = match pr.state.as_ref() {
+            "MERGED" => PullRequestState::Merged,
+            "DECLINED" => PullRequestState::Closed,
+            _ => PullRequestState::Open,
+        };
+        Self {
+            id: pr.id,
+            title: pr.title,
+            state,
+            draft: pr.draft.unwrap_or(false),
+            author: pr.author.user.name,
+            body: pr.description,
+            reviewers: pr.reviewers.into_iter().map(|r| r.user.name).collect(),
+            base: Some(pr.to_ref.display_id),
+            sha: pr.from_ref.latest_commit,
+            head: Some(pr.from_ref.display_id),
+            created_at: format_timestamp_millis(pr.created_date),
+            updated_at: format_timestamp_millis(pr.updated_date),
+            merged_at: pr
+                .closed_date
+                .filter(|_| state == PullRequestState::Merged)
+                .map(format_timestamp_millis),
+            url: pr
+                .links
+                .this
+                .first()
+                .map(|link| link.href.to_owned())
+                .unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<Comment> for structs::Comment {
+    fn from(comment: Comment) -> Self {
+        let mut comments = vec![];
+        comment.flatten(&mut comments);
+        comments.remove(0)
+    }
+}
+
+impl From<Change> for ChangedFile {
+    fn from(change: Change) -> Self {
+        Self {
+            status: match change.kind.as_ref() {
+                "ADD" | "COPY" => FileStatus::Added,
+                "DELETE" => FileStatus::Removed,
+                "MOVE" => FileStatus::Renamed,
+                _ => FileStatus::Modified,
+            },
+            path: change.path.to_string,
+            old_path: change.src_path.map(|path| path.to_string),
+            // Bitbucket does not count changed lines of files
+            additions: None,
+            deletions: None,
+        }
+    }
+}
+
+impl From<BuildStatus> for Check {
+    fn from(status: BuildStatus) -> Self {
+        Self {
+            state: match status.state.as_ref() {
+                "SUCCESSFUL" => CheckState::Success,
+                "FAILED" => CheckState::Failure,
+                "INPROGRESS" => CheckState::Running,
+                _ => CheckState::Pending,
+            },
+            name: status.name.unwrap_or(status.key.clone()),
+            id: status.key,
+            started_at: Some(format_timestamp_millis(status.date_added)),
+            url: status.url,
+            ..Default::default()
+        }
+    }
+}
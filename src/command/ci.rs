@@ -0,0 +1,315 @@
+use std::future::Future;
+use std::io::{stdout, Write};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use colored::Colorize;
+use log::debug;
+
+use crate::git::Git;
+use crate::output::{Output, OutputFormat};
+use crate::repository::{get_id, get_repo};
+use crate::structs::{CheckState, List, Pipeline};
+use crate::utils;
+
+/// Interval between polls of `--watch`.
+const WATCH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Interval between polls of `log --follow`.
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(3);
+
+#[inline]
+pub fn watch_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("watch")
+        .long("watch")
+        .short("w")
+        .help("wait until all checks finish")
+}
+
+#[inline]
+pub fn sub_command<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("ci")
+        .about("Manage CI pipelines (aka. workflow runs for GitHub Actions)")
+        .subcommand(
+            SubCommand::with_name("status")
+                .about("Show checks of the latest commit of branch, and exit with non-zero status if any of them failed")
+                .arg(
+                    Arg::with_name("branch")
+                        .takes_value(true)
+                        .help("branch or commit to check (default: current branch)"),
+                )
+                .arg(watch_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("list")
+                .about("List recent pipelines of current repository")
+                .arg(
+                    Arg::with_name("branch")
+                        .long("branch")
+                        .short("b")
+                        .takes_value(true)
+                        .help("only list pipelines of the branch"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("get")
+                .about("Get detail of single pipeline with its jobs, and exit with non-zero status if it failed")
+                .arg(Arg::with_name("id").required(true).takes_value(true))
+                .arg(watch_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("retry")
+                .about("Run pipeline again")
+                .arg(Arg::with_name("id").required(true).takes_value(true))
+                .arg(
+                    Arg::with_name("failed")
+                        .long("failed")
+                        .help("only rerun failed jobs"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("log")
+                .about("Show log of job")
+                .arg(
+                    Arg::with_name("job")
+                        .takes_value(true)
+                        .required_unless("failed")
+                        .conflicts_with("failed"),
+                )
+                .arg(
+                    Arg::with_name("follow")
+                        .long("follow")
+                        .short("f")
+                        .help("keep printing new output until the job finishes"),
+                )
+                .arg(
+                    Arg::with_name("failed")
+                        .long("failed")
+                        .help("show logs of failed jobs of the latest pipeline of branch"),
+                )
+                .arg(
+                    Arg::with_name("branch")
+                        .long("branch")
+                        .short("b")
+                        .takes_value(true)
+                        .requires("failed")
+                        .help("branch of the pipeline (default: current branch)"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("cancel")
+                .about("Cancel running pipeline")
+                .arg(Arg::with_name("id").required(true).takes_value(true)),
+        )
+}
+
+/// Print the result of `fetch`, polling until it finishes if `--watch` is
+/// given, and fail if it is failed.
+pub async fn watch<T, F, Fut>(
+    matches: &ArgMatches<'_>,
+    fetch: F,
+    get_state: fn(&T) -> CheckState,
+) -> Result<()>
+where
+    T: Output,
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let output = OutputFormat::from(matches);
+    let watch = matches.is_present("watch");
+    let redraw = output == OutputFormat::Human && utils::is_stdout_tty();
+
+    loop {
+        let data = fetch().await?;
+        let state = get_state(&data);
+
+        if !watch || state.is_finished() {
+            output.print(&data)?;
+            if state.is_failed() {
+                bail!("checks finished with {}", state)
+            }
+            return Ok(());
+        }
+
+        if redraw {
+            // clear the screen to show the latest state only
+            print!("\x1b[2J\x1b[H");
+            output.print(&data)?;
+            println!(
+                "\n{}",
+                format!(
+                    "checks are {}, refreshing every {}s...",
+                    state,
+                    WATCH_INTERVAL.as_secs()
+                )
+                .dimmed()
+            );
+        }
+        tokio::time::sleep(WATCH_INTERVAL).await;
+    }
+}
+
+pub struct Command<'a> {
+    command: &'a str,
+    matches: &'a ArgMatches<'a>,
+}
+
+impl<'a> Command<'a> {
+    pub fn new(matches: &'a ArgMatches<'a>) -> Option<Self> {
+        match matches.subcommand() {
+            (command, Some(arg_matches)) => Some(Command {
+                command,
+                matches: arg_matches,
+            }),
+            _ => {
+                println!("{}", matches.usage());
+                None
+            }
+        }
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        match self.command {
+            "status" => self.status().await,
+            "list" => self.list().await,
+            "get" => self.get().await,
+            "retry" => self.retry().await,
+            "cancel" => self.cancel().await,
+            "log" => self.log().await,
+            _ => {
+                println!("{}", self.matches.usage());
+                Ok(())
+            }
+        }
+    }
+
+    fn print<T: Output>(&self, data: &T) -> Result<()> {
+        OutputFormat::from(self.matches).print(data)
+    }
+
+    fn get_id(&self) -> Result<u64> {
+        get_id(self.matches, "id")
+    }
+
+    async fn status(&self) -> Result<()> {
+        let branch = match self.matches.value_of("branch") {
+            Some(branch) => branch.to_string(),
+            None => Git::current()?.get_current_branch()?,
+        };
+        let repo = get_repo().await?;
+
+        watch(
+            self.matches,
+            || async { Ok(List(repo.list_checks(&branch).await?)) },
+            |checks| CheckState::combine(checks.0.iter().map(|check| &check.state)),
+        )
+        .await
+    }
+
+    async fn list(&self) -> Result<()> {
+        let pipelines = get_repo()
+            .await?
+            .list_pipelines(self.matches.value_of("branch"))
+            .await?;
+
+        self.print(&List(pipelines))?;
+        Ok(())
+    }
+
+    async fn get(&self) -> Result<()> {
+        let id = self.get_id()?;
+        let repo = get_repo().await?;
+
+        watch(
+            self.matches,
+            || repo.get_pipeline(id),
+            |pipeline: &Pipeline| pipeline.state,
+        )
+        .await
+    }
+
+    async fn retry(&self) -> Result<()> {
+        let pipeline = get_repo()
+            .await?
+            .retry_pipeline(self.get_id()?, self.matches.is_present("failed"))
+            .await?;
+
+        self.print(&pipeline)?;
+        Ok(())
+    }
+
+    async fn log(&self) -> Result<()> {
+        if self.matches.is_present("failed") {
+            return self.log_failed().await;
+        }
+
+        let id = get_id(self.matches, "job")?;
+        let repo = get_repo().await?;
+
+        if !self.matches.is_present("follow") {
+            return utils::page_text(&repo.get_job_log(id).await?);
+        }
+
+        let mut printed = 0;
+        loop {
+            // check the state first so that no output is missed after the job finishes
+            let job = repo.get_job(id).await?;
+            match repo.get_job_log(id).await {
+                Ok(log) => {
+                    if let Some(output) = log.get(printed..) {
+                        print!("{}", output);
+                        stdout().flush()?;
+                    }
+                    printed = printed.max(log.len());
+                }
+                // the log might be unavailable while the job is running
+                Err(err) if !job.state.is_finished() => debug!("failed to get log: {}", err),
+                Err(err) => return Err(err),
+            }
+
+            if job.state.is_finished() {
+                if job.state.is_failed() {
+                    bail!("job #{} finished with {}", id, job.state)
+                }
+                return Ok(());
+            }
+            tokio::time::sleep(FOLLOW_INTERVAL).await;
+        }
+    }
+
+    async fn log_failed(&self) -> Result<()> {
+        let branch = match self.matches.value_of("branch") {
+            Some(branch) => branch.to_string(),
+            None => Git::current()?.get_current_branch()?,
+        };
+        let repo = get_repo().await?;
+        let latest = repo
+            .list_pipelines(Some(&branch))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("no pipeline for branch {}", branch))?;
+        let pipeline = repo.get_pipeline(latest.id).await?;
+
+        let mut logs = vec![];
+        for job in pipeline.jobs.iter().filter(|job| job.state.is_failed()) {
+            let id = job.id.parse::<u64>()?;
+            let header = format!("==> {} (#{}) <==", job.name, job.id).bold();
+            logs.push(format!("{}\n{}", header, repo.get_job_log(id).await?));
+        }
+        if logs.is_empty() {
+            println!("no failed jobs in pipeline #{}", pipeline.id);
+            return Ok(());
+        }
+
+        utils::page_text(&logs.join("\n"))
+    }
+
+    async fn cancel(&self) -> Result<()> {
+        let pipeline = get_repo().await?.cancel_pipeline(self.get_id()?).await?;
+
+        self.print(&pipeline)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+use anyhow::Result;
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use crate::output::{Output, OutputFormat};
+use crate::repository::{
+    get_id, get_repo, paginate, validate_positive_number, CreateIssueOpt, ListIssueOpt,
+};
+use crate::utils;
+
+#[inline]
+pub fn sub_command<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("issue")
+        .about("Manage issues")
+        .subcommand(
+            SubCommand::with_name("get")
+                .about("Get detail of single issue")
+                .arg(Arg::with_name("id").required(true).takes_value(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("close")
+                .about("Close issue")
+                .arg(Arg::with_name("id").required(true).takes_value(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("reopen")
+                .about("Reopen closed issue")
+                .arg(Arg::with_name("id").required(true).takes_value(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("list")
+                .about("List issues of current repository")
+                .arg(Arg::with_name("author").long("author").takes_value(true))
+                .arg(Arg::with_name("me").long("me").conflicts_with("author"))
+                .arg(
+                    Arg::with_name("assignee")
+                        .long("assignee")
+                        .takes_value(true)
+                        .help("only list issues assigned to the user"),
+                )
+                .arg(
+                    Arg::with_name("label")
+                        .long("label")
+                        .short("l")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("only list issues with all of the labels"),
+                )
+                .arg(
+                    Arg::with_name("milestone")
+                        .long("milestone")
+                        .takes_value(true)
+                        .help("only list issues in the milestone"),
+                )
+                .arg(
+                    Arg::with_name("status")
+                        .long("status")
+                        .alias("state")
+                        .takes_value(true)
+                        .possible_values(&["open", "closed", "all"])
+                        .default_value("open"),
+                )
+                .arg(
+                    Arg::with_name("page")
+                        .long("page")
+                        .takes_value(true)
+                        .validator(validate_positive_number),
+                )
+                .arg(
+                    Arg::with_name("per-page")
+                        .long("per-page")
+                        .takes_value(true)
+                        .validator(validate_positive_number)
+                        .help("number of issues fetched by each request"),
+                )
+                .arg(
+                    Arg::with_name("limit")
+                        .long("limit")
+                        .short("L")
+                        .takes_value(true)
+                        .validator(validate_positive_number)
+                        .conflicts_with("all")
+                        .help("list at most this number of issues across pages"),
+                )
+                .arg(
+                    Arg::with_name("all")
+                        .long("all")
+                        .short("A")
+                        .help("list issues of all pages"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("create")
+                .alias("new")
+                .about("Create a new issue")
+                .arg(Arg::with_name("title").required(true).takes_value(true))
+                .arg(
+                    Arg::with_name("body")
+                        .long("body")
+                        .takes_value(true)
+                        .conflicts_with("body-file")
+                        .help("description of the issue"),
+                )
+                .arg(
+                    Arg::with_name("body-file")
+                        .long("body-file")
+                        .short("F")
+                        .takes_value(true)
+                        .help("read description from file (use \"-\" to read from stdin)"),
+                )
+                .arg(
+                    Arg::with_name("assignee")
+                        .long("assignee")
+                        .short("a")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("assign users by their usernames"),
+                )
+                .arg(
+                    Arg::with_name("label")
+                        .long("label")
+                        .short("l")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("add labels by their names"),
+                )
+                .arg(
+                    Arg::with_name("milestone")
+                        .long("milestone")
+                        .takes_value(true)
+                        .help("add to milestone by its title or number"),
+                ),
+        )
+}
+
+pub struct Command<'a> {
+    command: &'a str,
+    matches: &'a ArgMatches<'a>,
+}
+
+impl<'a> Command<'a> {
+    pub fn new(matches: &'a ArgMatches<'a>) -> Option<Self> {
+        match matches.subcommand() {
+            (command, Some(arg_matches)) => Some(Command {
+                command,
+                matches: arg_matches,
+            }),
+            _ => {
+                println!("{}", matches.usage());
+                None
+            }
+        }
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        match self.command {
+            "get" => self.get().await,
+            "list" => self.list().await,
+            "create" => self.create().await,
+            "close" => self.close().await,
+            "reopen" => self.reopen().await,
+            _ => {
+                println!("{}", self.matches.usage());
+                Ok(())
+            }
+        }
+    }
+
+    fn print<T: Output>(&self, data: &T) -> Result<()> {
+        OutputFormat::from(self.matches).print(data)
+    }
+
+    fn get_id(&self) -> Result<usize> {
+        get_id(self.matches, "id")
+    }
+
+    async fn get(&self) -> Result<()> {
+        let issue = get_repo().await?.get_issue(self.get_id()?).await?;
+
+        self.print(&issue)?;
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<()> {
+        let opt = ListIssueOpt::from(self.matches.clone());
+        let repo = get_repo().await?;
+
+        let limit = self
+            .matches
+            .value_of("limit")
+            .and_then(|s| s.parse::<usize>().ok());
+        if limit.is_none() && !self.matches.is_present("all") {
+            let result = repo.list_issues(opt).await?;
+            self.print(&result)?;
+            return Ok(());
+        }
+
+        let issues = paginate(opt.get_page().max(1), limit, |page| {
+            repo.list_issues(opt.clone().with_page(page))
+        });
+        OutputFormat::from(self.matches).print_stream(issues).await
+    }
+
+    async fn create(&self) -> Result<()> {
+        let mut opt = CreateIssueOpt::from(self.matches.clone());
+        if let Some(path) = self.matches.value_of("body-file") {
+            opt.body = Some(utils::read_file_or_stdin(path)?);
+        }
+
+        let issue = get_repo().await?.create_issue(opt).await?;
+        self.print(&issue)?;
+        Ok(())
+    }
+
+    async fn close(&self) -> Result<()> {
+        let issue = get_repo().await?.close_issue(self.get_id()?).await?;
+
+        self.print(&issue)?;
+        Ok(())
+    }
+
+    async fn reopen(&self) -> Result<()> {
+        let issue = get_repo().await?.reopen_issue(self.get_id()?).await?;
+
+        self.print(&issue)?;
+        Ok(())
+    }
+}
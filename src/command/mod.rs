@@ -1,7 +1,12 @@
+mod ci;
+mod issue;
 mod pr;
 mod profile;
+mod repo;
 
 use crate::logger::Logger;
+use crate::output::OutputFormat;
+use crate::repository::{set_remote_opt, RemoteOpt};
 use anyhow::Result;
 use clap::{crate_authors, crate_version, App, AppSettings, Arg};
 use log::debug;
@@ -21,7 +26,35 @@ pub fn get_app<'a, 'b>() -> App<'a, 'b> {
                 .help("verbose mode")
                 .global(true),
         )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .help("output format")
+                .takes_value(true)
+                .possible_values(&["human", "json", "yaml", "tsv"])
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("remote")
+                .long("remote")
+                .help("git remote of the repository (default: yag.remote, upstream or origin)")
+                .takes_value(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("repo")
+                .long("repo")
+                .short("R")
+                .help("repository to work on instead of current one, like owner/name or host/owner/name")
+                .takes_value(true)
+                .conflicts_with("remote")
+                .global(true),
+        )
         .subcommand(pr::sub_command().setting(AppSettings::SubcommandRequiredElseHelp))
+        .subcommand(issue::sub_command().setting(AppSettings::SubcommandRequiredElseHelp))
+        .subcommand(ci::sub_command().setting(AppSettings::SubcommandRequiredElseHelp))
+        .subcommand(repo::sub_command().setting(AppSettings::SubcommandRequiredElseHelp))
         .subcommand(profile::sub_command().setting(AppSettings::SubcommandRequiredElseHelp))
 }
 
@@ -33,12 +66,21 @@ pub async fn run() -> Result<()> {
     Logger::init(matches.is_present("v"))?;
     debug!("verbose mode enabled");
 
+    set_remote_opt(RemoteOpt::from(&matches));
+
+    if OutputFormat::from(&matches) != OutputFormat::Human {
+        colored::control::set_override(false);
+    }
+
     if let (command, Some(arg_matches)) = matches.subcommand() {
         debug!("command: {}", command);
         debug!("arg matches: {:#?}", arg_matches);
         match command {
-            "pr" => pr::Command::new(&arg_matches).unwrap().run().await?,
-            "profile" => profile::Command::new(&arg_matches).unwrap().run().await?,
+            "pr" => pr::Command::new(arg_matches).unwrap().run().await?,
+            "issue" => issue::Command::new(arg_matches).unwrap().run().await?,
+            "ci" => ci::Command::new(arg_matches).unwrap().run().await?,
+            "repo" => repo::Command::new(arg_matches).unwrap().run().await?,
+            "profile" => profile::Command::new(arg_matches).unwrap().run().await?,
             _ => (),
         }
     } else {
@@ -1,9 +1,24 @@
+use std::convert::TryFrom;
+use std::fs;
+
 use anyhow::{bail, Error, Result};
-use clap::{App, Arg, ArgMatches, SubCommand};
+use clap::{App, Arg, ArgGroup, ArgMatches, SubCommand};
 use colored::Colorize;
+use git_url_parse::GitUrl;
 use utils::user_input;
 
-use crate::repository::{get_repo, ListPullRequestOpt};
+use super::ci;
+
+use crate::diff;
+use crate::git::{self, Git};
+use crate::output::{Output, OutputFormat};
+use crate::profile::load_profile;
+use crate::repository::{
+    get_id, get_remote_name, get_remote_url, get_repo, paginate, parse_head,
+    validate_positive_number, CreatePullRequestOpt, IncompletePullRequest, ListPullRequestOpt,
+    MergePullRequestOpt, Repository, ReviewOpt, UpdatePullRequestOpt,
+};
+use crate::structs::{CheckState, List};
 use crate::utils;
 
 #[inline]
@@ -26,14 +41,363 @@ pub fn sub_command<'a, 'b>() -> App<'a, 'b> {
                 .about("Close pull request")
                 .arg(Arg::with_name("id").required(true).takes_value(true)),
         )
+        .subcommand(
+            SubCommand::with_name("reopen")
+                .about("Reopen closed pull request")
+                .arg(Arg::with_name("id").required(true).takes_value(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("ready")
+                .about("Mark draft pull request as ready for review")
+                .arg(Arg::with_name("id").required(true).takes_value(true))
+                .arg(
+                    Arg::with_name("undo")
+                        .long("undo")
+                        .help("convert pull request back to draft"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("edit")
+                .alias("update")
+                .about("Edit pull request")
+                .arg(Arg::with_name("id").required(true).takes_value(true))
+                .arg(Arg::with_name("title").long("title").takes_value(true))
+                .arg(
+                    Arg::with_name("body")
+                        .long("body")
+                        .takes_value(true)
+                        .conflicts_with("body-file")
+                        .help("description of the pull request"),
+                )
+                .arg(
+                    Arg::with_name("body-file")
+                        .long("body-file")
+                        .short("F")
+                        .takes_value(true)
+                        .help("read description from file (use \"-\" to read from stdin)"),
+                )
+                .arg(
+                    Arg::with_name("base")
+                        .long("base")
+                        .short("b")
+                        .takes_value(true)
+                        .help("change the target branch"),
+                )
+                .arg(
+                    Arg::with_name("draft")
+                        .long("draft")
+                        .conflicts_with("ready")
+                        .help("convert to draft"),
+                )
+                .arg(
+                    Arg::with_name("ready")
+                        .long("ready")
+                        .help("mark as ready for review"),
+                )
+                .arg(
+                    Arg::with_name("reviewer")
+                        .long("reviewer")
+                        .short("r")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("replace reviewers with users by their usernames"),
+                )
+                .arg(
+                    Arg::with_name("assignee")
+                        .long("assignee")
+                        .short("a")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("replace assignees with users by their usernames"),
+                )
+                .arg(
+                    Arg::with_name("label")
+                        .long("label")
+                        .short("l")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("replace labels by their names"),
+                )
+                .arg(
+                    Arg::with_name("milestone")
+                        .long("milestone")
+                        .takes_value(true)
+                        .help("move to milestone by its title or number"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("merge")
+                .about("Merge pull request")
+                .arg(Arg::with_name("id").required(true).takes_value(true))
+                .arg(
+                    Arg::with_name("squash")
+                        .long("squash")
+                        .short("s")
+                        .conflicts_with("rebase")
+                        .help("squash commits into one commit"),
+                )
+                .arg(
+                    Arg::with_name("rebase")
+                        .long("rebase")
+                        .short("r")
+                        .help("rebase commits onto the base branch"),
+                )
+                .arg(
+                    Arg::with_name("delete-branch")
+                        .long("delete-branch")
+                        .short("d")
+                        .help("delete head branch after merged"),
+                )
+                .arg(
+                    Arg::with_name("auto")
+                        .long("auto")
+                        .help("merge automatically when checks pass"),
+                )
+                .arg(
+                    Arg::with_name("title")
+                        .long("title")
+                        .short("t")
+                        .takes_value(true)
+                        .help("title of the merge commit"),
+                )
+                .arg(
+                    Arg::with_name("message")
+                        .long("message")
+                        .short("m")
+                        .takes_value(true)
+                        .help("message of the merge commit"),
+                )
+                .arg(
+                    Arg::with_name("sha")
+                        .long("sha")
+                        .takes_value(true)
+                        .help("only merge if head of pull request matches this sha, which cannot be used with --rebase on GitLab"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("checks")
+                .about("Show CI checks of pull request, and exit with non-zero status if any of them failed")
+                .arg(Arg::with_name("id").required(true).takes_value(true))
+                .arg(ci::watch_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("diff")
+                .about("Show diff of pull request")
+                .arg(Arg::with_name("id").required(true).takes_value(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("files")
+                .about("List changed files of pull request")
+                .arg(Arg::with_name("id").required(true).takes_value(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("comments")
+                .about("List comments and discussions of pull request")
+                .arg(Arg::with_name("id").required(true).takes_value(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("comment")
+                .about("Comment on pull request")
+                .arg(Arg::with_name("id").required(true).takes_value(true))
+                .arg(
+                    Arg::with_name("body")
+                        .takes_value(true)
+                        .conflicts_with("body-file")
+                        .help("content of the comment, compose with editor if omitted"),
+                )
+                .arg(
+                    Arg::with_name("body-file")
+                        .long("body-file")
+                        .short("F")
+                        .takes_value(true)
+                        .help("read content from file (use \"-\" to read from stdin)"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("review")
+                .about("Review pull request")
+                .arg(Arg::with_name("id").required(true).takes_value(true))
+                .arg(
+                    Arg::with_name("approve")
+                        .long("approve")
+                        .short("a")
+                        .help("approve the changes"),
+                )
+                .arg(
+                    Arg::with_name("request-changes")
+                        .long("request-changes")
+                        .short("r")
+                        .help(
+                            "request changes before merging, which only publishes the comments \
+                             and revokes your approval on GitLab",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("comment")
+                        .long("comment")
+                        .short("c")
+                        .help("only leave comments"),
+                )
+                .group(
+                    ArgGroup::with_name("event")
+                        .args(&["approve", "request-changes", "comment"])
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("body")
+                        .long("body")
+                        .short("b")
+                        .takes_value(true)
+                        .conflicts_with("body-file")
+                        .help("summary of the review"),
+                )
+                .arg(
+                    Arg::with_name("body-file")
+                        .long("body-file")
+                        .short("F")
+                        .takes_value(true)
+                        .help("read summary from file (use \"-\" to read from stdin)"),
+                )
+                .arg(
+                    Arg::with_name("inline")
+                        .long("inline")
+                        .short("i")
+                        .takes_value(true)
+                        .value_names(&["path:line", "body"])
+                        .multiple(true)
+                        .number_of_values(2)
+                        .help("comment on a line of the changed file"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("checkout")
+                .alias("co")
+                .about("Fetch pull request and switch to its head branch")
+                .arg(Arg::with_name("id").required(true).takes_value(true))
+                .arg(
+                    Arg::with_name("branch")
+                        .long("branch")
+                        .short("b")
+                        .takes_value(true)
+                        .help("local branch name to use"),
+                )
+                .arg(
+                    Arg::with_name("force")
+                        .long("force")
+                        .short("f")
+                        .help("discard local changes and reset diverged local branch"),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("list")
-                .about("List pull requests")
                 .about("List pull requests of current repository")
                 .arg(Arg::with_name("author").long("author").takes_value(true))
                 .arg(Arg::with_name("me").long("me"))
-                .arg(Arg::with_name("status").long("status").takes_value(true))
-                .arg(Arg::with_name("page").long("page").takes_value(true)),
+                .arg(
+                    Arg::with_name("assignee")
+                        .long("assignee")
+                        .takes_value(true)
+                        .help("only list pull requests assigned to the user"),
+                )
+                .arg(
+                    Arg::with_name("reviewer")
+                        .long("reviewer")
+                        .alias("review-requested")
+                        .takes_value(true)
+                        .help("only list pull requests requesting review from the user"),
+                )
+                .arg(
+                    Arg::with_name("label")
+                        .long("label")
+                        .short("l")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("only list pull requests with all of the labels"),
+                )
+                .arg(
+                    Arg::with_name("base")
+                        .long("base")
+                        .short("b")
+                        .takes_value(true)
+                        .help("only list pull requests into the branch"),
+                )
+                .arg(
+                    Arg::with_name("head")
+                        .long("head")
+                        .takes_value(true)
+                        .help("only list pull requests from the branch"),
+                )
+                .arg(
+                    Arg::with_name("draft")
+                        .long("draft")
+                        .conflicts_with("ready")
+                        .help("only list draft pull requests"),
+                )
+                .arg(
+                    Arg::with_name("ready")
+                        .long("ready")
+                        .help("only list pull requests ready for review"),
+                )
+                .arg(
+                    Arg::with_name("search")
+                        .long("search")
+                        .short("S")
+                        .takes_value(true)
+                        .help("only list pull requests matching the text"),
+                )
+                .arg(
+                    Arg::with_name("sort")
+                        .long("sort")
+                        .takes_value(true)
+                        .possible_values(&["created", "updated", "comments"]),
+                )
+                .arg(
+                    Arg::with_name("order")
+                        .long("order")
+                        .takes_value(true)
+                        .possible_values(&["asc", "desc"])
+                        .default_value("desc"),
+                )
+                .arg(
+                    Arg::with_name("status")
+                        .long("status")
+                        .alias("state")
+                        .takes_value(true)
+                        .possible_values(&["open", "closed", "merged", "all"])
+                        .default_value("open"),
+                )
+                .arg(
+                    Arg::with_name("page")
+                        .long("page")
+                        .takes_value(true)
+                        .validator(validate_positive_number),
+                )
+                .arg(
+                    Arg::with_name("per-page")
+                        .long("per-page")
+                        .takes_value(true)
+                        .validator(validate_positive_number)
+                        .help("number of pull requests fetched by each request"),
+                )
+                .arg(
+                    Arg::with_name("limit")
+                        .long("limit")
+                        .short("L")
+                        .takes_value(true)
+                        .validator(validate_positive_number)
+                        .conflicts_with("all")
+                        .help("list at most this number of pull requests across pages"),
+                )
+                .arg(
+                    Arg::with_name("all")
+                        .long("all")
+                        .short("A")
+                        .help("list pull requests of all pages"),
+                ),
         )
         .subcommand(
             SubCommand::with_name("create")
@@ -53,6 +417,74 @@ pub fn sub_command<'a, 'b>() -> App<'a, 'b> {
                         .long("head")
                         .short("h")
                         .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("body")
+                        .long("body")
+                        .takes_value(true)
+                        .conflicts_with("body-file")
+                        .help("description of the pull request"),
+                )
+                .arg(
+                    Arg::with_name("body-file")
+                        .long("body-file")
+                        .short("F")
+                        .takes_value(true)
+                        .help("read description from file (use \"-\" to read from stdin)"),
+                )
+                .arg(
+                    Arg::with_name("edit")
+                        .long("edit")
+                        .short("e")
+                        .help("compose title and description with editor"),
+                )
+                .arg(
+                    Arg::with_name("draft")
+                        .long("draft")
+                        .short("d")
+                        .help("create as draft"),
+                )
+                .arg(
+                    Arg::with_name("reviewer")
+                        .long("reviewer")
+                        .short("r")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("request review from users by their usernames"),
+                )
+                .arg(
+                    Arg::with_name("assignee")
+                        .long("assignee")
+                        .short("a")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("assign users by their usernames"),
+                )
+                .arg(
+                    Arg::with_name("label")
+                        .long("label")
+                        .short("l")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("add labels by their names"),
+                )
+                .arg(
+                    Arg::with_name("milestone")
+                        .long("milestone")
+                        .takes_value(true)
+                        .help("add to milestone by its title or number"),
+                )
+                .arg(
+                    Arg::with_name("push")
+                        .long("push")
+                        .short("p")
+                        .help(
+                            "push the head branch without asking if it is not up to date on \
+                             the remote, which is never pushed otherwise when not interactive",
+                        ),
                 ),
         )
 }
@@ -66,7 +498,7 @@ impl<'a> Command<'a> {
     pub fn new(matches: &'a ArgMatches<'a>) -> Option<Self> {
         match matches.subcommand() {
             (command, Some(arg_matches)) => Some(Command {
-                command: command,
+                command,
                 matches: arg_matches,
             }),
             _ => {
@@ -83,30 +515,48 @@ impl<'a> Command<'a> {
             "list" => self.list().await,
             "create" => self.create().await,
             "close" => self.close().await,
-            _ => Ok(println!("{}", self.matches.usage())),
+            "reopen" => self.reopen().await,
+            "ready" => self.ready().await,
+            "edit" => self.edit().await,
+            "merge" => self.merge().await,
+            "checkout" => self.checkout().await,
+            "checks" => self.checks().await,
+            "diff" => self.diff().await,
+            "files" => self.files().await,
+            "comments" => self.comments().await,
+            "comment" => self.comment().await,
+            "review" => self.review().await,
+            _ => {
+                println!("{}", self.matches.usage());
+                Ok(())
+            }
         }
     }
 
+    fn output(&self) -> OutputFormat {
+        OutputFormat::from(self.matches)
+    }
+
+    fn print<T: Output>(&self, data: &T) -> Result<()> {
+        self.output().print(data)
+    }
+
+    fn get_id(&self) -> Result<usize> {
+        get_id(self.matches, "id")
+    }
+
     async fn get(&self) -> Result<()> {
-        let id = self
-            .matches
-            .value_of("id")
-            .and_then(|s| s.parse::<usize>().ok())
-            .unwrap();
+        let id = self.get_id()?;
 
         let pr = get_repo().await?.get_pull_request(id).await?;
 
-        println!("{:#}", pr);
+        self.print(&pr)?;
 
         Ok(())
     }
 
     async fn open(&self) -> Result<()> {
-        let id = self
-            .matches
-            .value_of("id")
-            .and_then(|s| s.parse::<usize>().ok())
-            .unwrap();
+        let id = self.get_id()?;
 
         let pr = get_repo().await?.get_pull_request(id).await?;
 
@@ -117,30 +567,46 @@ impl<'a> Command<'a> {
 
     async fn list(&self) -> Result<()> {
         let opt = ListPullRequestOpt::from(self.matches.clone());
-        let result = get_repo().await?.list_pull_requests(opt).await?;
+        let repo = get_repo().await?;
 
-        print!("{:#}", result);
-        Ok(())
+        let limit = self
+            .matches
+            .value_of("limit")
+            .and_then(|s| s.parse::<usize>().ok());
+        if limit.is_none() && !self.matches.is_present("all") {
+            let result = repo.list_pull_requests(opt).await?;
+            self.print(&result)?;
+            return Ok(());
+        }
+
+        let pull_requests = paginate(opt.get_page().max(1), limit, |page| {
+            repo.list_pull_requests(opt.clone().with_page(page))
+        });
+        self.output().print_stream(pull_requests).await
     }
 
     async fn create(&self) -> Result<()> {
+        let git = Git::current()?;
         let source_branch = match self.matches.value_of("head") {
             Some(head) => head.to_string(),
-            _ => utils::get_current_branch()?,
+            _ => git.get_current_branch()?,
         };
 
         let target_branch = self
             .matches
             .value_of("base")
             .map(|base| base.to_string())
-            .or_else(|| utils::get_git_config("yag.pr.target").ok())
+            .or_else(|| git.get_config("yag.pr.target").ok())
             .unwrap_or("master".to_string());
 
-        if source_branch == target_branch {
+        // heads like `owner:branch` from forks cannot be checked locally
+        let from_fork = parse_head(&source_branch).0.is_some();
+
+        if !from_fork && source_branch == target_branch {
             bail!("head branch and base branch are same: {}", source_branch)
         }
 
-        if utils::get_rev(&source_branch)? == utils::get_rev(&target_branch)? {
+        if !from_fork && git.get_rev(&source_branch)? == git.get_rev(&target_branch)? {
             let ok = user_input(&format!(
                 "{} head is same as base. still create pr? (Y/n) ",
                 "warning".yellow().bold()
@@ -150,32 +616,738 @@ impl<'a> Command<'a> {
             }
         }
 
-        let title = self
-            .matches
-            .value_of("title")
-            .map(|title| title.to_string())
-            .or_else(|| utils::get_latest_commit_message().ok())
-            .ok_or(Error::msg(
-                "Cannot get latest commit message. Please specify title manually.",
-            ))?;
+        let mut opt = CreatePullRequestOpt::from(self.matches.clone());
+        if let Some(path) = self.matches.value_of("body-file") {
+            opt.body = Some(utils::read_file_or_stdin(path)?);
+        }
 
-        let pr = get_repo()
-            .await?
-            .create_pull_request(&source_branch, &target_branch, &title)
-            .await?;
-        println!("{:#}", pr);
-        Ok(())
+        if self.matches.is_present("edit") {
+            let (title, body) = compose_pull_request(
+                &git,
+                &source_branch,
+                &target_branch,
+                self.matches.value_of("title"),
+                opt.body.as_deref(),
+            )?;
+            opt.title = title;
+            opt.body = body;
+        } else {
+            opt.title = self
+                .matches
+                .value_of("title")
+                .map(|title| title.to_string())
+                .or_else(|| git.get_latest_commit_message().ok())
+                .ok_or(Error::msg(
+                    "Cannot get latest commit message. Please specify title manually.",
+                ))?;
+        }
+
+        let repo = get_repo().await?;
+        opt.source_branch = self.push_head(&git, repo.as_ref(), &source_branch).await?;
+        opt.target_branch = target_branch;
+
+        match repo.create_pull_request(opt).await {
+            Ok(pr) => self.print(&pr),
+            Err(err) => {
+                let incomplete = err.downcast::<IncompletePullRequest>()?;
+                self.print(&incomplete.pr)?;
+                for err in incomplete.errors {
+                    eprintln!("{} {}", "warning".yellow().bold(), err);
+                }
+                Ok(())
+            }
+        }
     }
 
     async fn close(&self) -> Result<()> {
-        let id = self
-            .matches
-            .value_of("id")
-            .and_then(|s| s.parse::<usize>().ok())
-            .unwrap();
+        let id = self.get_id()?;
         let pr = get_repo().await?.close_pull_request(id).await?;
 
-        println!("{:#}", pr);
+        self.print(&pr)?;
+        Ok(())
+    }
+
+    async fn reopen(&self) -> Result<()> {
+        let id = self.get_id()?;
+        let pr = get_repo().await?.reopen_pull_request(id).await?;
+
+        self.print(&pr)?;
+        Ok(())
+    }
+
+    async fn ready(&self) -> Result<()> {
+        let id = self.get_id()?;
+        let opt = UpdatePullRequestOpt {
+            draft: Some(self.matches.is_present("undo")),
+            ..Default::default()
+        };
+        let pr = get_repo().await?.update_pull_request(id, opt).await?;
+
+        self.print(&pr)?;
+        Ok(())
+    }
+
+    async fn edit(&self) -> Result<()> {
+        let id = self.get_id()?;
+        let mut opt = UpdatePullRequestOpt::from(self.matches.clone());
+        if let Some(path) = self.matches.value_of("body-file") {
+            opt.body = Some(utils::read_file_or_stdin(path)?);
+        }
+        if opt.is_empty() {
+            bail!("nothing to update")
+        }
+
+        let pr = get_repo().await?.update_pull_request(id, opt).await?;
+        self.print(&pr)?;
+        Ok(())
+    }
+
+    async fn merge(&self) -> Result<()> {
+        let id = self.get_id()?;
+        let opt = MergePullRequestOpt::from(self.matches.clone());
+        let pr = get_repo().await?.merge_pull_request(id, opt).await?;
+
+        self.print(&pr)?;
+        Ok(())
+    }
+
+    async fn checks(&self) -> Result<()> {
+        let id = self.get_id()?;
+        let repo = get_repo().await?;
+        let pr = repo.get_pull_request(id).await?;
+        let git_ref = pr
+            .sha
+            .or(pr.head)
+            .unwrap_or_else(|| repo.get_pull_request_ref(id));
+
+        ci::watch(
+            self.matches,
+            || async { Ok(List(repo.list_checks(&git_ref).await?)) },
+            |checks| CheckState::combine(checks.0.iter().map(|check| &check.state)),
+        )
+        .await
+    }
+
+    async fn diff(&self) -> Result<()> {
+        let id = self.get_id()?;
+        let diff = get_repo().await?.get_pull_request_diff(id).await?;
+
+        if utils::is_stdout_tty() {
+            utils::page_text(&diff::colorize(&diff))
+        } else {
+            utils::page_text(&diff)
+        }
+    }
+
+    async fn files(&self) -> Result<()> {
+        let id = self.get_id()?;
+        let files = get_repo().await?.list_pull_request_files(id).await?;
+
+        match self.output() {
+            OutputFormat::Human => utils::page_text(&format!("{}", List(files))),
+            _ => self.print(&List(files)),
+        }
+    }
+
+    async fn comments(&self) -> Result<()> {
+        let id = self.get_id()?;
+        let discussions = get_repo().await?.list_pull_request_discussions(id).await?;
+
+        self.print(&List(discussions))?;
+        Ok(())
+    }
+
+    async fn comment(&self) -> Result<()> {
+        let id = self.get_id()?;
+
+        let body = match (
+            self.matches.value_of("body"),
+            self.matches.value_of("body-file"),
+        ) {
+            (Some(body), _) => body.to_string(),
+            (None, Some(path)) => utils::read_file_or_stdin(path)?,
+            (None, None) => compose_comment(
+                "COMMENT_EDITMSG",
+                &format!("the comment on pull request #{}", id),
+            )?,
+        };
+        if body.trim().is_empty() {
+            bail!("aborting comment due to empty content")
+        }
+
+        let comment = get_repo().await?.comment_pull_request(id, &body).await?;
+
+        self.print(&comment)?;
+        Ok(())
+    }
+
+    async fn review(&self) -> Result<()> {
+        let id = self.get_id()?;
+
+        let mut opt = ReviewOpt::try_from(self.matches.clone())?;
+        if let Some(path) = self.matches.value_of("body-file") {
+            opt.body = Some(utils::read_file_or_stdin(path)?);
+        }
+        // only approvals are accepted without a body, so ask for it up front
+        if opt.needs_body() {
+            opt.body = Some(compose_comment(
+                "REVIEW_EDITMSG",
+                &format!("the review of pull request #{}", id),
+            )?)
+            .filter(|body| !body.is_empty());
+            if opt.body.is_none() {
+                bail!("aborting review due to empty content")
+            }
+        }
+
+        let pr = get_repo().await?.review_pull_request(id, opt).await?;
+
+        self.print(&pr)?;
+        Ok(())
+    }
+
+    /// Push the head branch if it is not on the remote or has new commits, and
+    /// return the head to create the pull request from, which is prefixed by
+    /// the owner if it is pushed to a fork.
+    async fn push_head(&self, git: &Git, repo: &dyn Repository, branch: &str) -> Result<String> {
+        // the branch might only exist on the remote
+        if !git.branch_exists(branch) {
+            return Ok(branch.to_string());
+        }
+
+        let base = get_remote_url(&load_profile().await?)?;
+        let target = get_push_target(git, repo, &base, branch).await?;
+
+        if let Some(pending) = target.pending.as_ref() {
+            // only ask when someone can answer
+            let push = if self.matches.is_present("push") {
+                true
+            } else if self.output() == OutputFormat::Human
+                && utils::is_stdin_tty()
+                && utils::is_stdout_tty()
+            {
+                user_input(&format!(
+                    "{} {}. push it now? (Y/n) ",
+                    "warning".yellow().bold(),
+                    pending
+                ))? != "n"
+            } else {
+                eprintln!(
+                    "{} {}. use --push to push it",
+                    "warning".yellow().bold(),
+                    pending
+                );
+                false
+            };
+            if push {
+                git.push(
+                    &target.remote,
+                    &format!("{}:{}", branch, target.branch),
+                    !target.tracked,
+                )?;
+            }
+        }
+
+        // pull requests from forks are created with `owner:branch`
+        let owner = GitUrl::parse(&git.get_remote_url(&target.remote)?)?.owner;
+        Ok(match owner {
+            Some(owner) if Some(&owner) != base.owner.as_ref() => {
+                format!("{}:{}", owner, target.branch)
+            }
+            _ => target.branch,
+        })
+    }
+
+    async fn checkout(&self) -> Result<()> {
+        let id = self.get_id()?;
+        let force = self.matches.is_present("force");
+
+        let git = Git::current()?;
+        ensure_worktree_clean(&git, force)?;
+
+        let repo = get_repo().await?;
+        let pr = repo.get_pull_request(id).await?;
+
+        let branch = self
+            .matches
+            .value_of("branch")
+            .map(|branch| branch.to_string())
+            .or_else(|| {
+                pr.head
+                    .clone()
+                    .filter(|head| Some(head) != pr.base.as_ref())
+            })
+            .unwrap_or(format!("pr/{}", id));
+
+        let remote = get_remote_name(&git)?;
+        let remote_ref = repo.get_pull_request_ref(id);
+        checkout_ref(&git, &remote, &remote_ref, &branch, force)?;
+
+        self.print(&pr)?;
+        if self.output() == OutputFormat::Human {
+            println!("switched to branch {}", branch.cyan().bold());
+        }
         Ok(())
     }
 }
+
+/// Refuse to discard uncommitted changes unless forced.
+fn ensure_worktree_clean(git: &Git, force: bool) -> Result<()> {
+    if !force && !git.is_worktree_clean()? {
+        bail!("worktree has uncommitted changes. commit or stash them first, or use --force")
+    }
+    Ok(())
+}
+
+/// Fetch the ref from the remote and switch to it as the local branch tracking
+/// it, which is refused if the branch has diverged from it unless forced.
+fn checkout_ref(
+    git: &Git,
+    remote: &str,
+    remote_ref: &str,
+    branch: &str,
+    force: bool,
+) -> Result<()> {
+    git.run(&["fetch", remote, remote_ref])?;
+
+    if git.branch_exists(branch) && !git.is_ancestor(branch, "FETCH_HEAD") && !force {
+        bail!(
+            "local branch {} has diverged from {}. use --force to reset it",
+            branch,
+            remote_ref
+        )
+    }
+
+    if force {
+        git.run(&["checkout", "--force", "-B", branch, "FETCH_HEAD"])?;
+    } else {
+        git.run(&["checkout", "-B", branch, "FETCH_HEAD"])?;
+    }
+    git.set_config(&format!("branch.{}.remote", branch), remote)?;
+    git.set_config(&format!("branch.{}.merge", branch), remote_ref)?;
+    Ok(())
+}
+
+/// Where the local branch is pushed to.
+#[derive(Debug, PartialEq)]
+struct PushTarget {
+    remote: String,
+    /// Branch on the remote, which might be named differently from the local
+    /// one it tracks.
+    branch: String,
+    /// Whether the remote branch is the upstream of the local one already.
+    tracked: bool,
+    /// Why the branch needs to be pushed, if it does.
+    pending: Option<String>,
+}
+
+/// Push the branch to its upstream if it tracks a remote branch, or else to
+/// the push remote under the same name.
+async fn get_push_target(
+    git: &Git,
+    repo: &dyn Repository,
+    base: &GitUrl,
+    branch: &str,
+) -> Result<PushTarget> {
+    let remote = git.get_config(&format!("branch.{}.remote", branch)).ok();
+    let merge = git.get_config(&format!("branch.{}.merge", branch)).ok();
+    let upstream = git.get_upstream(branch)?;
+    if let (Some(remote), Some(merge), Some(upstream)) = (remote, merge, upstream) {
+        // refs like `refs/pull/1/head` of checked out pull requests cannot be
+        // pushed to
+        let upstream_branch = merge.strip_prefix("refs/heads/").filter(|_| remote != ".");
+        if let Some(upstream_branch) = upstream_branch {
+            let (ahead, _) = git.get_ahead_behind(branch, &upstream)?;
+            return Ok(PushTarget {
+                pending: Some(format!(
+                    "{} has new commits not pushed to {}",
+                    branch, upstream
+                ))
+                .filter(|_| ahead > 0),
+                remote,
+                branch: upstream_branch.to_string(),
+                tracked: true,
+            });
+        }
+    }
+
+    let remote = get_push_remote(git, repo, base, branch).await?;
+    Ok(PushTarget {
+        pending: Some(format!("{} is not pushed to {}", branch, remote)),
+        remote,
+        branch: branch.to_string(),
+        tracked: false,
+    })
+}
+
+/// Remote to push new branches to, which is a fork of `base` if the user
+/// cannot push to it.
+async fn get_push_remote(
+    git: &Git,
+    repo: &dyn Repository,
+    base: &GitUrl,
+    branch: &str,
+) -> Result<String> {
+    if let Ok(remote) = git
+        .get_config(&format!("branch.{}.pushRemote", branch))
+        .or_else(|_| git.get_config("remote.pushDefault"))
+    {
+        return Ok(remote);
+    }
+    let base_remote = get_remote_name(git)?;
+    if repo.can_push().await? {
+        return Ok(base_remote);
+    }
+
+    // forks usually keep the name of the repository
+    for remote in git.get_remotes()?.into_iter().filter(|r| *r != base_remote) {
+        let url = git
+            .get_remote_url(&remote)
+            .ok()
+            .and_then(|url| GitUrl::parse(&url).ok());
+        if url.is_some_and(|url| url.host == base.host && url.name == base.name) {
+            return Ok(remote);
+        }
+    }
+    bail!(
+        "no permission to push to {}. fork it and add the fork as a remote first",
+        base.fullname
+    )
+}
+
+const TEMPLATE_PATHS: &[&str] = &[
+    ".github/PULL_REQUEST_TEMPLATE.md",
+    ".github/pull_request_template.md",
+    "PULL_REQUEST_TEMPLATE.md",
+    "pull_request_template.md",
+    "docs/PULL_REQUEST_TEMPLATE.md",
+    "docs/pull_request_template.md",
+];
+
+const GITLAB_TEMPLATE_DIR: &str = ".gitlab/merge_request_templates";
+
+fn get_pull_request_template(git: &Git) -> Option<String> {
+    let root = git.get_toplevel().ok()?;
+
+    if let Some(template) = TEMPLATE_PATHS
+        .iter()
+        .find_map(|path| fs::read_to_string(root.join(path)).ok())
+    {
+        return Some(template);
+    }
+
+    // prefer the `Default` template as GitLab does
+    let mut templates = fs::read_dir(root.join(GITLAB_TEMPLATE_DIR))
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+        .collect::<Vec<_>>();
+    templates.sort_by_key(|path| {
+        (
+            path.file_stem().map_or(true, |stem| stem != "Default"),
+            path.clone(),
+        )
+    });
+    templates
+        .first()
+        .and_then(|path| fs::read_to_string(path).ok())
+}
+
+/// Open the editor to compose the title and the description of a pull request.
+/// The first line becomes the title and the rest becomes the description.
+fn compose_pull_request(
+    git: &Git,
+    source_branch: &str,
+    target_branch: &str,
+    title: Option<&str>,
+    body: Option<&str>,
+) -> Result<(String, Option<String>)> {
+    let remote = get_remote_name(git).unwrap_or("origin".to_string());
+    let messages = git
+        .get_commit_messages(&format!("{}/{}", remote, target_branch), source_branch)
+        .or_else(|_| git.get_commit_messages(target_branch, source_branch))
+        .unwrap_or_default();
+
+    let mut content = match (title, messages.as_slice()) {
+        (Some(title), _) => title.to_string(),
+        (None, [message]) => message.to_owned(),
+        (None, [first, ..]) => {
+            let subjects = messages
+                .iter()
+                .filter_map(|message| message.lines().next())
+                .map(|subject| format!("- {}", subject))
+                .collect::<Vec<String>>();
+            format!(
+                "{}\n\n{}",
+                first.lines().next().unwrap_or_default(),
+                subjects.join("\n")
+            )
+        }
+        (None, []) => String::new(),
+    };
+
+    if let Some(body) = body
+        .map(|body| body.to_string())
+        .or_else(|| get_pull_request_template(git))
+    {
+        content = format!("{}\n\n{}", content, body.trim());
+    }
+
+    let message = compose_markdown(
+        "PULLREQ_EDITMSG",
+        &content,
+        &[
+            "Please enter the title and the description of the pull request. The first".to_string(),
+            "line will be the title and the rest will be the description. An empty title"
+                .to_string(),
+            "aborts the pull request.".to_string(),
+            "".to_string(),
+            format!("{} -> {}", source_branch, target_branch),
+        ],
+    )?;
+
+    let mut lines = message.splitn(2, '\n');
+    let title = lines.next().unwrap_or_default().trim().to_string();
+    if title.is_empty() {
+        bail!("aborting pull request due to empty title")
+    }
+    let body = lines
+        .next()
+        .map(|body| body.trim().to_string())
+        .filter(|body| !body.is_empty());
+
+    Ok((title, body))
+}
+
+/// Candidates of the comment char when `core.commentChar` is unset or `auto`,
+/// in the order git tries them but without `#`, which starts Markdown headings.
+const COMMENT_CHARS: &str = ";@!$%^&|:";
+
+/// Pick the comment char for Markdown content, which is `core.commentChar` when
+/// it is set to a char, or else the first of `COMMENT_CHARS` that no line of
+/// the content starts with.
+fn pick_comment_char(config: Option<&str>, content: &str) -> char {
+    config
+        .filter(|config| *config != "auto")
+        .and_then(|config| config.chars().next())
+        .or_else(|| {
+            COMMENT_CHARS
+                .chars()
+                .find(|c| !content.lines().any(|line| line.starts_with(*c)))
+        })
+        .unwrap_or(';')
+}
+
+/// Open the editor with `content` followed by `instruction` as comments, and
+/// return what is entered without the comments.
+fn compose_markdown(filename: &str, content: &str, instruction: &[String]) -> Result<String> {
+    let config = git::get_config("core.commentChar").ok();
+    let comment_char = pick_comment_char(config.as_deref(), content);
+
+    let mut lines = vec!["".to_string()];
+    lines.extend_from_slice(instruction);
+    lines.extend_from_slice(&[
+        "".to_string(),
+        format!(
+            "Lines starting with '{}' will be ignored. Unless core.commentChar is set",
+            comment_char
+        ),
+        "to a char, '#' is not used since Markdown headings start with it.".to_string(),
+    ]);
+    let instruction = lines
+        .iter()
+        .map(|line| format!("{} {}", comment_char, line).trim_end().to_string())
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let content = if content.is_empty() {
+        instruction
+    } else {
+        format!("{}\n\n{}", content, instruction)
+    };
+    let edited = utils::edit_text(filename, &format!("{}\n", content))?;
+    Ok(utils::strip_comments(&edited, comment_char))
+}
+
+/// Open the editor to compose a comment or a review, where `subject` is what
+/// to enter, e.g. "the comment on pull request #1".
+fn compose_comment(filename: &str, subject: &str) -> Result<String> {
+    compose_markdown(
+        filename,
+        "",
+        &[format!(
+            "Please enter {}. An empty content aborts it.",
+            subject
+        )],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::git::test_utils::{commit, init_repo};
+    use crate::github::fixtures::{get_repo, repo};
+    use git2::Repository;
+    use mockito::mock;
+
+    fn get_base() -> GitUrl {
+        GitUrl::parse("https://github.com/owner/repo.git").unwrap()
+    }
+
+    #[test]
+    fn test_pick_comment_char() {
+        assert_eq!(pick_comment_char(None, "title\n\n## heading"), ';');
+        assert_eq!(pick_comment_char(Some("auto"), "title\n; quote"), '@');
+        assert_eq!(pick_comment_char(Some("#"), "title\n\n## heading"), '#');
+        assert_eq!(pick_comment_char(Some("%"), ""), '%');
+    }
+
+    #[test]
+    fn test_ensure_worktree_clean() {
+        let (dir, repo) = init_repo();
+        fs::write(dir.path().join("a.txt"), "a").unwrap();
+        commit(&repo, "first");
+        let git = Git::open(dir.path()).unwrap();
+        assert!(ensure_worktree_clean(&git, false).is_ok());
+
+        fs::write(dir.path().join("a.txt"), "changed").unwrap();
+        assert!(ensure_worktree_clean(&git, false).is_err());
+        assert!(ensure_worktree_clean(&git, true).is_ok());
+    }
+
+    #[test]
+    fn test_checkout_ref() {
+        let (remote_dir, remote) = init_repo();
+        fs::write(remote_dir.path().join("a.txt"), "a").unwrap();
+        let first = commit(&remote, "first");
+        fs::write(remote_dir.path().join("a.txt"), "b").unwrap();
+        let second = commit(&remote, "second");
+        remote
+            .reference("refs/pull/1/head", second, false, "")
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::clone(remote_dir.path().to_str().unwrap(), dir.path()).unwrap();
+        let git = Git::open(dir.path()).unwrap();
+
+        checkout_ref(&git, "origin", "refs/pull/1/head", "pr/1", false).unwrap();
+        assert_eq!(git.get_current_branch().unwrap(), "pr/1");
+        assert_eq!(git.get_rev("pr/1").unwrap(), second.to_string());
+        assert_eq!(
+            git.get_config("branch.pr/1.merge").unwrap(),
+            "refs/pull/1/head"
+        );
+
+        // commit onto the first commit, with the same files as the second one
+        repo.branch("feature", &repo.find_commit(first).unwrap(), false)
+            .unwrap();
+        repo.set_head("refs/heads/feature").unwrap();
+        let diverged = commit(&repo, "diverged");
+        repo.set_head("refs/heads/pr/1").unwrap();
+
+        let err = checkout_ref(&git, "origin", "refs/pull/1/head", "feature", false).unwrap_err();
+        assert!(err.to_string().contains("diverged"));
+        assert_eq!(git.get_rev("feature").unwrap(), diverged.to_string());
+
+        checkout_ref(&git, "origin", "refs/pull/1/head", "feature", true).unwrap();
+        assert_eq!(git.get_current_branch().unwrap(), "feature");
+        assert_eq!(git.get_rev("feature").unwrap(), second.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_get_push_target() {
+        let (remote_dir, remote) = init_repo();
+        fs::write(remote_dir.path().join("a.txt"), "a").unwrap();
+        let first = commit(&remote, "first");
+        remote
+            .branch("feature-x", &remote.find_commit(first).unwrap(), false)
+            .unwrap();
+        remote
+            .reference("refs/pull/1/head", first, false, "")
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::clone(remote_dir.path().to_str().unwrap(), dir.path()).unwrap();
+        let git = Git::open(dir.path()).unwrap();
+
+        // `fix` tracks `origin/feature-x` with a new commit
+        repo.branch("fix", &repo.find_commit(first).unwrap(), false)
+            .unwrap();
+        git.set_config("branch.fix.remote", "origin").unwrap();
+        git.set_config("branch.fix.merge", "refs/heads/feature-x")
+            .unwrap();
+        repo.set_head("refs/heads/fix").unwrap();
+        fs::write(dir.path().join("a.txt"), "b").unwrap();
+        commit(&repo, "second");
+
+        let target = get_push_target(&git, &get_repo(), &get_base(), "fix")
+            .await
+            .unwrap();
+        assert_eq!(
+            target,
+            PushTarget {
+                remote: "origin".to_string(),
+                branch: "feature-x".to_string(),
+                tracked: true,
+                pending: Some("fix has new commits not pushed to origin/feature-x".to_string()),
+            }
+        );
+
+        // checked out pull requests are pushed as new branches
+        checkout_ref(&git, "origin", "refs/pull/1/head", "pr/1", true).unwrap();
+        git.set_config("remote.pushDefault", "origin").unwrap();
+        let target = get_push_target(&git, &get_repo(), &get_base(), "pr/1")
+            .await
+            .unwrap();
+        assert_eq!(target.branch, "pr/1");
+        assert!(!target.tracked);
+        assert!(target.pending.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_get_push_remote() {
+        let (dir, _repo) = init_repo();
+        let git = Git::open(dir.path()).unwrap();
+        git.add_remote("origin", "https://github.com/owner/repo.git")
+            .unwrap();
+        git.add_remote("other", "https://github.com/someone/other.git")
+            .unwrap();
+        git.add_remote("fork", "https://github.com/someone/repo.git")
+            .unwrap();
+
+        let m = mock("GET", "/repos/owner/repo")
+            .with_body(repo(true))
+            .create();
+        let remote = get_push_remote(&git, &get_repo(), &get_base(), "fix")
+            .await
+            .unwrap();
+        assert_eq!(remote, "origin");
+        m.assert();
+        drop(m);
+
+        let _m = mock("GET", "/repos/owner/repo")
+            .with_body(repo(false))
+            .create();
+        let remote = get_push_remote(&git, &get_repo(), &get_base(), "fix")
+            .await
+            .unwrap();
+        assert_eq!(remote, "fork");
+
+        git.set_config("branch.fix.pushRemote", "other").unwrap();
+        let remote = get_push_remote(&git, &get_repo(), &get_base(), "fix")
+            .await
+            .unwrap();
+        assert_eq!(remote, "other");
+
+        // no fork is added
+        let (dir, _repo) = init_repo();
+        let git = Git::open(dir.path()).unwrap();
+        git.add_remote("origin", "https://github.com/owner/repo.git")
+            .unwrap();
+        let err = get_push_remote(&git, &get_repo(), &get_base(), "fix")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no permission to push"));
+    }
+}
@@ -18,7 +18,7 @@ impl<'a> Command<'a> {
     pub fn new(matches: &'a ArgMatches<'a>) -> Option<Self> {
         match matches.subcommand() {
             (command, Some(arg_matches)) => Some(Command {
-                command: command,
+                command,
                 matches: arg_matches,
             }),
             _ => None,
@@ -28,7 +28,10 @@ impl<'a> Command<'a> {
     pub async fn run(&self) -> Result<()> {
         match self.command {
             "add" => self.add().await,
-            _ => Ok(println!("{}", self.matches.usage())),
+            _ => {
+                println!("{}", self.matches.usage());
+                Ok(())
+            }
         }
     }
 
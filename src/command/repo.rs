@@ -0,0 +1,381 @@
+use std::env;
+use std::fs;
+
+use anyhow::{anyhow, bail, Result};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use colored::Colorize;
+use git_url_parse::{GitUrl, Scheme};
+
+use crate::git::{self, Git};
+use crate::output::{Output, OutputFormat};
+use crate::profile::load_profile;
+use crate::repository::{
+    get_forge, get_host, get_remote_name, get_remote_url, get_repo, parse_repo, split_host,
+    CreateRepositoryOpt,
+};
+use crate::structs::Repo;
+
+#[inline]
+fn protocol_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("protocol")
+        .long("protocol")
+        .short("p")
+        .takes_value(true)
+        .possible_values(&["https", "ssh"])
+        .help("protocol of the remote url (default: yag.protocol config or https)")
+}
+
+#[inline]
+pub fn sub_command<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("repo")
+        .about("Manage repositories")
+        .subcommand(
+            SubCommand::with_name("view")
+                .about("Show repository with its stars and counts of open issues and pull requests")
+                .arg(
+                    Arg::with_name("name")
+                        .takes_value(true)
+                        .help("owner/name or host/owner/name (default: current repository)"),
+                )
+                .arg(
+                    Arg::with_name("web")
+                        .long("web")
+                        .short("w")
+                        .help("open the repository in the browser"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("clone")
+                .about("Clone repository")
+                .arg(
+                    Arg::with_name("name")
+                        .required(true)
+                        .takes_value(true)
+                        .help("owner/name or host/owner/name"),
+                )
+                .arg(
+                    Arg::with_name("dir")
+                        .takes_value(true)
+                        .help("directory to clone into"),
+                )
+                .arg(protocol_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("create")
+                .about("Create repository")
+                .arg(
+                    Arg::with_name("name")
+                        .required(true)
+                        .takes_value(true)
+                        .help("name, owner/name or host/owner/name, where owner is an organization or group"),
+                )
+                .arg(
+                    Arg::with_name("description")
+                        .long("description")
+                        .short("d")
+                        .takes_value(true),
+                )
+                .arg(Arg::with_name("private").long("private"))
+                .arg(
+                    Arg::with_name("internal")
+                        .long("internal")
+                        .conflicts_with("private")
+                        .help("visible to all signed-in users"),
+                )
+                .arg(
+                    Arg::with_name("push")
+                        .long("push")
+                        .help("add the repository as a remote and push current branch"),
+                )
+                .arg(
+                    Arg::with_name("remote-name")
+                        .long("remote-name")
+                        .takes_value(true)
+                        .default_value("origin")
+                        .requires("push")
+                        .help("name of the remote to add"),
+                )
+                .arg(protocol_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("browse")
+                .about("Open current branch, or the file of it, in the browser")
+                .arg(
+                    Arg::with_name("path")
+                        .takes_value(true)
+                        .help("file or directory, optionally followed by :line"),
+                )
+                .arg(
+                    Arg::with_name("branch")
+                        .long("branch")
+                        .short("b")
+                        .takes_value(true)
+                        .help("branch or commit to browse (default: current branch)"),
+                )
+                .arg(
+                    Arg::with_name("no-browser")
+                        .long("no-browser")
+                        .short("n")
+                        .help("print the url instead of opening it"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("fork")
+                .about("Fork current repository and add the fork as a remote")
+                .arg(
+                    Arg::with_name("org")
+                        .long("org")
+                        .takes_value(true)
+                        .help("organization or group to fork into (default: yourself)"),
+                )
+                .arg(
+                    Arg::with_name("remote-name")
+                        .long("remote-name")
+                        .takes_value(true)
+                        .default_value("fork")
+                        .help("name of the remote of the fork"),
+                )
+                .arg(
+                    Arg::with_name("no-remote")
+                        .long("no-remote")
+                        .help("do not add the fork as a remote"),
+                ),
+        )
+}
+
+pub struct Command<'a> {
+    command: &'a str,
+    matches: &'a ArgMatches<'a>,
+}
+
+impl<'a> Command<'a> {
+    pub fn new(matches: &'a ArgMatches<'a>) -> Option<Self> {
+        match matches.subcommand() {
+            (command, Some(arg_matches)) => Some(Command {
+                command,
+                matches: arg_matches,
+            }),
+            _ => {
+                println!("{}", matches.usage());
+                None
+            }
+        }
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        match self.command {
+            "view" => self.view().await,
+            "clone" => self.clone_repo().await,
+            "create" => self.create().await,
+            "browse" => self.browse().await,
+            "fork" => self.fork().await,
+            _ => {
+                println!("{}", self.matches.usage());
+                Ok(())
+            }
+        }
+    }
+
+    fn output(&self) -> OutputFormat {
+        OutputFormat::from(self.matches)
+    }
+
+    fn print<T: Output>(&self, data: &T) -> Result<()> {
+        self.output().print(data)
+    }
+
+    /// Url of `--protocol`, or `yag.protocol` config, to clone the repository.
+    fn get_clone_url(&self, repo: Repo) -> Result<String> {
+        let protocol = match self.matches.value_of("protocol") {
+            Some(protocol) => protocol.to_string(),
+            None => git::get_config("yag.protocol").unwrap_or("https".to_string()),
+        };
+        match protocol.as_str() {
+            "https" => Ok(repo.clone_url),
+            "ssh" => match repo.ssh_url {
+                Some(ssh_url) => Ok(ssh_url),
+                None => bail!("no ssh url for {}", repo.name),
+            },
+            _ => bail!(
+                "invalid protocol: {}, which should be https or ssh",
+                protocol
+            ),
+        }
+    }
+
+    async fn view(&self) -> Result<()> {
+        let profile = load_profile().await?;
+        let url = match self.matches.value_of("name") {
+            Some(repo) => parse_repo(&profile, repo)?,
+            None => get_remote_url(&profile)?,
+        };
+        let repo = get_forge(&get_host(&profile, &url)?)
+            .await?
+            .get_repository(&url.fullname)
+            .await?;
+
+        if self.matches.is_present("web") {
+            open::that(repo.url)?;
+            return Ok(());
+        }
+        self.print(&repo)
+    }
+
+    async fn clone_repo(&self) -> Result<()> {
+        let profile = load_profile().await?;
+        let url = parse_repo(&profile, self.matches.value_of("name").unwrap())?;
+        let repo = get_forge(&get_host(&profile, &url)?)
+            .await?
+            .get_repository(&url.fullname)
+            .await?;
+
+        git::clone(&self.get_clone_url(repo)?, self.matches.value_of("dir"))
+    }
+
+    async fn create(&self) -> Result<()> {
+        let (host, path) = split_host(
+            &load_profile().await?,
+            self.matches.value_of("name").unwrap(),
+        );
+        let remote = self.matches.value_of("remote-name").unwrap();
+
+        // check before creating so that it is not left half done
+        let git = if self.matches.is_present("push") {
+            let git = Git::current()?;
+            if git.get_remotes()?.iter().any(|name| name == remote) {
+                bail!("remote {} already exists", remote)
+            }
+            Some(git)
+        } else {
+            None
+        };
+
+        let repo = get_forge(&host)
+            .await?
+            .create_repository(CreateRepositoryOpt::new(&path, self.matches))
+            .await?;
+        self.print(&repo)?;
+
+        if let Some(git) = git {
+            let url = self.get_clone_url(repo)?;
+            git.add_remote(remote, &url)?;
+            if self.output() == OutputFormat::Human {
+                println!("added remote {} for {}", remote.cyan().bold(), url);
+            }
+            git.push(remote, &git.get_current_branch()?, true)?;
+        }
+        Ok(())
+    }
+
+    async fn browse(&self) -> Result<()> {
+        let git = Git::current()?;
+        let git_ref = match self.matches.value_of("branch") {
+            Some(branch) => branch.to_string(),
+            None => match git.get_current_branch()?.as_str() {
+                "HEAD" => git.get_rev("HEAD")?,
+                branch => branch.to_string(),
+            },
+        };
+
+        let (path, line) = match self.matches.value_of("path") {
+            Some(path) => parse_path(path),
+            None => (None, None),
+        };
+        let path = match path {
+            Some(path) => get_repo_path(&git, path)?,
+            None => String::new(),
+        };
+
+        let profile = load_profile().await?;
+        let url = get_remote_url(&profile)?;
+        let web_url = get_forge(&get_host(&profile, &url)?).await?.get_web_url(
+            &url.fullname,
+            &git_ref,
+            &path,
+            line,
+        );
+
+        if self.matches.is_present("no-browser") {
+            println!("{}", web_url);
+        } else {
+            open::that(web_url)?;
+        }
+        Ok(())
+    }
+
+    async fn fork(&self) -> Result<()> {
+        let fork = get_repo()
+            .await?
+            .fork_repository(self.matches.value_of("org"))
+            .await?;
+        self.print(&fork)?;
+
+        // `--repo` works outside of repositories, where no remote is added
+        let git = match Git::current() {
+            Ok(git) if !self.matches.is_present("no-remote") => git,
+            _ => return Ok(()),
+        };
+
+        // use the same protocol as the remote of the parent
+        let parent_url = GitUrl::parse(&git.get_remote_url(&get_remote_name(&git)?)?)?;
+        let url = match (parent_url.scheme, fork.ssh_url) {
+            (Scheme::Ssh, Some(ssh_url)) | (Scheme::GitSsh, Some(ssh_url)) => ssh_url,
+            _ => fork.clone_url,
+        };
+        let remote = self.matches.value_of("remote-name").unwrap();
+        git.add_remote(remote, &url)?;
+
+        if self.output() == OutputFormat::Human {
+            println!("added remote {} for {}", remote.cyan().bold(), url);
+        }
+        Ok(())
+    }
+}
+
+/// Split `path[:line]` into the path and the line.
+fn parse_path(arg: &str) -> (Option<&str>, Option<usize>) {
+    match arg.rsplit_once(':') {
+        Some((path, line)) => match line.parse::<usize>() {
+            Ok(line) => (Some(path).filter(|path| !path.is_empty()), Some(line)),
+            Err(_) => (Some(arg), None),
+        },
+        None => (Some(arg), None),
+    }
+}
+
+/// Path relative to the root of the repository, which ends with `/` for
+/// directories.
+fn get_repo_path(git: &Git, path: &str) -> Result<String> {
+    let path = fs::canonicalize(env::current_dir()?.join(path))
+        .map_err(|err| anyhow!("{}: {}", path, err))?;
+    let toplevel = fs::canonicalize(git.get_toplevel()?)?;
+    let relative = path
+        .strip_prefix(&toplevel)
+        .map_err(|_| anyhow!("{} is outside of current repository", path.display()))?;
+
+    let mut parts = relative
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>();
+    if path.is_dir() && !parts.is_empty() {
+        parts.push("".into());
+    }
+    Ok(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(parse_path("src/main.rs"), (Some("src/main.rs"), None));
+        assert_eq!(
+            parse_path("src/main.rs:10"),
+            (Some("src/main.rs"), Some(10))
+        );
+        assert_eq!(parse_path(":10"), (None, Some(10)));
+        assert_eq!(parse_path("a:b"), (Some("a:b"), None));
+        assert_eq!(parse_path("a:12"), (Some("a"), Some(12)));
+    }
+}
@@ -0,0 +1,83 @@
+use colored::*;
+
+/// Decorate a unified diff with terminal colors as `git diff` does.
+pub fn colorize(diff: &str) -> String {
+    let mut lines = vec![];
+    let mut in_header = false;
+
+    for line in diff.lines() {
+        if line.starts_with("diff ") {
+            in_header = true;
+        } else if line.starts_with("@@") {
+            in_header = false;
+        }
+
+        let colored = if in_header {
+            line.bold().to_string()
+        } else if line.starts_with("@@") {
+            line.cyan().to_string()
+        } else if line.starts_with('+') {
+            line.green().to_string()
+        } else if line.starts_with('-') {
+            line.red().to_string()
+        } else {
+            line.to_string()
+        };
+        lines.push(colored);
+    }
+
+    lines.join("\n")
+}
+
+/// Count added and deleted lines in hunks of a diff.
+pub fn count_changes(diff: &str) -> (u64, u64) {
+    let mut in_hunk = false;
+    let mut additions = 0;
+    let mut deletions = 0;
+
+    for line in diff.lines() {
+        if line.starts_with("@@") {
+            in_hunk = true;
+        } else if line.starts_with("diff ") {
+            in_hunk = false;
+        } else if in_hunk && line.starts_with('+') {
+            additions += 1;
+        } else if in_hunk && line.starts_with('-') {
+            deletions += 1;
+        }
+    }
+
+    (additions, deletions)
+}
+
+/// Build the header of a file in a unified diff, where a missing path means
+/// the file is added or deleted.
+pub fn file_header(old_path: Option<&str>, new_path: Option<&str>) -> String {
+    let a = old_path.or(new_path).unwrap_or_default();
+    let b = new_path.or(old_path).unwrap_or_default();
+    format!(
+        "diff --git a/{} b/{}\n--- {}\n+++ {}\n",
+        a,
+        b,
+        old_path.map_or("/dev/null".to_string(), |path| format!("a/{}", path)),
+        new_path.map_or("/dev/null".to_string(), |path| format!("b/{}", path)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_changes() {
+        let diff = format!(
+            "{}@@ -1,2 +1,2 @@\n-a\n+b\n+c\n--- not a header\n c\n",
+            file_header(Some("src/main.rs"), Some("src/main.rs"))
+        );
+        assert_eq!(count_changes(&diff), (2, 2));
+        assert_eq!(
+            file_header(None, Some("new.rs")),
+            "diff --git a/new.rs b/new.rs\n--- /dev/null\n+++ b/new.rs\n"
+        );
+    }
+}
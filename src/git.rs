@@ -0,0 +1,486 @@
+use anyhow::{anyhow, bail, Result};
+use git2::{BranchType, Config, ConfigLevel, ErrorCode, Oid, Repository, Sort, StatusOptions};
+use log::debug;
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Local git repository, accessed with libgit2 where possible and with the
+/// git CLI otherwise.
+pub struct Git {
+    path: PathBuf,
+    /// `None` if libgit2 cannot open the repository, e.g. with extensions it
+    /// does not support yet.
+    repo: Option<Repository>,
+}
+
+impl Git {
+    /// Open the repository containing the path.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        match Repository::discover(&path) {
+            Ok(repo) => Ok(Git {
+                path,
+                repo: Some(repo),
+            }),
+            Err(err) => {
+                debug!("fallback to git CLI: {}", err);
+                let git = Git::with_cli(path);
+                git.run(&["rev-parse", "--git-dir"])
+                    .map_err(|_| anyhow!("not a git repository: {}", err.message()))?;
+                Ok(git)
+            }
+        }
+    }
+
+    /// Open the repository of the working directory.
+    pub fn current() -> Result<Self> {
+        Self::open(env::current_dir()?)
+    }
+
+    fn with_cli(path: PathBuf) -> Self {
+        Git { path, repo: None }
+    }
+
+    /// Run git with the arguments in the repository and return its stdout.
+    pub fn run(&self, args: &[&str]) -> Result<String> {
+        debug!("executing: git {}", args.join(" "));
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&self.path)
+            .args(args)
+            .output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            debug!("stderr: {}", stderr);
+            bail!(
+                "failed to execute git {}: {}",
+                args.join(" "),
+                stderr.trim()
+            )
+        }
+
+        let result = String::from_utf8(output.stdout)?;
+        debug!("result: {}", result);
+        Ok(result)
+    }
+
+    fn resolve(repo: &Repository, rev: &str) -> Result<Oid> {
+        Ok(repo.revparse_single(rev)?.peel_to_commit()?.id())
+    }
+
+    /// Name of the checked out branch, or `HEAD` if it is detached.
+    pub fn get_current_branch(&self) -> Result<String> {
+        match &self.repo {
+            Some(repo) => {
+                let head = repo.find_reference("HEAD")?;
+                Ok(match head.symbolic_target() {
+                    Some(target) => target.trim_start_matches("refs/heads/").to_string(),
+                    None => "HEAD".to_string(),
+                })
+            }
+            None => {
+                let branch = self.run(&["branch", "--show-current"])?;
+                match branch.trim() {
+                    "" => Ok("HEAD".to_string()),
+                    branch => Ok(branch.to_string()),
+                }
+            }
+        }
+    }
+
+    /// Commit id which the revision points to.
+    pub fn get_rev(&self, rev: &str) -> Result<String> {
+        match &self.repo {
+            Some(repo) => Ok(Self::resolve(repo, rev)?.to_string()),
+            None => self
+                .run(&["rev-parse", "--verify", &format!("{}^{{commit}}", rev)])
+                .map(|x| x.trim().to_string()),
+        }
+    }
+
+    pub fn get_latest_commit_message(&self) -> Result<String> {
+        match &self.repo {
+            Some(repo) => {
+                let commit = repo.head()?.peel_to_commit()?;
+                Ok(commit.message().unwrap_or_default().trim().to_string())
+            }
+            None => self
+                .run(&["log", "--max-count=1", "--format=%B", "HEAD"])
+                .map(|x| x.trim().to_string()),
+        }
+    }
+
+    /// Full messages of commits reachable from `to` but not from `from`, from
+    /// the oldest to the newest.
+    pub fn get_commit_messages(&self, from: &str, to: &str) -> Result<Vec<String>> {
+        let messages = match &self.repo {
+            Some(repo) => {
+                let mut walk = repo.revwalk()?;
+                walk.push(Self::resolve(repo, to)?)?;
+                walk.hide(Self::resolve(repo, from)?)?;
+                walk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+
+                let mut messages = vec![];
+                for id in walk {
+                    let commit = repo.find_commit(id?)?;
+                    messages.push(commit.message().unwrap_or_default().trim().to_string());
+                }
+                messages
+            }
+            None => self
+                .run(&[
+                    "log",
+                    "--reverse",
+                    "--format=%B%x00",
+                    &format!("{}..{}", from, to),
+                ])?
+                .split('\0')
+                .map(|message| message.trim().to_string())
+                .collect(),
+        };
+        Ok(messages
+            .into_iter()
+            .filter(|message| !message.is_empty())
+            .collect())
+    }
+
+    pub fn get_config(&self, key: &str) -> Result<String> {
+        match &self.repo {
+            Some(repo) => Ok(repo.config()?.snapshot()?.get_string(key)?),
+            None => self
+                .run(&["config", "--get", key])
+                .map(|x| x.trim().to_string()),
+        }
+    }
+
+    /// Set the config of the repository rather than the global one.
+    pub fn set_config(&self, key: &str, value: &str) -> Result<()> {
+        match &self.repo {
+            Some(repo) => Ok(repo
+                .config()?
+                .open_level(ConfigLevel::Local)?
+                .set_str(key, value)?),
+            None => self.run(&["config", key, value]).map(|_| ()),
+        }
+    }
+
+    pub fn get_remotes(&self) -> Result<Vec<String>> {
+        match &self.repo {
+            Some(repo) => Ok(repo
+                .remotes()?
+                .iter()
+                .flatten()
+                .map(|remote| remote.to_string())
+                .collect()),
+            None => Ok(self
+                .run(&["remote"])?
+                .lines()
+                .map(|remote| remote.to_string())
+                .collect()),
+        }
+    }
+
+    pub fn get_remote_url(&self, remote: &str) -> Result<String> {
+        match &self.repo {
+            Some(repo) => repo
+                .find_remote(remote)?
+                .url()
+                .map(|url| url.to_string())
+                .ok_or_else(|| anyhow!("invalid url of remote {}", remote)),
+            None => self
+                .run(&["remote", "get-url", remote])
+                .map(|x| x.trim().to_string()),
+        }
+    }
+
+    pub fn add_remote(&self, remote: &str, url: &str) -> Result<()> {
+        match &self.repo {
+            Some(repo) => repo.remote(remote, url).map(|_| ()).map_err(Into::into),
+            None => self.run(&["remote", "add", remote, url]).map(|_| ()),
+        }
+    }
+
+    /// Remote-tracking branch of the local branch, e.g. `origin/master`.
+    pub fn get_upstream(&self, branch: &str) -> Result<Option<String>> {
+        match &self.repo {
+            Some(repo) => match repo.find_branch(branch, BranchType::Local)?.upstream() {
+                Ok(upstream) => Ok(upstream.name()?.map(|name| name.to_string())),
+                Err(err) if err.code() == ErrorCode::NotFound => Ok(None),
+                Err(err) => Err(err.into()),
+            },
+            None => {
+                let upstream = self.run(&[
+                    "for-each-ref",
+                    "--format=%(upstream:short)",
+                    &format!("refs/heads/{}", branch),
+                ])?;
+                Ok(Some(upstream.trim().to_string()).filter(|upstream| !upstream.is_empty()))
+            }
+        }
+    }
+
+    /// Numbers of commits which are only in `local` and only in `upstream`.
+    pub fn get_ahead_behind(&self, local: &str, upstream: &str) -> Result<(usize, usize)> {
+        match &self.repo {
+            Some(repo) => Ok(repo
+                .graph_ahead_behind(Self::resolve(repo, local)?, Self::resolve(repo, upstream)?)?),
+            None => {
+                let counts = self.run(&[
+                    "rev-list",
+                    "--left-right",
+                    "--count",
+                    &format!("{}...{}", local, upstream),
+                ])?;
+                let mut counts = counts.split_whitespace().map(|n| n.parse::<usize>());
+                match (counts.next(), counts.next()) {
+                    (Some(Ok(ahead)), Some(Ok(behind))) => Ok((ahead, behind)),
+                    _ => bail!("failed to count commits between {} and {}", local, upstream),
+                }
+            }
+        }
+    }
+
+    pub fn is_ancestor(&self, ancestor: &str, rev: &str) -> bool {
+        match &self.repo {
+            Some(repo) => match (Self::resolve(repo, ancestor), Self::resolve(repo, rev)) {
+                (Ok(ancestor), Ok(rev)) => {
+                    ancestor == rev || repo.graph_descendant_of(rev, ancestor).unwrap_or(false)
+                }
+                _ => false,
+            },
+            None => self
+                .run(&["merge-base", "--is-ancestor", ancestor, rev])
+                .is_ok(),
+        }
+    }
+
+    pub fn branch_exists(&self, branch: &str) -> bool {
+        match &self.repo {
+            Some(repo) => repo.find_branch(branch, BranchType::Local).is_ok(),
+            None => self
+                .run(&[
+                    "rev-parse",
+                    "--verify",
+                    "--quiet",
+                    &format!("refs/heads/{}", branch),
+                ])
+                .is_ok(),
+        }
+    }
+
+    /// Whether tracked files have no changes, either staged or not.
+    pub fn is_worktree_clean(&self) -> Result<bool> {
+        match &self.repo {
+            Some(repo) => {
+                let mut opts = StatusOptions::new();
+                opts.include_untracked(false).include_ignored(false);
+                Ok(repo.statuses(Some(&mut opts))?.is_empty())
+            }
+            None => self
+                .run(&["status", "--porcelain", "--untracked-files=no"])
+                .map(|x| x.trim().is_empty()),
+        }
+    }
+
+    pub fn get_git_dir(&self) -> Result<PathBuf> {
+        match &self.repo {
+            Some(repo) => Ok(repo.path().to_path_buf()),
+            None => self
+                .run(&["rev-parse", "--absolute-git-dir"])
+                .map(|x| PathBuf::from(x.trim())),
+        }
+    }
+
+    /// Root directory of the working tree.
+    pub fn get_toplevel(&self) -> Result<PathBuf> {
+        match &self.repo {
+            Some(repo) => repo
+                .workdir()
+                .map(|dir| dir.to_path_buf())
+                .ok_or_else(|| anyhow!("bare repository has no working tree")),
+            None => self
+                .run(&["rev-parse", "--show-toplevel"])
+                .map(|x| PathBuf::from(x.trim())),
+        }
+    }
+
+    /// Push the refspec like `branch` or `local:remote` to the remote. The git
+    /// CLI is used so that credential helpers and hooks work as usual and the
+    /// progress is shown.
+    pub fn push(&self, remote: &str, refspec: &str, set_upstream: bool) -> Result<()> {
+        let mut args = vec!["push"];
+        if set_upstream {
+            args.push("--set-upstream");
+        }
+        args.extend(&[remote, refspec]);
+
+        debug!("executing: git {}", args.join(" "));
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(&self.path)
+            .args(&args)
+            .status()?;
+        if !status.success() {
+            bail!("failed to push {} to {}", refspec, remote)
+        }
+        Ok(())
+    }
+}
+
+/// Clone the repository into the directory, or the one named after it, with
+/// the progress shown.
+pub fn clone(url: &str, dir: Option<&str>) -> Result<()> {
+    let mut args = vec!["clone", url];
+    args.extend(dir);
+
+    debug!("executing: git {}", args.join(" "));
+    let status = Command::new("git").args(&args).status()?;
+    if !status.success() {
+        bail!("failed to clone {}", url)
+    }
+    Ok(())
+}
+
+/// Config of the current repository, or the global one outside of
+/// repositories.
+pub fn get_config(key: &str) -> Result<String> {
+    match Git::current() {
+        Ok(git) => git.get_config(key),
+        Err(_) => Ok(Config::open_default()?.snapshot()?.get_string(key)?),
+    }
+}
+
+/// Temporary repositories for tests.
+#[cfg(test)]
+pub mod test_utils {
+    use git2::{Oid, Repository, Signature};
+    use tempfile::TempDir;
+
+    /// Empty repository with `main` as the current branch.
+    pub fn init_repo() -> (TempDir, Repository) {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        (dir, repo)
+    }
+
+    /// Commit all files of the worktree onto `HEAD`.
+    pub fn commit(repo: &Repository, message: &str) -> Oid {
+        let signature = Signature::now("someone", "someone@example.com").unwrap();
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents = parent.iter().collect::<Vec<_>>();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_utils::{commit, init_repo};
+    use super::*;
+
+    use std::fs;
+
+    /// Both implementations over the same repository.
+    fn open_all(path: &Path) -> Vec<Git> {
+        vec![Git::open(path).unwrap(), Git::with_cli(path.to_path_buf())]
+    }
+
+    #[test]
+    fn test_branch_and_commits() {
+        let (dir, repo) = init_repo();
+        fs::write(dir.path().join("a.txt"), "a").unwrap();
+        let first = commit(&repo, "first\n\ndetails\n");
+        fs::write(dir.path().join("b.txt"), "b").unwrap();
+        let second = commit(&repo, "second");
+
+        for git in open_all(dir.path()) {
+            assert_eq!(git.get_current_branch().unwrap(), "main");
+            assert_eq!(git.get_rev("main").unwrap(), second.to_string());
+            assert_eq!(git.get_rev("HEAD~").unwrap(), first.to_string());
+            assert!(git.get_rev("missing").is_err());
+            assert_eq!(git.get_latest_commit_message().unwrap(), "second");
+            assert_eq!(
+                git.get_commit_messages(&first.to_string(), "main").unwrap(),
+                vec!["second"]
+            );
+            assert_eq!(
+                git.get_ahead_behind("main", &first.to_string()).unwrap(),
+                (1, 0)
+            );
+            assert!(git.is_ancestor(&first.to_string(), "main"));
+            assert!(!git.is_ancestor("main", &first.to_string()));
+            assert!(git.branch_exists("main"));
+            assert!(!git.branch_exists("other"));
+            assert!(git.is_worktree_clean().unwrap());
+        }
+
+        fs::write(dir.path().join("a.txt"), "changed").unwrap();
+        repo.set_head_detached(first).unwrap();
+        for git in open_all(dir.path()) {
+            assert_eq!(git.get_current_branch().unwrap(), "HEAD");
+            assert!(!git.is_worktree_clean().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_config() {
+        let (dir, _repo) = init_repo();
+
+        for git in open_all(dir.path()) {
+            git.set_config("yag.pr.target", "release 1.0").unwrap();
+            assert_eq!(git.get_config("yag.pr.target").unwrap(), "release 1.0");
+            assert!(git.get_config("yag.missing").is_err());
+        }
+    }
+
+    #[test]
+    fn test_remotes_and_push() {
+        let (dir, repo) = init_repo();
+        commit(&repo, "first");
+        let remote_dir = tempfile::tempdir().unwrap();
+        let remote_path = remote_dir.path().join("remote repo.git");
+        Repository::init_bare(&remote_path).unwrap();
+        repo.remote("origin", remote_path.to_str().unwrap())
+            .unwrap();
+
+        let git = Git::open(dir.path()).unwrap();
+        assert_eq!(git.get_upstream("main").unwrap(), None);
+        git.push("origin", "main", true).unwrap();
+        commit(&repo, "second");
+
+        Git::with_cli(dir.path().to_path_buf())
+            .add_remote("fork", "git@example.com:someone/repo.git")
+            .unwrap();
+        for git in open_all(dir.path()) {
+            assert_eq!(git.get_remotes().unwrap(), vec!["fork", "origin"]);
+            assert_eq!(
+                git.get_remote_url("origin").unwrap(),
+                remote_path.to_str().unwrap()
+            );
+            assert!(git.get_remote_url("upstream").is_err());
+            assert_eq!(
+                git.get_upstream("main").unwrap().as_deref(),
+                Some("origin/main")
+            );
+            assert_eq!(git.get_ahead_behind("main", "origin/main").unwrap(), (1, 0));
+        }
+    }
+}
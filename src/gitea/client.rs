@@ -0,0 +1,39 @@
+use anyhow::Result;
+use log::debug;
+use reqwest::header::HeaderMap;
+use reqwest::{Client, Method, RequestBuilder, Url};
+
+pub struct GiteaClient {
+    endpoint: String,
+    client: Client,
+}
+
+impl GiteaClient {
+    /// Build a client for the API endpoint, e.g. `https://gitea.com/api/v1`.
+    pub fn build(endpoint: &str, token: &str) -> Result<Self> {
+        Url::parse(endpoint)?;
+        let mut headers = HeaderMap::new();
+        headers.insert("Accept", "application/json".parse()?);
+        headers.insert("Authorization", format!("token {}", token).parse()?);
+        headers.insert(
+            "User-Agent",
+            format!("yag/{}", env!("CARGO_PKG_VERSION")).parse()?,
+        );
+        debug!("default headers: {:?}", headers);
+        let client = Client::builder().default_headers(headers).build()?;
+
+        Ok(GiteaClient {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            client,
+        })
+    }
+
+    pub fn call(&self, method: Method, uri: &str) -> RequestBuilder {
+        let mut url = Url::parse(&self.endpoint).unwrap();
+        url.set_path(&format!("{}{}", url.path().trim_end_matches('/'), uri));
+
+        self.client
+            .request(method, url)
+            .header("Content-Type", "application/json")
+    }
+}
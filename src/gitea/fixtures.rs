@@ -0,0 +1,30 @@
+//! Clients and responses of Gitea shared by tests.
+
+use mockito::server_url;
+
+use super::client::GiteaClient;
+use super::repository::GiteaRepository;
+
+pub const PULL: &str = r#"{
+    "number": 1,
+    "html_url": "https://gitea.example.com/owner/repo/pulls/1",
+    "title": "WIP: add something",
+    "body": "description",
+    "state": "closed",
+    "merged": true,
+    "merged_at": "2020-09-02T00:00:00Z",
+    "user": { "login": "someone" },
+    "labels": [{ "id": 1, "name": "bug" }],
+    "assignees": null,
+    "requested_reviewers": [{ "login": "reviewer" }],
+    "mergeable": false,
+    "base": { "ref": "master" },
+    "head": { "ref": "feature" },
+    "created_at": "2020-09-01T00:00:00Z",
+    "updated_at": "2020-09-02T00:00:00Z"
+}"#;
+
+pub fn get_repo() -> GiteaRepository {
+    let client = GiteaClient::build(&format!("{}/api/v1", server_url()), "token").unwrap();
+    GiteaRepository::new(client, "owner/repo")
+}
@@ -0,0 +1,6 @@
+mod client;
+#[cfg(test)]
+mod fixtures;
+pub mod profile;
+pub mod repository;
+mod structs;
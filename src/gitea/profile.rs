@@ -0,0 +1,47 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_derive::*;
+
+use crate::{
+    profile::{Profile, ProfileConfig, Prompter},
+    utils,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GiteaConfig {
+    pub host: String,
+    pub token: String,
+}
+
+impl GiteaConfig {
+    pub fn get_api_endpoint(&self) -> String {
+        format!("https://{}/api/v1", self.host)
+    }
+}
+
+impl ProfileConfig for GiteaConfig {
+    fn fill_profile(&self, profile: &mut Profile) {
+        let mut default = vec![];
+        let configs = profile.gitea.as_mut().unwrap_or(&mut default);
+        configs.retain(|config| config.host != self.host);
+        configs.push(self.to_owned());
+        profile.gitea = Some(configs.to_owned());
+    }
+}
+
+#[derive(Default)]
+pub struct GiteaPrompter;
+
+#[async_trait]
+impl Prompter for GiteaPrompter {
+    fn display_name(&self) -> String {
+        "Gitea / Forgejo".to_string()
+    }
+
+    async fn prompt(&self) -> Result<Box<dyn ProfileConfig>> {
+        Ok(Box::new(GiteaConfig {
+            host: utils::user_input("host: ")?,
+            token: utils::user_input("access token: ")?,
+        }))
+    }
+}
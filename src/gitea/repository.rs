@@ -0,0 +1,850 @@
+use super::client::GiteaClient;
+use super::structs::{
+    CombinedStatus, GiteaResponse, Issue, IssueComment, Label, Milestone, Pull, PullFile, Repo,
+    Review, ReviewComment, User, DRAFT_PREFIXES,
+};
+use crate::profile::load_profile;
+use crate::repository::{
+    set_draft_prefix, CreateIssueOpt, CreatePullRequestOpt, ListIssueOpt, ListPullRequestOpt,
+    MergeMethod, MergePullRequestOpt, PullRequestSort, Repository, ReviewEvent, ReviewOpt,
+    UpdatePullRequestOpt,
+};
+use crate::structs::{
+    self, ChangedFile, Check, Comment, Discussion, IssueState, PaginationResult, Pipeline,
+    PullRequest, PullRequestState,
+};
+use crate::utils;
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use git_url_parse::GitUrl;
+use log::debug;
+use reqwest::Method;
+use serde_json::json;
+
+/// Gitea Actions provides no API to manage workflow runs yet.
+const PIPELINES_NOT_SUPPORTED: &str = "pipelines are not supported by Gitea";
+
+pub struct GiteaRepository {
+    repo: String,
+    client: GiteaClient,
+}
+
+impl GiteaRepository {
+    pub async fn init(host: &str, remote_url: &GitUrl) -> Result<Self> {
+        let profile = load_profile().await?;
+        let config = profile
+            .get_gitea_by_host(host)
+            .ok_or_else(|| anyhow!("no Gitea profile for {}: Try `yag profile add` first", host))?;
+        let client = GiteaClient::build(&config.get_api_endpoint(), &config.token)?;
+        Ok(Self::new(client, remote_url.fullname.as_ref()))
+    }
+
+    pub(crate) fn new(client: GiteaClient, repo: &str) -> Self {
+        GiteaRepository {
+            repo: repo.to_string(),
+            client,
+        }
+    }
+}
+
+#[async_trait]
+impl Repository for GiteaRepository {
+    async fn get_pull_request(&self, id: usize) -> Result<PullRequest> {
+        let res = self
+            .client
+            .call(Method::GET, &format!("/repos/{}/pulls/{}", self.repo, id))
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GiteaResponse<Pull>>(&text)?
+            .map(|pr| Ok(PullRequest::from(pr.to_owned())))
+    }
+
+    async fn list_pull_requests(
+        &self,
+        opt: ListPullRequestOpt,
+    ) -> Result<PaginationResult<PullRequest>> {
+        debug!("opt: {:#?}", opt);
+
+        if let Some(filter) = opt.get_filters().into_iter().find(|f| *f != "label") {
+            bail!(
+                "filtering pull requests by {} is not supported on Gitea",
+                filter
+            )
+        }
+
+        // merged pull requests are listed as closed ones
+        let state = match opt.state {
+            Some(PullRequestState::Open) => "open",
+            Some(PullRequestState::Closed) | Some(PullRequestState::Merged) => "closed",
+            None => "all",
+        };
+
+        let page = opt.get_page().max(1);
+        let mut req = self
+            .client
+            .call(Method::GET, &format!("/repos/{}/pulls", self.repo))
+            .query(&[("state", state)])
+            .query(&[("limit", opt.get_per_page()), ("page", page)]);
+
+        if opt.me {
+            req = req.query(&[("poster", self.get_current_user().await?.login)]);
+        } else if let Some(author) = opt.author.as_ref() {
+            req = req.query(&[("poster", author)]);
+        }
+        if !opt.labels.is_empty() {
+            for id in self.get_label_ids(&opt.labels).await? {
+                req = req.query(&[("labels", id)]);
+            }
+        }
+        let sort = match (opt.sort, opt.ascending) {
+            (Some(PullRequestSort::Created), false) | (None, false) => None,
+            (Some(PullRequestSort::Created), true) | (None, true) => Some("oldest"),
+            (Some(PullRequestSort::Updated), false) => Some("recentupdate"),
+            (Some(PullRequestSort::Updated), true) => Some("leastupdate"),
+            (Some(PullRequestSort::Comments), false) => Some("mostcomment"),
+            (Some(PullRequestSort::Comments), true) => Some("leastcomment"),
+        };
+        if let Some(sort) = sort {
+            req = req.query(&[("sort", sort)]);
+        }
+
+        let res = req.send().await?;
+
+        let total = res
+            .headers()
+            .get("x-total-count")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+        let next_page = res
+            .headers()
+            .get("link")
+            .and_then(|v| v.to_str().ok())
+            .and_then(utils::get_next_page);
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        let result = serde_json::from_str::<GiteaResponse<Vec<Pull>>>(&text)?.map(|pulls| {
+            Ok(pulls
+                .iter()
+                .filter(|pr| opt.state.is_none() || Some(pr.get_state()) == opt.state)
+                .map(|pr| PullRequest::from(pr.to_owned()))
+                .collect::<Vec<PullRequest>>())
+        })?;
+
+        // closed and merged ones are filtered from closed ones, so the total
+        // count is unknown
+        let total = match opt.state {
+            Some(PullRequestState::Closed) | Some(PullRequestState::Merged) => None,
+            _ => total,
+        };
+
+        Ok(PaginationResult::new(result, total).with_page(page, next_page))
+    }
+
+    async fn create_pull_request(&self, opt: CreatePullRequestOpt) -> Result<PullRequest> {
+        let title = if opt.draft {
+            set_draft_prefix(&opt.title, true, DRAFT_PREFIXES)
+        } else {
+            opt.title.to_owned()
+        };
+
+        let mut body = json!({
+            "head": opt.source_branch,
+            "base": opt.target_branch,
+            "title": title,
+            "body": opt.body,
+        });
+        if !opt.assignees.is_empty() {
+            body["assignees"] = json!(opt.assignees);
+        }
+        if !opt.labels.is_empty() {
+            body["labels"] = json!(self.get_label_ids(&opt.labels).await?);
+        }
+        if let Some(milestone) = opt.milestone.as_ref() {
+            body["milestone"] = json!(self.get_milestone_id(milestone).await?);
+        }
+
+        let res = self
+            .client
+            .call(Method::POST, &format!("/repos/{}/pulls", self.repo))
+            .body(body.to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        let pull =
+            serde_json::from_str::<GiteaResponse<Pull>>(&text)?.map(|pr| Ok(pr.to_owned()))?;
+
+        if opt.reviewers.is_empty() {
+            return Ok(PullRequest::from(pull));
+        }
+
+        self.call_requested_reviewers(Method::POST, pull.number, &opt.reviewers)
+            .await?;
+        self.get_pull_request(pull.number as usize).await
+    }
+
+    async fn close_pull_request(&self, id: usize) -> Result<PullRequest> {
+        self.update_pull(id, json!({ "state": "closed" })).await
+    }
+
+    async fn reopen_pull_request(&self, id: usize) -> Result<PullRequest> {
+        self.update_pull(id, json!({ "state": "open" })).await
+    }
+
+    async fn update_pull_request(
+        &self,
+        id: usize,
+        opt: UpdatePullRequestOpt,
+    ) -> Result<PullRequest> {
+        let pr = self.get_pull_request(id).await?;
+
+        let mut body = json!({});
+        let title = match opt.draft {
+            Some(draft) => Some(set_draft_prefix(
+                opt.title.as_ref().unwrap_or(&pr.title),
+                draft,
+                DRAFT_PREFIXES,
+            )),
+            None => opt.title,
+        };
+        if let Some(title) = title {
+            body["title"] = json!(title);
+        }
+        if let Some(description) = opt.body.as_ref() {
+            body["body"] = json!(description);
+        }
+        if let Some(base) = opt.target_branch.as_ref() {
+            body["base"] = json!(base);
+        }
+        if let Some(assignees) = opt.assignees.as_ref() {
+            body["assignees"] = json!(assignees);
+        }
+        if let Some(labels) = opt.labels.as_ref() {
+            body["labels"] = json!(self.get_label_ids(labels).await?);
+        }
+        if let Some(milestone) = opt.milestone.as_ref() {
+            body["milestone"] = json!(self.get_milestone_id(milestone).await?);
+        }
+        if body.as_object().is_some_and(|body| !body.is_empty()) {
+            self.update_pull(id, body).await?;
+        }
+
+        if let Some(reviewers) = opt.reviewers.as_ref() {
+            let removed = pr
+                .reviewers
+                .iter()
+                .filter(|reviewer| !reviewers.contains(reviewer))
+                .cloned()
+                .collect::<Vec<String>>();
+            if !removed.is_empty() {
+                self.call_requested_reviewers(Method::DELETE, pr.id, &removed)
+                    .await?;
+            }
+
+            let added = reviewers
+                .iter()
+                .filter(|reviewer| !pr.reviewers.contains(reviewer))
+                .cloned()
+                .collect::<Vec<String>>();
+            if !added.is_empty() {
+                self.call_requested_reviewers(Method::POST, pr.id, &added)
+                    .await?;
+            }
+        }
+
+        self.get_pull_request(id).await
+    }
+
+    async fn merge_pull_request(&self, id: usize, opt: MergePullRequestOpt) -> Result<PullRequest> {
+        let method = match opt.method {
+            MergeMethod::Merge => "merge",
+            MergeMethod::Squash => "squash",
+            MergeMethod::Rebase => "rebase",
+        };
+
+        let mut body = json!({
+            "Do": method,
+            "delete_branch_after_merge": opt.delete_branch,
+            "merge_when_checks_succeed": opt.auto,
+        });
+        if let Some(title) = opt.title.as_ref() {
+            body["MergeTitleField"] = json!(title);
+        }
+        if let Some(message) = opt.message.as_ref() {
+            body["MergeMessageField"] = json!(message);
+        }
+        if let Some(sha) = opt.sha.as_ref() {
+            body["head_commit_id"] = json!(sha);
+        }
+
+        let res = self
+            .client
+            .call(
+                Method::POST,
+                &format!("/repos/{}/pulls/{}/merge", self.repo, id),
+            )
+            .body(body.to_string())
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            let text = res.text().await?;
+            debug!("res: {}", text);
+            serde_json::from_str::<GiteaResponse<Pull>>(&text)?.map(|_| Ok(()))?;
+        }
+
+        self.get_pull_request(id).await
+    }
+
+    fn get_pull_request_ref(&self, id: usize) -> String {
+        format!("refs/pull/{}/head", id)
+    }
+
+    async fn list_pull_request_discussions(&self, id: usize) -> Result<Vec<Discussion>> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/repos/{}/issues/{}/comments", self.repo, id),
+            )
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        let mut discussions = serde_json::from_str::<GiteaResponse<Vec<IssueComment>>>(&text)?
+            .map(|comments| {
+                Ok(comments
+                    .iter()
+                    .map(|comment| {
+                        let comment = Comment::from(comment.to_owned());
+                        Discussion {
+                            id: comment.id.to_string(),
+                            comments: vec![comment],
+                            ..Default::default()
+                        }
+                    })
+                    .collect::<Vec<Discussion>>())
+            })?;
+
+        // comments on the diff can only be listed review by review
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/repos/{}/pulls/{}/reviews", self.repo, id),
+            )
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        let reviews = serde_json::from_str::<GiteaResponse<Vec<Review>>>(&text)?
+            .map(|reviews| Ok(reviews.to_owned()))?;
+
+        for review in reviews {
+            let res = self
+                .client
+                .call(
+                    Method::GET,
+                    &format!(
+                        "/repos/{}/pulls/{}/reviews/{}/comments",
+                        self.repo, id, review.id
+                    ),
+                )
+                .send()
+                .await?;
+
+            let text = res.text().await?;
+            debug!("res: {}", text);
+
+            let comments = serde_json::from_str::<GiteaResponse<Vec<ReviewComment>>>(&text)?
+                .map(|comments| Ok(comments.to_owned()))?;
+            discussions.extend(comments.into_iter().map(Discussion::from));
+        }
+
+        discussions.sort_by(|a, b| a.comments[0].created_at.cmp(&b.comments[0].created_at));
+        Ok(discussions)
+    }
+
+    async fn comment_pull_request(&self, id: usize, body: &str) -> Result<Comment> {
+        let res = self
+            .client
+            .call(
+                Method::POST,
+                &format!("/repos/{}/issues/{}/comments", self.repo, id),
+            )
+            .body(json!({ "body": body }).to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GiteaResponse<IssueComment>>(&text)?
+            .map(|comment| Ok(Comment::from(comment.to_owned())))
+    }
+
+    async fn get_pull_request_diff(&self, id: usize) -> Result<String> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/repos/{}/pulls/{}.diff", self.repo, id),
+            )
+            .send()
+            .await?;
+
+        let status = res.status();
+        let text = res.text().await?;
+        if !status.is_success() {
+            debug!("res: {}", text);
+            serde_json::from_str::<GiteaResponse<Pull>>(&text)?.map(|_| Ok(()))?;
+        }
+        Ok(text)
+    }
+
+    async fn list_pull_request_files(&self, id: usize) -> Result<Vec<ChangedFile>> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/repos/{}/pulls/{}/files", self.repo, id),
+            )
+            .query(&[("limit", "100")])
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GiteaResponse<Vec<PullFile>>>(&text)?.map(|files| {
+            Ok(files
+                .iter()
+                .map(|file| ChangedFile::from(file.to_owned()))
+                .collect())
+        })
+    }
+
+    async fn review_pull_request(&self, id: usize, opt: ReviewOpt) -> Result<PullRequest> {
+        let event = match opt.event {
+            ReviewEvent::Approve => "APPROVED",
+            ReviewEvent::RequestChanges => "REQUEST_CHANGES",
+            ReviewEvent::Comment => "COMMENT",
+        };
+        let comments = opt
+            .comments
+            .iter()
+            .map(|comment| {
+                json!({
+                    "path": comment.path,
+                    "new_position": comment.line,
+                    "body": comment.body,
+                })
+            })
+            .collect::<Vec<serde_json::Value>>();
+
+        let res = self
+            .client
+            .call(
+                Method::POST,
+                &format!("/repos/{}/pulls/{}/reviews", self.repo, id),
+            )
+            .body(
+                json!({
+                    "event": event,
+                    "body": opt.body.unwrap_or_default(),
+                    "comments": comments,
+                })
+                .to_string(),
+            )
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GiteaResponse<Review>>(&text)?.map(|_| Ok(()))?;
+
+        self.get_pull_request(id).await
+    }
+
+    async fn get_issue(&self, id: usize) -> Result<structs::Issue> {
+        let res = self
+            .client
+            .call(Method::GET, &format!("/repos/{}/issues/{}", self.repo, id))
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GiteaResponse<Issue>>(&text)?
+            .map(|issue| Ok(structs::Issue::from(issue.to_owned())))
+    }
+
+    async fn list_issues(&self, opt: ListIssueOpt) -> Result<PaginationResult<structs::Issue>> {
+        debug!("opt: {:#?}", opt);
+
+        let state = match opt.state {
+            Some(IssueState::Open) => "open",
+            Some(IssueState::Closed) => "closed",
+            None => "all",
+        };
+
+        let page = opt.get_page().max(1);
+        let mut req = self
+            .client
+            .call(Method::GET, &format!("/repos/{}/issues", self.repo))
+            .query(&[("type", "issues"), ("state", state)])
+            .query(&[("limit", opt.get_per_page()), ("page", page)]);
+
+        if opt.me {
+            req = req.query(&[("created_by", self.get_current_user().await?.login)]);
+        } else if let Some(author) = opt.author.as_ref() {
+            req = req.query(&[("created_by", author)]);
+        }
+        if let Some(assignee) = opt.assignee.as_ref() {
+            req = req.query(&[("assigned_by", assignee)]);
+        }
+        if !opt.labels.is_empty() {
+            req = req.query(&[("labels", opt.labels.join(","))]);
+        }
+        if let Some(milestone) = opt.milestone.as_ref() {
+            req = req.query(&[("milestones", milestone)]);
+        }
+
+        let res = req.send().await?;
+
+        let total = res
+            .headers()
+            .get("x-total-count")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+        let next_page = res
+            .headers()
+            .get("link")
+            .and_then(|v| v.to_str().ok())
+            .and_then(utils::get_next_page);
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        let result = serde_json::from_str::<GiteaResponse<Vec<Issue>>>(&text)?.map(|issues| {
+            Ok(issues
+                .iter()
+                .map(|issue| structs::Issue::from(issue.to_owned()))
+                .collect::<Vec<structs::Issue>>())
+        })?;
+
+        Ok(PaginationResult::new(result, total).with_page(page, next_page))
+    }
+
+    async fn create_issue(&self, opt: CreateIssueOpt) -> Result<structs::Issue> {
+        let mut body = json!({
+            "title": opt.title,
+            "body": opt.body,
+        });
+        if !opt.assignees.is_empty() {
+            body["assignees"] = json!(opt.assignees);
+        }
+        if !opt.labels.is_empty() {
+            body["labels"] = json!(self.get_label_ids(&opt.labels).await?);
+        }
+        if let Some(milestone) = opt.milestone.as_ref() {
+            body["milestone"] = json!(self.get_milestone_id(milestone).await?);
+        }
+
+        let res = self
+            .client
+            .call(Method::POST, &format!("/repos/{}/issues", self.repo))
+            .body(body.to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GiteaResponse<Issue>>(&text)?
+            .map(|issue| Ok(structs::Issue::from(issue.to_owned())))
+    }
+
+    async fn close_issue(&self, id: usize) -> Result<structs::Issue> {
+        self.set_issue_state(id, IssueState::Closed).await
+    }
+
+    async fn reopen_issue(&self, id: usize) -> Result<structs::Issue> {
+        self.set_issue_state(id, IssueState::Open).await
+    }
+
+    async fn list_checks(&self, git_ref: &str) -> Result<Vec<Check>> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/repos/{}/commits/{}/status", self.repo, git_ref),
+            )
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GiteaResponse<CombinedStatus>>(&text)?.map(|combined| {
+            Ok(combined
+                .statuses
+                .to_owned()
+                .unwrap_or_default()
+                .into_iter()
+                .map(Check::from)
+                .collect())
+        })
+    }
+
+    async fn list_pipelines(&self, _branch: Option<&str>) -> Result<Vec<Pipeline>> {
+        Err(anyhow!(PIPELINES_NOT_SUPPORTED))
+    }
+
+    async fn get_pipeline(&self, _id: u64) -> Result<Pipeline> {
+        Err(anyhow!(PIPELINES_NOT_SUPPORTED))
+    }
+
+    async fn retry_pipeline(&self, _id: u64, _failed_only: bool) -> Result<Pipeline> {
+        Err(anyhow!(PIPELINES_NOT_SUPPORTED))
+    }
+
+    async fn cancel_pipeline(&self, _id: u64) -> Result<Pipeline> {
+        Err(anyhow!(PIPELINES_NOT_SUPPORTED))
+    }
+
+    async fn get_job(&self, _id: u64) -> Result<Check> {
+        Err(anyhow!(PIPELINES_NOT_SUPPORTED))
+    }
+
+    async fn get_job_log(&self, _id: u64) -> Result<String> {
+        Err(anyhow!(PIPELINES_NOT_SUPPORTED))
+    }
+
+    async fn can_push(&self) -> Result<bool> {
+        let res = self
+            .client
+            .call(Method::GET, &format!("/repos/{}", self.repo))
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GiteaResponse<Repo>>(&text)?.map(|repo| {
+            Ok(repo
+                .permissions
+                .as_ref()
+                .map(|permissions| permissions.push)
+                .unwrap_or(false))
+        })
+    }
+
+    /// Gitea creates forks synchronously.
+    async fn fork_repository(&self, owner: Option<&str>) -> Result<structs::Repo> {
+        let mut body = json!({});
+        if let Some(owner) = owner {
+            body["organization"] = json!(owner);
+        }
+
+        let res = self
+            .client
+            .call(Method::POST, &format!("/repos/{}/forks", self.repo))
+            .body(body.to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GiteaResponse<Repo>>(&text)?
+            .map(|repo| Ok(structs::Repo::from(repo.to_owned())))
+    }
+}
+
+impl GiteaRepository {
+    async fn update_pull(&self, id: usize, body: serde_json::Value) -> Result<PullRequest> {
+        let res = self
+            .client
+            .call(Method::PATCH, &format!("/repos/{}/pulls/{}", self.repo, id))
+            .body(body.to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GiteaResponse<Pull>>(&text)?
+            .map(|pr| Ok(PullRequest::from(pr.to_owned())))
+    }
+
+    /// Request reviews with `POST`, or cancel them with `DELETE`.
+    async fn call_requested_reviewers(
+        &self,
+        method: Method,
+        number: u64,
+        reviewers: &[String],
+    ) -> Result<()> {
+        let res = self
+            .client
+            .call(
+                method,
+                &format!("/repos/{}/pulls/{}/requested_reviewers", self.repo, number),
+            )
+            .body(json!({ "reviewers": reviewers }).to_string())
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            let text = res.text().await?;
+            debug!("res: {}", text);
+            serde_json::from_str::<GiteaResponse<Pull>>(&text)?.map(|_| Ok(()))?;
+        }
+        Ok(())
+    }
+
+    async fn get_current_user(&self) -> Result<User> {
+        let res = self.client.call(Method::GET, "/user").send().await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GiteaResponse<User>>(&text)?.map(|user| Ok(user.to_owned()))
+    }
+
+    async fn set_issue_state(&self, id: usize, state: IssueState) -> Result<structs::Issue> {
+        let res = self
+            .client
+            .call(
+                Method::PATCH,
+                &format!("/repos/{}/issues/{}", self.repo, id),
+            )
+            .body(json!({ "state": state }).to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GiteaResponse<Issue>>(&text)?
+            .map(|issue| Ok(structs::Issue::from(issue.to_owned())))
+    }
+
+    async fn get_label_ids(&self, labels: &[String]) -> Result<Vec<u64>> {
+        let res = self
+            .client
+            .call(Method::GET, &format!("/repos/{}/labels", self.repo))
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GiteaResponse<Vec<Label>>>(&text)?.map(|data| {
+            labels
+                .iter()
+                .map(|label| {
+                    data.iter()
+                        .find(|l| &l.name == label)
+                        .map(|l| l.id)
+                        .ok_or_else(|| anyhow!("label not found: {}", label))
+                })
+                .collect()
+        })
+    }
+
+    async fn get_milestone_id(&self, milestone: &str) -> Result<u64> {
+        if let Ok(id) = milestone.parse::<u64>() {
+            return Ok(id);
+        }
+
+        let res = self
+            .client
+            .call(Method::GET, &format!("/repos/{}/milestones", self.repo))
+            .query(&[("name", milestone)])
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GiteaResponse<Vec<Milestone>>>(&text)?.map(|data| {
+            data.iter()
+                .find(|m| m.title == milestone)
+                .map(|m| m.id)
+                .ok_or_else(|| anyhow!("milestone not found: {}", milestone))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mockito::mock;
+
+    use crate::gitea::fixtures::{get_repo, PULL};
+
+    #[tokio::test]
+    async fn test_get_pull_request() -> Result<()> {
+        let _m = mock("GET", "/api/v1/repos/owner/repo/pulls/1")
+            .match_header("authorization", "token token")
+            .with_body(PULL)
+            .create();
+
+        let pr = get_repo().get_pull_request(1).await?;
+        assert_eq!(pr.id, 1);
+        assert_eq!(pr.state, PullRequestState::Merged);
+        assert!(pr.draft);
+        assert_eq!(pr.labels, vec!["bug"]);
+        assert_eq!(pr.reviewers, vec!["reviewer"]);
+        assert_eq!(pr.head.unwrap(), "feature");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_pull_requests() -> Result<()> {
+        let _m = mock("GET", "/api/v1/repos/owner/repo/pulls")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("state".into(), "closed".into()),
+                mockito::Matcher::UrlEncoded("poster".into(), "someone".into()),
+            ]))
+            .with_header("x-total-count", "1")
+            .with_body(format!("[{}]", PULL))
+            .create();
+
+        let mut opt = ListPullRequestOpt::default();
+        opt.author = Some("someone".to_string());
+        opt.state = Some(PullRequestState::Closed);
+        let result = get_repo().list_pull_requests(opt).await?;
+        assert_eq!(result.total, None);
+        assert!(result.result.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_close_pull_request_error() -> Result<()> {
+        let _m = mock("PATCH", "/api/v1/repos/owner/repo/pulls/2")
+            .with_status(404)
+            .with_body(r#"{ "message": "not found" }"#)
+            .create();
+
+        let err = get_repo().close_pull_request(2).await.unwrap_err();
+        assert_eq!(err.to_string(), "not found");
+        Ok(())
+    }
+}
@@ -0,0 +1,362 @@
+use std::fmt;
+
+use anyhow::{bail, Result};
+use log::debug;
+use serde_derive::*;
+
+use crate::structs::{
+    self, ChangedFile, Check, CheckState, Comment, FileStatus, IssueState, PullRequest,
+    PullRequestState, Visibility,
+};
+
+/// Gitea marks pull requests as work in progress by the prefix of their titles.
+pub const DRAFT_PREFIXES: &[&str] = &["WIP:", "[WIP]"];
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum GiteaResponse<T> {
+    Ok(T),
+    Error { message: String },
+}
+
+impl<T> GiteaResponse<T>
+where
+    T: fmt::Debug,
+{
+    #[inline]
+    pub fn map<R, F>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&T) -> Result<R>,
+    {
+        match self {
+            GiteaResponse::Ok(data) => f(data),
+            GiteaResponse::Error { message } => {
+                debug!("found an error: {:#?}", self);
+                bail!(message.to_string())
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct User {
+    pub login: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Permissions {
+    pub push: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Parent {
+    pub full_name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Repo {
+    pub full_name: String,
+    pub description: Option<String>,
+    pub private: bool,
+    #[serde(default)]
+    pub internal: bool,
+    /// Only present for forks.
+    pub parent: Option<Parent>,
+    pub default_branch: Option<String>,
+    pub html_url: String,
+    pub clone_url: String,
+    pub ssh_url: Option<String>,
+    pub permissions: Option<Permissions>,
+}
+
+impl From<Repo> for structs::Repo {
+    fn from(repo: Repo) -> Self {
+        Self {
+            name: repo.full_name,
+            description: repo
+                .description
+                .filter(|description| !description.is_empty()),
+            visibility: if repo.private {
+                Visibility::Private
+            } else if repo.internal {
+                Visibility::Internal
+            } else {
+                Visibility::Public
+            },
+            parent: repo.parent.map(|parent| parent.full_name),
+            default_branch: repo.default_branch,
+            url: repo.html_url,
+            clone_url: repo.clone_url,
+            ssh_url: repo.ssh_url,
+            stars: None,
+            open_issues: None,
+            open_pull_requests: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Label {
+    pub id: u64,
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Milestone {
+    pub id: u64,
+    pub title: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Ref {
+    #[serde(rename = "ref")]
+    pub name: String,
+    pub sha: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Pull {
+    pub number: u64,
+    html_url: String,
+    title: String,
+    body: Option<String>,
+    state: String,
+    #[serde(default)]
+    merged: bool,
+    merged_at: Option<String>,
+    draft: Option<bool>,
+    user: User,
+    #[serde(default)]
+    labels: Vec<Label>,
+    assignees: Option<Vec<User>>,
+    requested_reviewers: Option<Vec<User>>,
+    mergeable: Option<bool>,
+    additions: Option<u64>,
+    deletions: Option<u64>,
+    changed_files: Option<u64>,
+    base: Option<Ref>,
+    head: Option<Ref>,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Issue {
+    number: u64,
+    html_url: String,
+    title: String,
+    body: Option<String>,
+    state: String,
+    user: User,
+    #[serde(default)]
+    labels: Vec<Label>,
+    assignees: Option<Vec<User>>,
+    milestone: Option<Milestone>,
+    comments: Option<u64>,
+    created_at: String,
+    updated_at: String,
+    closed_at: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct IssueComment {
+    id: u64,
+    html_url: String,
+    body: String,
+    user: User,
+    created_at: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PullFile {
+    filename: String,
+    previous_filename: Option<String>,
+    status: String,
+    additions: u64,
+    deletions: u64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CommitStatus {
+    id: u64,
+    context: String,
+    status: String,
+    target_url: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+/// Latest status of each context of a commit.
+#[derive(Deserialize, Debug, Clone)]
+pub struct CombinedStatus {
+    pub statuses: Option<Vec<CommitStatus>>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Review {
+    pub id: u64,
+}
+
+/// Comment on the diff of a pull request, made within a review.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ReviewComment {
+    pub id: u64,
+    html_url: String,
+    body: String,
+    user: User,
+    pub path: String,
+    position: Option<u64>,
+    original_position: Option<u64>,
+    resolver: Option<User>,
+    created_at: String,
+}
+
+impl Pull {
+    pub fn get_state(&self) -> PullRequestState {
+        match (self.state.as_ref(), self.merged) {
+            ("open", _) => PullRequestState::Open,
+            (_, true) => PullRequestState::Merged,
+            _ => PullRequestState::Closed,
+        }
+    }
+}
+
+impl From<Pull> for PullRequest {
+    fn from(pr: Pull) -> Self {
+        Self {
+            state: pr.get_state(),
+            id: pr.number,
+            // older versions mark drafts only by the prefix of titles
+            draft: pr.draft.unwrap_or_else(|| {
+                DRAFT_PREFIXES
+                    .iter()
+                    .any(|prefix| pr.title.starts_with(prefix))
+            }),
+            title: pr.title,
+            author: pr.user.login,
+            body: pr.body,
+            labels: pr.labels.into_iter().map(|l| l.name).collect(),
+            assignees: pr
+                .assignees
+                .unwrap_or_default()
+                .into_iter()
+                .map(|u| u.login)
+                .collect(),
+            reviewers: pr
+                .requested_reviewers
+                .unwrap_or_default()
+                .into_iter()
+                .map(|u| u.login)
+                .collect(),
+            mergeable: pr.mergeable,
+            additions: pr.additions,
+            deletions: pr.deletions,
+            changed_files: pr.changed_files,
+            base: pr.base.map(|r| r.name),
+            sha: pr.head.as_ref().and_then(|r| r.sha.clone()),
+            head: pr.head.map(|r| r.name),
+            created_at: pr.created_at,
+            updated_at: pr.updated_at,
+            merged_at: pr.merged_at,
+            url: pr.html_url,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<Issue> for structs::Issue {
+    fn from(issue: Issue) -> Self {
+        Self {
+            id: issue.number,
+            title: issue.title,
+            state: match issue.state.as_ref() {
+                "closed" => IssueState::Closed,
+                _ => IssueState::Open,
+            },
+            author: issue.user.login,
+            body: issue.body,
+            labels: issue.labels.into_iter().map(|l| l.name).collect(),
+            assignees: issue
+                .assignees
+                .unwrap_or_default()
+                .into_iter()
+                .map(|u| u.login)
+                .collect(),
+            milestone: issue.milestone.map(|m| m.title),
+            comments: issue.comments,
+            created_at: issue.created_at,
+            updated_at: issue.updated_at,
+            closed_at: issue.closed_at,
+            url: issue.html_url,
+        }
+    }
+}
+
+impl From<IssueComment> for Comment {
+    fn from(comment: IssueComment) -> Self {
+        Self {
+            id: comment.id,
+            author: comment.user.login,
+            body: comment.body,
+            created_at: comment.created_at,
+            url: Some(comment.html_url),
+        }
+    }
+}
+
+impl From<ReviewComment> for structs::Discussion {
+    fn from(comment: ReviewComment) -> Self {
+        Self {
+            id: comment.id.to_string(),
+            path: Some(comment.path),
+            line: comment
+                .position
+                .filter(|position| *position > 0)
+                .or(comment.original_position),
+            resolved: Some(comment.resolver.is_some()),
+            comments: vec![Comment {
+                id: comment.id,
+                author: comment.user.login,
+                body: comment.body,
+                created_at: comment.created_at,
+                url: Some(comment.html_url),
+            }],
+        }
+    }
+}
+
+impl From<PullFile> for ChangedFile {
+    fn from(file: PullFile) -> Self {
+        Self {
+            status: match file.status.as_ref() {
+                "added" => FileStatus::Added,
+                "deleted" => FileStatus::Removed,
+                "renamed" => FileStatus::Renamed,
+                _ => FileStatus::Modified,
+            },
+            path: file.filename,
+            old_path: file.previous_filename.filter(|path| !path.is_empty()),
+            additions: Some(file.additions),
+            deletions: Some(file.deletions),
+        }
+    }
+}
+
+impl From<CommitStatus> for Check {
+    fn from(status: CommitStatus) -> Self {
+        let state = match status.status.as_ref() {
+            "success" | "warning" => CheckState::Success,
+            "failure" | "error" => CheckState::Failure,
+            _ => CheckState::Pending,
+        };
+        Self {
+            id: status.id.to_string(),
+            name: status.context,
+            finished_at: Some(status.updated_at).filter(|_| state.is_finished()),
+            started_at: Some(status.created_at),
+            state,
+            url: status.target_url.filter(|url| !url.is_empty()),
+            ..Default::default()
+        }
+    }
+}
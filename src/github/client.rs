@@ -1,14 +1,19 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use log::debug;
 use reqwest::{header::HeaderMap, Client, Method, RequestBuilder, Response, Url};
 use serde_json::json;
 
+use super::profile::GitHubConfig;
 use super::structs::{DeviceCode, GetAccessTokenResponse};
+use crate::profile::Profile;
 
 const GITHUB_API_ENDPOINT: &str = "https://api.github.com";
+const GITHUB_GRAPHQL_ENDPOINT: &str = "https://api.github.com/graphql";
 
 pub struct GitHubClient {
     client: Client,
+    endpoint: String,
+    graphql_endpoint: String,
 }
 
 impl GitHubClient {
@@ -29,7 +34,7 @@ impl GitHubClient {
         debug!("default headers: {:?}", headers);
         let client = Client::builder().default_headers(headers).build()?;
 
-        Ok(Self { client: client })
+        Ok(Self::new(client))
     }
 
     pub fn build_with_oauth_token(token: &str) -> Result<Self> {
@@ -38,19 +43,63 @@ impl GitHubClient {
         debug!("default headers: {:?}", headers);
         let client = Client::builder().default_headers(headers).build()?;
 
-        Ok(Self { client: client })
+        Ok(Self::new(client))
+    }
+
+    fn new(client: Client) -> Self {
+        Self {
+            client,
+            endpoint: GITHUB_API_ENDPOINT.to_string(),
+            graphql_endpoint: GITHUB_GRAPHQL_ENDPOINT.to_string(),
+        }
+    }
+
+    /// Client of GitHub, or GitHub Enterprise Server of the host.
+    pub fn from_profile(profile: &Profile, host: &str) -> Result<Self> {
+        if let Some(config) = profile.get_github_enterprise_by_host(host) {
+            return Self::build_with_oauth_token(&config.token)?
+                .with_endpoint(&config.get_api_endpoint(), &config.get_graphql_endpoint());
+        }
+
+        let config = profile
+            .github
+            .as_ref()
+            .ok_or(anyhow!("no GitHub profile: Try `yag profile add` first"))?;
+        match config {
+            GitHubConfig {
+                access_token: Some(token),
+                token: _,
+                username: _,
+            } => Self::build_with_oauth_token(token),
+            GitHubConfig {
+                access_token: _,
+                token: Some(token),
+                username: Some(username),
+            } => Self::build_with_basic_auth(username, token),
+            _ => bail!("wrong GitHub profile config"),
+        }
+    }
+
+    /// Use the API endpoints of a GitHub Enterprise Server instead.
+    pub fn with_endpoint(mut self, endpoint: &str, graphql_endpoint: &str) -> Result<Self> {
+        Url::parse(endpoint)?;
+        Url::parse(graphql_endpoint)?;
+        self.endpoint = endpoint.trim_end_matches('/').to_string();
+        self.graphql_endpoint = graphql_endpoint.to_string();
+        Ok(self)
     }
 
     pub fn call(&self, method: Method, uri: &str) -> RequestBuilder {
-        let mut url = Url::parse(GITHUB_API_ENDPOINT).unwrap();
-        url.set_path(uri);
+        let mut url = Url::parse(&self.endpoint).unwrap();
+        url.set_path(&format!("{}{}", url.path().trim_end_matches('/'), uri));
 
         self.client.request(method, url)
     }
 
     pub async fn graphql(&self, query: &str, variables: serde_json::Value) -> Result<Response> {
         Ok(self
-            .call(Method::POST, "/graphql")
+            .client
+            .request(Method::POST, &self.graphql_endpoint)
             .header("Content-Type", "application/json")
             .body(
                 json!({
@@ -78,7 +127,7 @@ impl GitHubAnonymousClient {
 
         let client = Client::builder().default_headers(headers).build()?;
 
-        Ok(Self { client: client })
+        Ok(Self { client })
     }
 
     pub async fn gen_device_code(&self) -> Result<DeviceCode> {
@@ -0,0 +1,88 @@
+//! Clients and responses of GitHub shared by tests.
+
+use mockito::server_url;
+
+use super::client::GitHubClient;
+use super::repository::GitHubRepository;
+
+pub fn get_client() -> GitHubClient {
+    GitHubClient::build_with_oauth_token("token")
+        .unwrap()
+        .with_endpoint(&server_url(), &format!("{}/graphql", server_url()))
+        .unwrap()
+}
+
+pub fn get_repo() -> GitHubRepository {
+    GitHubRepository::new(get_client(), "owner/repo")
+}
+
+pub fn issue(number: u64, is_pull: bool) -> String {
+    let pull_request = if is_pull {
+        r#"{ "url": "https://api.github.com/repos/owner/repo/pulls/2" }"#
+    } else {
+        "null"
+    };
+    format!(
+        r#"{{
+            "number": {},
+            "html_url": "https://github.com/owner/repo/issues/{}",
+            "title": "something wrong",
+            "user": {{ "login": "someone" }},
+            "state": "open",
+            "body": null,
+            "labels": [{{ "name": "bug" }}],
+            "assignees": [],
+            "milestone": {{ "number": 1, "title": "v1.0" }},
+            "comments": 3,
+            "pull_request": {},
+            "created_at": "2020-09-01T00:00:00Z",
+            "updated_at": "2020-09-02T00:00:00Z",
+            "closed_at": null
+        }}"#,
+        number, number, pull_request
+    )
+}
+
+pub fn pull(number: u64) -> String {
+    format!(
+        r#"{{
+            "node_id": "PR_{}",
+            "html_url": "https://github.com/owner/repo/pull/{}",
+            "title": "add something",
+            "user": {{ "login": "someone" }},
+            "number": {},
+            "state": "open",
+            "draft": false,
+            "body": null,
+            "mergeable": null,
+            "commits": 1,
+            "additions": 1,
+            "deletions": 0,
+            "changed_files": 1,
+            "merged_at": null,
+            "base": {{ "ref": "main" }},
+            "head": {{ "ref": "feature" }},
+            "created_at": "2020-09-01T00:00:00Z",
+            "updated_at": "2020-09-02T00:00:00Z"
+        }}"#,
+        number, number, number
+    )
+}
+
+/// Repository `owner/repo` seen by a user who can push to it or not.
+pub fn repo(push: bool) -> String {
+    format!(
+        r#"{{
+            "full_name": "owner/repo",
+            "description": null,
+            "private": false,
+            "parent": null,
+            "default_branch": "main",
+            "html_url": "https://github.com/owner/repo",
+            "clone_url": "https://github.com/owner/repo.git",
+            "ssh_url": "git@github.com:owner/repo.git",
+            "permissions": {{ "push": {} }}
+        }}"#,
+        push
+    )
+}
@@ -0,0 +1,167 @@
+use super::client::GitHubClient;
+use super::structs::{GitHubResponse, Issue, Repo, SearchResult};
+use crate::profile::Profile;
+use crate::repository::{self, CreateRepositoryOpt, Forge};
+use crate::structs::{self, Visibility};
+use anyhow::Result;
+use async_trait::async_trait;
+use log::debug;
+use reqwest::Method;
+use serde_json::json;
+
+pub struct GitHubForge {
+    host: String,
+    client: GitHubClient,
+}
+
+impl GitHubForge {
+    pub fn init(profile: &Profile, host: &str) -> Result<Self> {
+        Ok(GitHubForge {
+            host: host.to_string(),
+            client: GitHubClient::from_profile(profile, host)?,
+        })
+    }
+
+    async fn count_open_pulls(&self, name: &str) -> Result<u64> {
+        let res = self
+            .client
+            .call(Method::GET, "/search/issues")
+            .query(&[
+                ("q", format!("repo:{} is:pr is:open", name)),
+                ("per_page", "1".to_string()),
+            ])
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GitHubResponse<SearchResult<Issue>>>(&text)?
+            .map(|result| Ok(result.total_count))
+    }
+}
+
+#[async_trait]
+impl Forge for GitHubForge {
+    async fn get_repository(&self, name: &str) -> Result<structs::Repo> {
+        let res = self
+            .client
+            .call(Method::GET, &format!("/repos/{}", name))
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        let mut repo = serde_json::from_str::<GitHubResponse<Repo>>(&text)?
+            .map(|repo| Ok(structs::Repo::from(repo.to_owned())))?;
+
+        // open issues of GitHub include pull requests
+        let pulls = self.count_open_pulls(name).await?;
+        repo.open_issues = repo.open_issues.map(|count| count.saturating_sub(pulls));
+        repo.open_pull_requests = Some(pulls);
+        Ok(repo)
+    }
+
+    async fn create_repository(&self, opt: CreateRepositoryOpt) -> Result<structs::Repo> {
+        let uri = match opt.owner.as_ref() {
+            Some(owner) => format!("/orgs/{}/repos", owner),
+            None => "/user/repos".to_string(),
+        };
+        let mut body = json!({
+            "name": opt.name,
+            "private": opt.visibility != Visibility::Public,
+        });
+        if let Some(description) = opt.description {
+            body["description"] = json!(description);
+        }
+        if opt.visibility == Visibility::Internal {
+            body["visibility"] = json!(opt.visibility.as_str());
+        }
+
+        let res = self
+            .client
+            .call(Method::POST, &uri)
+            .body(body.to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GitHubResponse<Repo>>(&text)?
+            .map(|repo| Ok(structs::Repo::from(repo.to_owned())))
+    }
+
+    fn get_web_url(&self, name: &str, git_ref: &str, path: &str, line: Option<usize>) -> String {
+        let repo_url = format!("https://{}/{}", self.host, name);
+        repository::get_web_url(&repo_url, git_ref, path, line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mockito::{mock, Matcher};
+
+    use crate::github::fixtures::get_client;
+
+    fn get_forge() -> GitHubForge {
+        GitHubForge {
+            host: "github.com".to_string(),
+            client: get_client(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_repository() -> Result<()> {
+        let _m1 = mock("GET", "/repos/owner/repo")
+            .with_body(
+                r#"{
+                    "full_name": "owner/repo",
+                    "description": "something",
+                    "private": false,
+                    "parent": null,
+                    "default_branch": "master",
+                    "html_url": "https://github.com/owner/repo",
+                    "clone_url": "https://github.com/owner/repo.git",
+                    "ssh_url": "git@github.com:owner/repo.git",
+                    "stargazers_count": 42,
+                    "open_issues_count": 5
+                }"#,
+            )
+            .create();
+        let m2 = mock("GET", "/search/issues")
+            .match_query(Matcher::UrlEncoded(
+                "q".to_string(),
+                "repo:owner/repo is:pr is:open".to_string(),
+            ))
+            .with_body(r#"{ "total_count": 2, "incomplete_results": false, "items": [] }"#)
+            .create();
+
+        let repo = get_forge().get_repository("owner/repo").await?;
+        m2.assert();
+        assert_eq!(repo.stars, Some(42));
+        assert_eq!(repo.open_issues, Some(3));
+        assert_eq!(repo.open_pull_requests, Some(2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_web_url() {
+        let forge = get_forge();
+        assert_eq!(
+            forge.get_web_url("owner/repo", "main", "", None),
+            "https://github.com/owner/repo/tree/main"
+        );
+        assert_eq!(
+            forge.get_web_url("owner/repo", "main", "src/", None),
+            "https://github.com/owner/repo/tree/main/src"
+        );
+        assert_eq!(
+            forge.get_web_url("owner/repo", "main", "src/main.rs", Some(10)),
+            "https://github.com/owner/repo/blob/main/src/main.rs#L10"
+        );
+    }
+}
@@ -21,6 +21,76 @@ impl ProfileConfig for GitHubConfig {
         profile.github = Some(self.to_owned());
     }
 }
+pub const GITHUB_HOST: &str = "github.com";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GitHubEnterpriseConfig {
+    pub host: String,
+    pub token: String,
+    pub api_endpoint: Option<String>,
+    pub graphql_endpoint: Option<String>,
+}
+
+impl GitHubEnterpriseConfig {
+    pub fn get_api_endpoint(&self) -> String {
+        self.api_endpoint
+            .clone()
+            .unwrap_or(format!("https://{}/api/v3", self.host))
+    }
+
+    pub fn get_graphql_endpoint(&self) -> String {
+        self.graphql_endpoint
+            .clone()
+            .unwrap_or(format!("https://{}/api/graphql", self.host))
+    }
+}
+
+impl ProfileConfig for GitHubEnterpriseConfig {
+    fn fill_profile(&self, profile: &mut Profile) {
+        let mut default = vec![];
+        let configs = profile.github_enterprise.as_mut().unwrap_or(&mut default);
+        configs.retain(|config| config.host != self.host);
+        configs.push(self.to_owned());
+        profile.github_enterprise = Some(configs.to_owned());
+    }
+}
+
+#[derive(Default)]
+pub struct GitHubEnterprisePrompter;
+
+#[async_trait]
+impl Prompter for GitHubEnterprisePrompter {
+    fn display_name(&self) -> String {
+        "GitHub Enterprise Server".to_string()
+    }
+
+    async fn prompt(&self) -> Result<Box<dyn ProfileConfig>> {
+        let host = utils::user_input("host: ")?;
+        let mut config = GitHubEnterpriseConfig {
+            host,
+            token: String::new(),
+            api_endpoint: None,
+            graphql_endpoint: None,
+        };
+        let api_endpoint = utils::user_input(&format!(
+            "API endpoint (default: {}): ",
+            config.get_api_endpoint()
+        ))?;
+        if !api_endpoint.is_empty() {
+            config.api_endpoint = Some(api_endpoint);
+        }
+        let graphql_endpoint = utils::user_input(&format!(
+            "GraphQL endpoint (default: {}): ",
+            config.get_graphql_endpoint()
+        ))?;
+        if !graphql_endpoint.is_empty() {
+            config.graphql_endpoint = Some(graphql_endpoint);
+        }
+        config.token = utils::user_input("personal access token: ")?;
+        Ok(Box::new(config))
+    }
+}
+
 #[derive(Default)]
 pub struct GitHubPrompter;
 
@@ -1,16 +1,39 @@
-use crate::repository::Repository;
+use crate::repository::{
+    paginate, CreateIssueOpt, CreatePullRequestOpt, IncompletePullRequest, ListIssueOpt,
+    MergeMethod, MergePullRequestOpt, PullRequestSort, Repository, ReviewEvent, ReviewOpt,
+    UpdatePullRequestOpt, FORK_POLL_INTERVAL, FORK_POLL_TIMES,
+};
 
 use super::client::GitHubClient;
-use super::profile::GitHubConfig;
-use super::structs::{GitHubResponse, Pull, SearchResult};
+use super::structs::{
+    CheckRun, CheckRuns, CombinedStatus, GitHubResponse, Issue, IssueComment, Jobs, MergeResult,
+    Milestone, Pull, PullFile, Repo, Review, ReviewComment, SearchResult, User, WorkflowRun,
+    WorkflowRuns,
+};
 use crate::profile::load_profile;
-use crate::structs::{PaginationResult, PullRequest};
+use crate::structs::{
+    self, ChangedFile, Check, Comment, Discussion, IssueState, PaginationResult, Pipeline,
+    PullRequest, PullRequestState,
+};
+use crate::utils;
 use anyhow::{anyhow, bail, Result};
 use async_trait::async_trait;
+use futures::TryStreamExt;
 use git_url_parse::GitUrl;
 use log::debug;
-use reqwest::Method;
+use reqwest::{Method, Response};
+use serde::de::DeserializeOwned;
 use serde_json::json;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Next page in the `Link` header of the response.
+fn get_next_page(res: &Response) -> Option<usize> {
+    res.headers()
+        .get("link")
+        .and_then(|v| v.to_str().ok())
+        .and_then(utils::get_next_page)
+}
 
 pub struct GitHubRepository {
     repo: String,
@@ -18,45 +41,24 @@ pub struct GitHubRepository {
 }
 
 impl GitHubRepository {
-    pub async fn init(remote_url: &GitUrl) -> Result<Self> {
+    pub async fn init(host: &str, remote_url: &GitUrl) -> Result<Self> {
         let profile = load_profile().await?;
-        let config = profile
-            .github
-            .ok_or(anyhow!("no GitHub profile: Try `yag profile add` first"))?;
-        let client = match config {
-            GitHubConfig {
-                access_token: Some(token),
-                token: _,
-                username: _,
-            } => GitHubClient::build_with_oauth_token(&token)?,
-            GitHubConfig {
-                access_token: _,
-                token: Some(token),
-                username: Some(username),
-            } => GitHubClient::build_with_basic_auth(&username, &token)?,
-            _ => bail!("wrong GitHub profile config"),
-        };
-        Ok(GitHubRepository {
-            repo: remote_url.fullname.to_string(),
-            client: client,
-        })
+        let client = GitHubClient::from_profile(&profile, host)?;
+        Ok(Self::new(client, remote_url.fullname.as_ref()))
+    }
+
+    pub(crate) fn new(client: GitHubClient, repo: &str) -> Self {
+        GitHubRepository {
+            repo: repo.to_string(),
+            client,
+        }
     }
 }
 
 #[async_trait]
 impl Repository for GitHubRepository {
     async fn get_pull_request(&self, id: usize) -> Result<PullRequest> {
-        let res = self
-            .client
-            .call(Method::GET, &format!("/repos/{}/pulls/{}", self.repo, id))
-            .send()
-            .await?;
-
-        let text = res.text().await?;
-        debug!("res: {}", text);
-
-        serde_json::from_str::<GitHubResponse<Pull>>(&text)?
-            .map(|pr| Ok(PullRequest::from(pr.to_owned())))
+        self.get_pull(id).await.map(PullRequest::from)
     }
 
     async fn list_pull_requests(
@@ -65,8 +67,17 @@ impl Repository for GitHubRepository {
     ) -> Result<PaginationResult<PullRequest>> {
         debug!("opt: {:#?}", opt);
 
-        let mut pairs: Vec<(String, String)> =
-            vec![("is".into(), "pr".into()), ("is".into(), "open".into())];
+        let mut pairs: Vec<(String, String)> = vec![("is".into(), "pr".into())];
+
+        match opt.state {
+            Some(PullRequestState::Open) => pairs.push(("is".into(), "open".into())),
+            Some(PullRequestState::Closed) => {
+                pairs.push(("is".into(), "closed".into()));
+                pairs.push(("is".into(), "unmerged".into()));
+            }
+            Some(PullRequestState::Merged) => pairs.push(("is".into(), "merged".into())),
+            None => (),
+        }
 
         pairs.push(("repo".into(), self.repo.to_owned()));
 
@@ -75,36 +86,69 @@ impl Repository for GitHubRepository {
         } else if let Some(author) = opt.author.clone() {
             pairs.push(("author".into(), author));
         }
+        if let Some(assignee) = opt.assignee.clone() {
+            pairs.push(("assignee".into(), assignee));
+        }
+        if let Some(reviewer) = opt.reviewer.clone() {
+            pairs.push(("review-requested".into(), reviewer));
+        }
+        for label in opt.labels.iter() {
+            pairs.push(("label".into(), label.to_owned()));
+        }
+        if let Some(base) = opt.base.clone() {
+            pairs.push(("base".into(), base));
+        }
+        if let Some(head) = opt.head.clone() {
+            pairs.push(("head".into(), head));
+        }
+        if let Some(draft) = opt.draft {
+            pairs.push(("draft".into(), draft.to_string()));
+        }
 
-        let res = self
+        let mut query = self.build_query(&pairs);
+        if let Some(search) = opt.search.as_ref() {
+            query = format!("{} {}", query, search);
+        }
+
+        let page = opt.get_page().max(1);
+        let mut req = self
             .client
             .call(Method::GET, "/search/issues")
-            .query(&[("per_page", "10")])
-            .query(&[("page", opt.get_page())])
-            .query(&[("q", self.build_query(&pairs))])
-            .send()
-            .await?;
+            .query(&[("per_page", opt.get_per_page()), ("page", page)])
+            .query(&[("q", query)]);
+
+        if opt.sort.is_some() || opt.ascending {
+            let sort = match opt.sort.unwrap_or(PullRequestSort::Created) {
+                PullRequestSort::Created => "created",
+                PullRequestSort::Updated => "updated",
+                PullRequestSort::Comments => "comments",
+            };
+            let order = if opt.ascending { "asc" } else { "desc" };
+            req = req.query(&[("sort", sort), ("order", order)]);
+        }
+
+        let res = req.send().await?;
+        let next_page = get_next_page(&res);
 
         let text = res.text().await?;
         debug!("res: {}", text);
         let result = serde_json::from_str::<GitHubResponse<SearchResult<Pull>>>(&text)?;
-        result.map::<PaginationResult<PullRequest>, _>(|r| Ok(PaginationResult::from(r.clone())))
+        result.map::<PaginationResult<PullRequest>, _>(|r| {
+            Ok(PaginationResult::from(r.clone()).with_page(page, next_page))
+        })
     }
 
-    async fn create_pull_request(
-        &self,
-        source_branch: &str,
-        target_branch: &str,
-        title: &str,
-    ) -> Result<PullRequest> {
+    async fn create_pull_request(&self, opt: CreatePullRequestOpt) -> Result<PullRequest> {
         let res = self
             .client
             .call(Method::POST, &format!("/repos/{}/pulls", self.repo))
             .body(
                 json!({
-                    "title": title,
-                    "head": source_branch,
-                    "base": target_branch,
+                    "title": opt.title,
+                    "head": opt.source_branch,
+                    "base": opt.target_branch,
+                    "body": opt.body,
+                    "draft": opt.draft,
                 })
                 .to_string(),
             )
@@ -114,8 +158,57 @@ impl Repository for GitHubRepository {
         let text = res.text().await?;
         debug!("res: {}", text);
 
-        serde_json::from_str::<GitHubResponse<Pull>>(&text)?
-            .map(|pr| Ok(PullRequest::from(pr.to_owned())))
+        let pull =
+            serde_json::from_str::<GitHubResponse<Pull>>(&text)?.map(|pr| Ok(pr.to_owned()))?;
+
+        if opt.reviewers.is_empty()
+            && opt.assignees.is_empty()
+            && opt.labels.is_empty()
+            && opt.milestone.is_none()
+        {
+            return Ok(PullRequest::from(pull));
+        }
+
+        // the pull request exists from now on, so that the rest are reported
+        // along with it instead of failing the whole command
+        let mut errors = vec![];
+        if !opt.reviewers.is_empty() {
+            if let Err(err) = self.request_reviewers(pull.number, &opt.reviewers).await {
+                errors.push(anyhow!("failed to request reviewers: {}", err));
+            }
+        }
+
+        let mut issue = json!({});
+        if !opt.assignees.is_empty() {
+            issue["assignees"] = json!(opt.assignees);
+        }
+        if !opt.labels.is_empty() {
+            issue["labels"] = json!(opt.labels);
+        }
+        if let Some(milestone) = opt.milestone.as_ref() {
+            match self.get_milestone_number(milestone).await {
+                Ok(number) => issue["milestone"] = json!(number),
+                Err(err) => errors.push(anyhow!("failed to set milestone: {}", err)),
+            }
+        }
+        if issue.as_object().is_some_and(|issue| !issue.is_empty()) {
+            if let Err(err) = self.update_issue(pull.number, issue).await {
+                errors.push(anyhow!(
+                    "failed to set assignees, labels or milestone: {}",
+                    err
+                ));
+            }
+        }
+
+        let pr = match self.get_pull_request(pull.number as usize).await {
+            Ok(pr) => pr,
+            Err(_) => PullRequest::from(pull),
+        };
+        if errors.is_empty() {
+            Ok(pr)
+        } else {
+            Err(IncompletePullRequest { pr, errors }.into())
+        }
     }
 
     async fn close_pull_request(&self, id: usize) -> Result<PullRequest> {
@@ -137,14 +230,1512 @@ impl Repository for GitHubRepository {
         serde_json::from_str::<GitHubResponse<Pull>>(&text)?
             .map(|data| Ok(PullRequest::from(data.to_owned())))
     }
-}
 
-impl GitHubRepository {
-    fn build_query(&self, pairs: &[(String, String)]) -> String {
-        pairs
+    async fn reopen_pull_request(&self, id: usize) -> Result<PullRequest> {
+        let res = self
+            .client
+            .call(Method::PATCH, &format!("/repos/{}/pulls/{}", self.repo, id))
+            .body(json!({ "state": "open" }).to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        serde_json::from_str::<GitHubResponse<Pull>>(&text)?
+            .map(|data| Ok(PullRequest::from(data.to_owned())))
+    }
+
+    async fn update_pull_request(
+        &self,
+        id: usize,
+        opt: UpdatePullRequestOpt,
+    ) -> Result<PullRequest> {
+        let pull = self.get_pull(id).await?;
+        let pr = PullRequest::from(pull.clone());
+
+        let mut body = json!({});
+        if let Some(title) = opt.title.as_ref() {
+            body["title"] = json!(title);
+        }
+        if let Some(description) = opt.body.as_ref() {
+            body["body"] = json!(description);
+        }
+        if let Some(base) = opt.target_branch.as_ref() {
+            body["base"] = json!(base);
+        }
+        if body.as_object().is_some_and(|body| !body.is_empty()) {
+            let res = self
+                .client
+                .call(Method::PATCH, &format!("/repos/{}/pulls/{}", self.repo, id))
+                .body(body.to_string())
+                .send()
+                .await?;
+
+            let text = res.text().await?;
+            debug!("res: {}", text);
+            serde_json::from_str::<GitHubResponse<Pull>>(&text)?.map(|_| Ok(()))?;
+        }
+
+        if let Some(draft) = opt.draft.filter(|draft| *draft != pr.draft) {
+            self.set_draft(&pull, draft).await?;
+        }
+
+        if let Some(reviewers) = opt.reviewers.as_ref() {
+            let removed = pr
+                .reviewers
+                .iter()
+                .filter(|reviewer| !reviewers.contains(reviewer))
+                .collect::<Vec<&String>>();
+            if !removed.is_empty() {
+                self.remove_requested_reviewers(pull.number, &removed)
+                    .await?;
+            }
+
+            let added = reviewers
+                .iter()
+                .filter(|reviewer| !pr.reviewers.contains(reviewer))
+                .cloned()
+                .collect::<Vec<String>>();
+            if !added.is_empty() {
+                self.request_reviewers(pull.number, &added).await?;
+            }
+        }
+
+        let mut issue = json!({});
+        if let Some(assignees) = opt.assignees.as_ref() {
+            issue["assignees"] = json!(assignees);
+        }
+        if let Some(labels) = opt.labels.as_ref() {
+            issue["labels"] = json!(labels);
+        }
+        if let Some(milestone) = opt.milestone.as_ref() {
+            issue["milestone"] = json!(self.get_milestone_number(milestone).await?);
+        }
+        if issue.as_object().is_some_and(|issue| !issue.is_empty()) {
+            self.update_issue(pull.number, issue).await?;
+        }
+
+        self.get_pull_request(id).await
+    }
+
+    async fn merge_pull_request(&self, id: usize, opt: MergePullRequestOpt) -> Result<PullRequest> {
+        // auto-merge of GitHub leaves deleting the branch to the repository
+        // settings
+        if opt.auto && opt.delete_branch {
+            bail!("--delete-branch cannot be used with --auto on GitHub")
+        }
+        let pull = self.get_pull(id).await?;
+
+        if opt.auto {
+            self.enable_auto_merge(&pull, &opt).await?;
+            return Ok(PullRequest::from(pull));
+        }
+
+        let merge_method = match opt.method {
+            MergeMethod::Merge => "merge",
+            MergeMethod::Squash => "squash",
+            MergeMethod::Rebase => "rebase",
+        };
+
+        let mut body = json!({
+            "merge_method": merge_method,
+        });
+        if let Some(title) = opt.title.as_ref() {
+            body["commit_title"] = json!(title);
+        }
+        if let Some(message) = opt.message.as_ref() {
+            body["commit_message"] = json!(message);
+        }
+        if let Some(sha) = opt.sha.as_ref() {
+            body["sha"] = json!(sha);
+        }
+
+        let res = self
+            .client
+            .call(
+                Method::PUT,
+                &format!("/repos/{}/pulls/{}/merge", self.repo, id),
+            )
+            .body(body.to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GitHubResponse<MergeResult>>(&text)?.map(|result| {
+            if !result.merged {
+                bail!(result.message.to_owned())
+            }
+            Ok(())
+        })?;
+
+        if opt.delete_branch {
+            self.delete_head_branch(&pull).await?;
+        }
+
+        self.get_pull_request(id).await
+    }
+
+    fn get_pull_request_ref(&self, id: usize) -> String {
+        format!("refs/pull/{}/head", id)
+    }
+
+    async fn list_pull_request_discussions(&self, id: usize) -> Result<Vec<Discussion>> {
+        let comments = self
+            .list_all::<Vec<IssueComment>, _>(&format!(
+                "/repos/{}/issues/{}/comments",
+                self.repo, id
+            ))
+            .await?;
+        let reviews = self
+            .list_all::<Vec<Review>, _>(&format!("/repos/{}/pulls/{}/reviews", self.repo, id))
+            .await?;
+        let review_comments = self
+            .list_all::<Vec<ReviewComment>, _>(&format!(
+                "/repos/{}/pulls/{}/comments",
+                self.repo, id
+            ))
+            .await?;
+
+        // summaries of reviews are shown like comments, unless they are empty
+        let mut discussions = comments
+            .into_iter()
+            .map(|comment| (comment.id, Comment::from(comment)))
+            .chain(
+                reviews
+                    .into_iter()
+                    .filter(|review| review.has_body())
+                    .map(|review| (review.id, Comment::from(review))),
+            )
+            .map(|(id, comment)| Discussion {
+                id: id.to_string(),
+                comments: vec![comment],
+                ..Default::default()
+            })
+            .collect::<Vec<Discussion>>();
+
+        // resolved state is only available through GraphQL
+        let resolved = self.get_review_thread_states(id).await.unwrap_or_else(|e| {
+            debug!("failed to get review threads: {:#?}", e);
+            HashMap::new()
+        });
+
+        // replies point to the comment they reply to, which is in a known thread
+        let mut threads: HashMap<u64, usize> = HashMap::new();
+        for comment in review_comments {
+            let thread = comment
+                .in_reply_to_id
+                .and_then(|id| threads.get(&id).copied());
+            let index = match thread {
+                Some(index) => index,
+                None => {
+                    discussions.push(Discussion {
+                        id: comment.id.to_string(),
+                        path: Some(comment.path.to_owned()),
+                        line: comment.get_line(),
+                        resolved: resolved.get(&comment.id).copied(),
+                        comments: vec![],
+                    });
+                    discussions.len() - 1
+                }
+            };
+            threads.insert(comment.id, index);
+            discussions[index].comments.push(Comment::from(comment));
+        }
+
+        discussions.sort_by(|a, b| a.comments[0].created_at.cmp(&b.comments[0].created_at));
+        Ok(discussions)
+    }
+
+    async fn comment_pull_request(&self, id: usize, body: &str) -> Result<Comment> {
+        let res = self
+            .client
+            .call(
+                Method::POST,
+                &format!("/repos/{}/issues/{}/comments", self.repo, id),
+            )
+            .body(json!({ "body": body }).to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GitHubResponse<IssueComment>>(&text)?
+            .map(|comment| Ok(Comment::from(comment.to_owned())))
+    }
+
+    async fn get_pull_request_diff(&self, id: usize) -> Result<String> {
+        let res = self
+            .client
+            .call(Method::GET, &format!("/repos/{}/pulls/{}", self.repo, id))
+            .header("Accept", "application/vnd.github.v3.diff")
+            .send()
+            .await?;
+
+        let status = res.status();
+        let text = res.text().await?;
+        if !status.is_success() {
+            debug!("res: {}", text);
+            serde_json::from_str::<GitHubResponse<Pull>>(&text)?.map(|_| Ok(()))?;
+        }
+        Ok(text)
+    }
+
+    async fn list_pull_request_files(&self, id: usize) -> Result<Vec<ChangedFile>> {
+        let files = self
+            .list_all::<Vec<PullFile>, _>(&format!("/repos/{}/pulls/{}/files", self.repo, id))
+            .await?;
+        Ok(files.into_iter().map(ChangedFile::from).collect())
+    }
+
+    async fn review_pull_request(&self, id: usize, opt: ReviewOpt) -> Result<PullRequest> {
+        if opt.needs_body() {
+            bail!("body is required unless approving")
+        }
+        let event = match opt.event {
+            ReviewEvent::Approve => "APPROVE",
+            ReviewEvent::RequestChanges => "REQUEST_CHANGES",
+            ReviewEvent::Comment => "COMMENT",
+        };
+        let comments = opt
+            .comments
             .iter()
-            .map(|(k, v)| format!("{}:{}", k, v))
-            .collect::<Vec<String>>()
-            .join(" ")
+            .map(|comment| {
+                json!({
+                    "path": comment.path,
+                    "line": comment.line,
+                    "side": "RIGHT",
+                    "body": comment.body,
+                })
+            })
+            .collect::<Vec<serde_json::Value>>();
+
+        let mut body = json!({
+            "event": event,
+            "comments": comments,
+        });
+        if let Some(review) = opt.body.as_ref() {
+            body["body"] = json!(review);
+        }
+
+        let res = self
+            .client
+            .call(
+                Method::POST,
+                &format!("/repos/{}/pulls/{}/reviews", self.repo, id),
+            )
+            .body(body.to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GitHubResponse<Review>>(&text)?.map(|review| {
+            debug!("submitted review: {}", review.id);
+            Ok(())
+        })?;
+
+        self.get_pull_request(id).await
+    }
+
+    async fn get_issue(&self, id: usize) -> Result<structs::Issue> {
+        let res = self
+            .client
+            .call(Method::GET, &format!("/repos/{}/issues/{}", self.repo, id))
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        let issue =
+            serde_json::from_str::<GitHubResponse<Issue>>(&text)?.map(|i| Ok(i.to_owned()))?;
+        if issue.pull_request.is_some() {
+            bail!("#{} is a pull request", id)
+        }
+        Ok(structs::Issue::from(issue))
+    }
+
+    async fn list_issues(&self, opt: ListIssueOpt) -> Result<PaginationResult<structs::Issue>> {
+        debug!("opt: {:#?}", opt);
+
+        let state = match opt.state {
+            Some(IssueState::Open) => "open",
+            Some(IssueState::Closed) => "closed",
+            None => "all",
+        };
+
+        let page = opt.get_page().max(1);
+        let per_page = opt.get_per_page();
+        let mut req = self
+            .client
+            .call(Method::GET, &format!("/repos/{}/issues", self.repo))
+            .query(&[("state", state)])
+            .query(&[("per_page", per_page), ("page", page)]);
+
+        if opt.me {
+            req = req.query(&[("creator", self.get_current_user().await?.login)]);
+        } else if let Some(author) = opt.author.as_ref() {
+            req = req.query(&[("creator", author)]);
+        }
+        if let Some(assignee) = opt.assignee.as_ref() {
+            req = req.query(&[("assignee", assignee)]);
+        }
+        if !opt.labels.is_empty() {
+            req = req.query(&[("labels", opt.labels.join(","))]);
+        }
+        if let Some(milestone) = opt.milestone.as_ref() {
+            req = req.query(&[("milestone", self.get_milestone_number(milestone).await?)]);
+        }
+
+        let res = req.send().await?;
+        let next_page = get_next_page(&res);
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        let result = serde_json::from_str::<GitHubResponse<Vec<Issue>>>(&text)?.map(|issues| {
+            Ok(issues
+                .iter()
+                .filter(|issue| issue.pull_request.is_none())
+                .map(|issue| structs::Issue::from(issue.to_owned()))
+                .collect::<Vec<structs::Issue>>())
+        })?;
+
+        // the issues API does not count, and pull requests are filtered out
+        Ok(PaginationResult::new(result, None).with_page(page, next_page))
+    }
+
+    async fn create_issue(&self, opt: CreateIssueOpt) -> Result<structs::Issue> {
+        let mut body = json!({
+            "title": opt.title,
+            "body": opt.body,
+        });
+        if !opt.assignees.is_empty() {
+            body["assignees"] = json!(opt.assignees);
+        }
+        if !opt.labels.is_empty() {
+            body["labels"] = json!(opt.labels);
+        }
+        if let Some(milestone) = opt.milestone.as_ref() {
+            body["milestone"] = json!(self.get_milestone_number(milestone).await?);
+        }
+
+        let res = self
+            .client
+            .call(Method::POST, &format!("/repos/{}/issues", self.repo))
+            .body(body.to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GitHubResponse<Issue>>(&text)?
+            .map(|issue| Ok(structs::Issue::from(issue.to_owned())))
+    }
+
+    async fn close_issue(&self, id: usize) -> Result<structs::Issue> {
+        self.set_issue_state(id, IssueState::Closed).await
+    }
+
+    async fn reopen_issue(&self, id: usize) -> Result<structs::Issue> {
+        self.set_issue_state(id, IssueState::Open).await
+    }
+
+    async fn list_checks(&self, git_ref: &str) -> Result<Vec<Check>> {
+        let git_ref = utils::encode_segment(git_ref);
+        let mut checks = self
+            .list_all::<CheckRuns, _>(&format!(
+                "/repos/{}/commits/{}/check-runs",
+                self.repo, git_ref
+            ))
+            .await?
+            .into_iter()
+            .map(Check::from)
+            .collect::<Vec<Check>>();
+
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/repos/{}/commits/{}/status", self.repo, git_ref),
+            )
+            .query(&[("per_page", "100")])
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        let statuses =
+            serde_json::from_str::<GitHubResponse<CombinedStatus>>(&text)?.map(|combined| {
+                Ok(combined
+                    .statuses
+                    .iter()
+                    .map(|status| Check::from(status.to_owned()))
+                    .collect::<Vec<Check>>())
+            })?;
+        checks.extend(statuses);
+
+        Ok(checks)
+    }
+
+    async fn list_pipelines(&self, branch: Option<&str>) -> Result<Vec<Pipeline>> {
+        let mut query = vec![("per_page", "20")];
+        if let Some(branch) = branch {
+            query.push(("branch", branch));
+        }
+
+        let res = self
+            .client
+            .call(Method::GET, &format!("/repos/{}/actions/runs", self.repo))
+            .query(&query)
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GitHubResponse<WorkflowRuns>>(&text)?.map(|runs| {
+            Ok(runs
+                .workflow_runs
+                .iter()
+                .map(|run| Pipeline::from(run.to_owned()))
+                .collect())
+        })
+    }
+
+    async fn get_pipeline(&self, id: u64) -> Result<Pipeline> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/repos/{}/actions/runs/{}", self.repo, id),
+            )
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        let mut pipeline = serde_json::from_str::<GitHubResponse<WorkflowRun>>(&text)?
+            .map(|run| Ok(Pipeline::from(run.to_owned())))?;
+
+        pipeline.jobs = self
+            .list_all::<Jobs, _>(&format!("/repos/{}/actions/runs/{}/jobs", self.repo, id))
+            .await?
+            .into_iter()
+            .map(Check::from)
+            .collect();
+
+        Ok(pipeline)
+    }
+
+    async fn retry_pipeline(&self, id: u64, failed_only: bool) -> Result<Pipeline> {
+        let action = if failed_only {
+            "rerun-failed-jobs"
+        } else {
+            "rerun"
+        };
+        self.call_workflow_run_action(id, action).await?;
+        self.get_pipeline(id).await
+    }
+
+    async fn cancel_pipeline(&self, id: u64) -> Result<Pipeline> {
+        self.call_workflow_run_action(id, "cancel").await?;
+        self.get_pipeline(id).await
+    }
+
+    async fn get_job(&self, id: u64) -> Result<Check> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/repos/{}/actions/jobs/{}", self.repo, id),
+            )
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GitHubResponse<CheckRun>>(&text)?
+            .map(|job| Ok(Check::from(job.to_owned())))
+    }
+
+    /// GitHub redirects to the archived log, which is only available after
+    /// the job is completed.
+    async fn get_job_log(&self, id: u64) -> Result<String> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/repos/{}/actions/jobs/{}/logs", self.repo, id),
+            )
+            .send()
+            .await?;
+
+        let status = res.status();
+        let text = res.text().await?;
+        if !status.is_success() {
+            debug!("res: {}", text);
+            serde_json::from_str::<GitHubResponse<CheckRun>>(&text)?.map(|_| Ok(()))?;
+        }
+        Ok(text)
+    }
+
+    async fn can_push(&self) -> Result<bool> {
+        let res = self
+            .client
+            .call(Method::GET, &format!("/repos/{}", self.repo))
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GitHubResponse<Repo>>(&text)?.map(|repo| {
+            Ok(repo
+                .permissions
+                .as_ref()
+                .map(|permissions| permissions.push)
+                .unwrap_or(false))
+        })
+    }
+
+    async fn fork_repository(&self, owner: Option<&str>) -> Result<structs::Repo> {
+        let mut body = json!({});
+        if let Some(owner) = owner {
+            body["organization"] = json!(owner);
+        }
+
+        let res = self
+            .client
+            .call(Method::POST, &format!("/repos/{}/forks", self.repo))
+            .body(body.to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        let fork =
+            serde_json::from_str::<GitHubResponse<Repo>>(&text)?.map(|repo| Ok(repo.to_owned()))?;
+
+        // commits of the fork are unavailable until it is ready
+        for _ in 0..FORK_POLL_TIMES {
+            let res = self
+                .client
+                .call(Method::GET, &format!("/repos/{}/commits", fork.full_name))
+                .query(&[("per_page", 1)])
+                .send()
+                .await?;
+            if res.status().is_success() {
+                return Ok(structs::Repo::from(fork));
+            }
+            debug!("fork is not ready: {}", res.status());
+            tokio::time::sleep(FORK_POLL_INTERVAL).await;
+        }
+        bail!("fork {} is not ready yet, try again later", fork.full_name)
+    }
+}
+
+impl GitHubRepository {
+    async fn get_pull(&self, id: usize) -> Result<Pull> {
+        let res = self
+            .client
+            .call(Method::GET, &format!("/repos/{}/pulls/{}", self.repo, id))
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GitHubResponse<Pull>>(&text)?.map(|pr| Ok(pr.to_owned()))
+    }
+
+    /// Fetch a page of a list API, where the total is unknown. Items are
+    /// returned as `R` like `Vec<T>`, or an object like `CheckRuns` for some
+    /// APIs.
+    async fn list_page<R, T>(&self, uri: &str, page: usize) -> Result<PaginationResult<T>>
+    where
+        R: DeserializeOwned + Clone + fmt::Debug + Into<Vec<T>>,
+    {
+        let res = self
+            .client
+            .call(Method::GET, uri)
+            .query(&[("per_page", 100), ("page", page)])
+            .send()
+            .await?;
+        let next_page = get_next_page(&res);
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        let result = serde_json::from_str::<GitHubResponse<R>>(&text)?
+            .map(|items| Ok(items.to_owned().into()))?;
+        Ok(PaginationResult::new(result, None).with_page(page, next_page))
+    }
+
+    /// Fetch all items of a list API page by page.
+    async fn list_all<R, T>(&self, uri: &str) -> Result<Vec<T>>
+    where
+        R: DeserializeOwned + Clone + fmt::Debug + Into<Vec<T>> + Send,
+        T: Send,
+    {
+        paginate(1, None, |page| self.list_page::<R, T>(uri, page))
+            .try_collect()
+            .await
+    }
+
+    async fn enable_auto_merge(&self, pull: &Pull, opt: &MergePullRequestOpt) -> Result<()> {
+        let merge_method = match opt.method {
+            MergeMethod::Merge => "MERGE",
+            MergeMethod::Squash => "SQUASH",
+            MergeMethod::Rebase => "REBASE",
+        };
+
+        let res = self
+            .client
+            .graphql(
+                "mutation($input: EnablePullRequestAutoMergeInput!) {
+                    enablePullRequestAutoMerge(input: $input) { clientMutationId }
+                }",
+                json!({
+                    "input": {
+                        "pullRequestId": pull.node_id,
+                        "mergeMethod": merge_method,
+                        "commitHeadline": opt.title,
+                        "commitBody": opt.message,
+                        "expectedHeadOid": opt.sha,
+                    }
+                }),
+            )
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        let data = serde_json::from_str::<serde_json::Value>(&text)?;
+        if let Some(message) = data["errors"][0]["message"].as_str() {
+            bail!(message.to_string())
+        }
+        Ok(())
+    }
+
+    async fn delete_head_branch(&self, pull: &Pull) -> Result<()> {
+        let head = pull
+            .head
+            .as_ref()
+            .ok_or_else(|| anyhow!("cannot resolve head branch of pull request"))?;
+
+        let head_repo = head.repo.as_ref().map(|repo| repo.full_name.as_str());
+        if head_repo != Some(self.repo.as_str()) {
+            bail!("head branch {} belongs to another repository", head.name)
+        }
+
+        let res = self
+            .client
+            .call(
+                Method::DELETE,
+                &format!("/repos/{}/git/refs/heads/{}", self.repo, head.name),
+            )
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            debug!("res: {}", res.text().await?);
+            bail!("failed to delete branch {}", head.name)
+        }
+        Ok(())
+    }
+
+    /// Request reviews from users, or teams if given in the form of
+    /// `org/team-slug`.
+    async fn request_reviewers(&self, number: u64, reviewers: &[String]) -> Result<()> {
+        let (teams, users): (Vec<&String>, Vec<&String>) = reviewers
+            .iter()
+            .partition(|reviewer| reviewer.contains('/'));
+        let teams = teams
+            .iter()
+            .filter_map(|team| team.split('/').nth(1))
+            .collect::<Vec<&str>>();
+
+        let res = self
+            .client
+            .call(
+                Method::POST,
+                &format!("/repos/{}/pulls/{}/requested_reviewers", self.repo, number),
+            )
+            .body(
+                json!({
+                    "reviewers": users,
+                    "team_reviewers": teams,
+                })
+                .to_string(),
+            )
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GitHubResponse<Pull>>(&text)?.map(|_| Ok(()))
+    }
+
+    async fn remove_requested_reviewers(&self, number: u64, reviewers: &[&String]) -> Result<()> {
+        let res = self
+            .client
+            .call(
+                Method::DELETE,
+                &format!("/repos/{}/pulls/{}/requested_reviewers", self.repo, number),
+            )
+            .body(json!({ "reviewers": reviewers }).to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GitHubResponse<Pull>>(&text)?.map(|_| Ok(()))
+    }
+
+    /// Draft state can only be changed through the GraphQL API.
+    async fn set_draft(&self, pull: &Pull, draft: bool) -> Result<()> {
+        let query = if draft {
+            "mutation($input: ConvertPullRequestToDraftInput!) {
+                convertPullRequestToDraft(input: $input) { clientMutationId }
+            }"
+        } else {
+            "mutation($input: MarkPullRequestReadyForReviewInput!) {
+                markPullRequestReadyForReview(input: $input) { clientMutationId }
+            }"
+        };
+
+        let res = self
+            .client
+            .graphql(query, json!({ "input": { "pullRequestId": pull.node_id } }))
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        let data = serde_json::from_str::<serde_json::Value>(&text)?;
+        if let Some(message) = data["errors"][0]["message"].as_str() {
+            bail!(message.to_string())
+        }
+        Ok(())
+    }
+
+    /// Pull requests are issues on GitHub, so that assignees, labels and
+    /// milestone are updated through the issues API.
+    async fn update_issue(&self, number: u64, data: serde_json::Value) -> Result<()> {
+        let res = self
+            .client
+            .call(
+                Method::PATCH,
+                &format!("/repos/{}/issues/{}", self.repo, number),
+            )
+            .body(data.to_string())
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            let text = res.text().await?;
+            debug!("res: {}", text);
+            serde_json::from_str::<GitHubResponse<Pull>>(&text)?.map(|_| Ok(()))?;
+        }
+        Ok(())
+    }
+
+    /// Actions on workflow runs respond with no content.
+    async fn call_workflow_run_action(&self, id: u64, action: &str) -> Result<()> {
+        let res = self
+            .client
+            .call(
+                Method::POST,
+                &format!("/repos/{}/actions/runs/{}/{}", self.repo, id, action),
+            )
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            let text = res.text().await?;
+            debug!("res: {}", text);
+            serde_json::from_str::<GitHubResponse<WorkflowRun>>(&text)?.map(|_| Ok(()))?;
+        }
+        Ok(())
+    }
+
+    async fn get_milestone_number(&self, milestone: &str) -> Result<u64> {
+        if let Ok(number) = milestone.parse::<u64>() {
+            return Ok(number);
+        }
+
+        let res = self
+            .client
+            .call(Method::GET, &format!("/repos/{}/milestones", self.repo))
+            .query(&[("state", "open"), ("per_page", "100")])
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GitHubResponse<Vec<Milestone>>>(&text)?.map(|milestones| {
+            milestones
+                .iter()
+                .find(|m| m.title == milestone)
+                .map(|m| m.number)
+                .ok_or_else(|| anyhow!("milestone not found: {}", milestone))
+        })
+    }
+
+    /// Resolved state of review threads, by the id of their first comments.
+    async fn get_review_thread_states(&self, number: usize) -> Result<HashMap<u64, bool>> {
+        let (owner, name) = self
+            .repo
+            .split_once('/')
+            .ok_or_else(|| anyhow!("invalid repository name: {}", self.repo))?;
+
+        let mut states = HashMap::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let res = self
+                .client
+                .graphql(
+                    "query($owner: String!, $name: String!, $number: Int!, $cursor: String) {
+                        repository(owner: $owner, name: $name) {
+                            pullRequest(number: $number) {
+                                reviewThreads(first: 100, after: $cursor) {
+                                    pageInfo { hasNextPage endCursor }
+                                    nodes {
+                                        isResolved
+                                        comments(first: 1) { nodes { databaseId } }
+                                    }
+                                }
+                            }
+                        }
+                    }",
+                    json!({
+                        "owner": owner,
+                        "name": name,
+                        "number": number,
+                        "cursor": cursor,
+                    }),
+                )
+                .await?;
+
+            let text = res.text().await?;
+            debug!("res: {}", text);
+
+            let data = serde_json::from_str::<serde_json::Value>(&text)?;
+            if let Some(message) = data["errors"][0]["message"].as_str() {
+                bail!(message.to_string())
+            }
+
+            let threads = &data["data"]["repository"]["pullRequest"]["reviewThreads"];
+            states.extend(
+                threads["nodes"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|thread| {
+                        let id = thread["comments"]["nodes"][0]["databaseId"].as_u64()?;
+                        let resolved = thread["isResolved"].as_bool()?;
+                        Some((id, resolved))
+                    }),
+            );
+
+            cursor = threads["pageInfo"]["endCursor"]
+                .as_str()
+                .map(|cursor| cursor.to_string())
+                .filter(|_| threads["pageInfo"]["hasNextPage"].as_bool() == Some(true));
+            if cursor.is_none() {
+                return Ok(states);
+            }
+        }
+    }
+
+    async fn get_current_user(&self) -> Result<User> {
+        let res = self.client.call(Method::GET, "/user").send().await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GitHubResponse<User>>(&text)?.map(|user| Ok(user.to_owned()))
+    }
+
+    async fn set_issue_state(&self, id: usize, state: IssueState) -> Result<structs::Issue> {
+        // pull requests can be closed or reopened through the issues API too
+        self.get_issue(id).await?;
+
+        let res = self
+            .client
+            .call(
+                Method::PATCH,
+                &format!("/repos/{}/issues/{}", self.repo, id),
+            )
+            .body(json!({ "state": state }).to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("res: {}", text);
+
+        serde_json::from_str::<GitHubResponse<Issue>>(&text)?
+            .map(|issue| Ok(structs::Issue::from(issue.to_owned())))
+    }
+
+    fn build_query(&self, pairs: &[(String, String)]) -> String {
+        pairs
+            .iter()
+            .map(|(k, v)| {
+                if v.contains(char::is_whitespace) {
+                    format!("{}:\"{}\"", k, v)
+                } else {
+                    format!("{}:{}", k, v)
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mockito::{mock, server_url, Matcher};
+
+    use crate::github::fixtures::{get_repo, issue, pull};
+    use crate::repository::ListPullRequestOpt;
+    use crate::structs::CheckState;
+
+    #[tokio::test]
+    async fn test_list_pull_requests_by_state() -> Result<()> {
+        let cases = vec![
+            (
+                Some(PullRequestState::Open),
+                "is:pr is:open repo:owner/repo",
+            ),
+            (
+                Some(PullRequestState::Closed),
+                "is:pr is:closed is:unmerged repo:owner/repo",
+            ),
+            (
+                Some(PullRequestState::Merged),
+                "is:pr is:merged repo:owner/repo",
+            ),
+            (None, "is:pr repo:owner/repo"),
+        ];
+        for (state, query) in cases {
+            let m = mock("GET", "/search/issues")
+                .match_query(Matcher::UrlEncoded("q".into(), query.into()))
+                .with_body(r#"{ "total_count": 0, "incomplete_results": false, "items": [] }"#)
+                .create();
+
+            let mut opt = ListPullRequestOpt::default();
+            opt.state = state;
+            get_repo().list_pull_requests(opt).await?;
+            m.assert();
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_pull_requests_with_filters() -> Result<()> {
+        let _m = mock("GET", "/search/issues")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded(
+                    "q".into(),
+                    "is:pr is:open repo:owner/repo review-requested:someone label:\"help wanted\" base:main draft:false fix crash".into(),
+                ),
+                Matcher::UrlEncoded("sort".into(), "updated".into()),
+                Matcher::UrlEncoded("order".into(), "asc".into()),
+            ]))
+            .with_body(r#"{ "total_count": 0, "incomplete_results": false, "items": [] }"#)
+            .create();
+
+        let mut opt = ListPullRequestOpt::default();
+        opt.state = Some(PullRequestState::Open);
+        opt.reviewer = Some("someone".to_string());
+        opt.labels = vec!["help wanted".to_string()];
+        opt.base = Some("main".to_string());
+        opt.draft = Some(false);
+        opt.search = Some("fix crash".to_string());
+        opt.sort = Some(PullRequestSort::Updated);
+        opt.ascending = true;
+        let result = get_repo().list_pull_requests(opt).await?;
+        assert_eq!(result.total, Some(0));
+        _m.assert();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_pull_request_with_failed_reviewers() -> Result<()> {
+        let _m1 = mock("POST", "/repos/owner/repo/pulls")
+            .with_status(201)
+            .with_body(pull(3))
+            .create();
+        let _m2 = mock("POST", "/repos/owner/repo/pulls/3/requested_reviewers")
+            .with_status(422)
+            .with_body(r#"{ "message": "Reviews may only be requested from collaborators." }"#)
+            .create();
+        let _m3 = mock("GET", "/repos/owner/repo/pulls/3")
+            .with_body(pull(3))
+            .create();
+
+        let opt = CreatePullRequestOpt {
+            title: "add something".to_string(),
+            source_branch: "feature".to_string(),
+            target_branch: "main".to_string(),
+            reviewers: vec!["stranger".to_string()],
+            ..Default::default()
+        };
+        let err = get_repo().create_pull_request(opt).await.unwrap_err();
+        let incomplete = err.downcast::<IncompletePullRequest>()?;
+        assert_eq!(incomplete.pr.id, 3);
+        assert_eq!(incomplete.errors.len(), 1);
+        assert!(incomplete.errors[0].to_string().contains("collaborators"));
+        Ok(())
+    }
+
+    fn repo(full_name: &str, parent: &str) -> String {
+        format!(
+            r#"{{
+                "full_name": "{}",
+                "description": "",
+                "private": false,
+                "parent": {},
+                "default_branch": "master",
+                "html_url": "https://github.com/{}",
+                "clone_url": "https://github.com/{}.git",
+                "ssh_url": "git@github.com:{}.git",
+                "permissions": {{ "admin": false, "push": false, "pull": true }}
+            }}"#,
+            full_name, parent, full_name, full_name, full_name
+        )
+    }
+
+    #[tokio::test]
+    async fn test_auto_merge_with_delete_branch() {
+        let opt = MergePullRequestOpt {
+            auto: true,
+            delete_branch: true,
+            ..Default::default()
+        };
+        let err = get_repo().merge_pull_request(1, opt).await.unwrap_err();
+        assert!(err.to_string().contains("--delete-branch"));
+    }
+
+    #[tokio::test]
+    async fn test_can_push() -> Result<()> {
+        let _m = mock("GET", "/repos/owner/repo")
+            .with_body(repo("owner/repo", "null"))
+            .create();
+
+        assert!(!get_repo().can_push().await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fork_repository() -> Result<()> {
+        let _m = mock("POST", "/repos/owner/repo/forks")
+            .match_body(Matcher::Json(json!({ "organization": "org" })))
+            .with_status(202)
+            .with_body(repo("org/repo", r#"{ "full_name": "owner/repo" }"#))
+            .create();
+        let _m2 = mock("GET", "/repos/org/repo/commits")
+            .match_query(Matcher::Any)
+            .with_body("[]")
+            .create();
+
+        let fork = get_repo().fork_repository(Some("org")).await?;
+        assert_eq!(fork.name, "org/repo");
+        assert_eq!(fork.parent.as_deref(), Some("owner/repo"));
+        assert_eq!(fork.description, None);
+        assert_eq!(fork.ssh_url.as_deref(), Some("git@github.com:org/repo.git"));
+        _m2.assert();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_issues() -> Result<()> {
+        let _m = mock("GET", "/repos/owner/repo/issues")
+            .match_header("authorization", "token token")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("state".into(), "open".into()),
+                Matcher::UrlEncoded("creator".into(), "someone".into()),
+                Matcher::UrlEncoded("labels".into(), "bug,help wanted".into()),
+            ]))
+            .with_body(format!("[{}, {}]", issue(1, false), issue(2, true)))
+            .create();
+
+        let mut opt = ListIssueOpt::default();
+        opt.author = Some("someone".to_string());
+        opt.labels = vec!["bug".to_string(), "help wanted".to_string()];
+        opt.state = Some(IssueState::Open);
+        let result = get_repo().list_issues(opt).await?;
+        assert_eq!(result.total, None);
+        assert_eq!(result.result.len(), 1);
+        assert_eq!(result.result[0].id, 1);
+        assert_eq!(result.result[0].milestone.as_deref(), Some("v1.0"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_issue_of_pull_request() -> Result<()> {
+        let _m = mock("GET", "/repos/owner/repo/issues/2")
+            .with_body(issue(2, true))
+            .create();
+
+        let err = get_repo().get_issue(2).await.unwrap_err();
+        assert_eq!(err.to_string(), "#2 is a pull request");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_close_issue_of_pull_request() -> Result<()> {
+        let _m1 = mock("GET", "/repos/owner/repo/issues/2")
+            .with_body(issue(2, true))
+            .create();
+        let m2 = mock("PATCH", "/repos/owner/repo/issues/2")
+            .with_body(issue(2, true))
+            .expect(0)
+            .create();
+
+        let err = get_repo().close_issue(2).await.unwrap_err();
+        assert_eq!(err.to_string(), "#2 is a pull request");
+        m2.assert();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_pull_request_discussions() -> Result<()> {
+        let _m1 = mock("GET", "/repos/owner/repo/issues/3/comments")
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .with_header(
+                "link",
+                &format!(
+                    r#"<{}/repos/owner/repo/issues/3/comments?page=2>; rel="next""#,
+                    server_url()
+                ),
+            )
+            .with_body(
+                r#"[{
+                    "id": 10,
+                    "html_url": "https://github.com/owner/repo/pull/3#issuecomment-10",
+                    "body": "looks good",
+                    "user": { "login": "someone" },
+                    "created_at": "2020-09-03T00:00:00Z"
+                }]"#,
+            )
+            .create();
+        let _m4 = mock("GET", "/repos/owner/repo/issues/3/comments")
+            .match_query(Matcher::UrlEncoded("page".into(), "2".into()))
+            .with_body(
+                r#"[{
+                    "id": 11,
+                    "html_url": "https://github.com/owner/repo/pull/3#issuecomment-11",
+                    "body": "thanks",
+                    "user": { "login": "someone" },
+                    "created_at": "2020-09-04T00:00:00Z"
+                }]"#,
+            )
+            .create();
+        let _m5 = mock("GET", "/repos/owner/repo/pulls/3/reviews")
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"[{
+                    "id": 30,
+                    "html_url": "https://github.com/owner/repo/pull/3#pullrequestreview-30",
+                    "body": "some nits",
+                    "user": { "login": "reviewer" },
+                    "submitted_at": "2020-09-02T12:00:00Z"
+                }, {
+                    "id": 31,
+                    "html_url": "https://github.com/owner/repo/pull/3#pullrequestreview-31",
+                    "body": "",
+                    "user": { "login": "reviewer" },
+                    "submitted_at": "2020-09-02T13:00:00Z"
+                }]"#,
+            )
+            .create();
+        let _m2 = mock("GET", "/repos/owner/repo/pulls/3/comments")
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"[{
+                    "id": 20,
+                    "in_reply_to_id": null,
+                    "path": "src/main.rs",
+                    "line": null,
+                    "original_line": 12,
+                    "html_url": "https://github.com/owner/repo/pull/3#discussion_r20",
+                    "body": "typo",
+                    "user": { "login": "reviewer" },
+                    "created_at": "2020-09-01T00:00:00Z"
+                }, {
+                    "id": 21,
+                    "in_reply_to_id": 20,
+                    "path": "src/main.rs",
+                    "line": null,
+                    "original_line": 12,
+                    "html_url": "https://github.com/owner/repo/pull/3#discussion_r21",
+                    "body": "fixed",
+                    "user": { "login": "someone" },
+                    "created_at": "2020-09-02T00:00:00Z"
+                }]"#,
+            )
+            .create();
+        let _m3 = mock("POST", "/graphql")
+            .with_body(
+                r#"{ "data": { "repository": { "pullRequest": { "reviewThreads": { "nodes": [
+                    { "isResolved": true, "comments": { "nodes": [{ "databaseId": 20 }] } }
+                ] } } } } }"#,
+            )
+            .create();
+
+        let discussions = get_repo().list_pull_request_discussions(3).await?;
+        assert_eq!(discussions.len(), 4);
+        assert_eq!(discussions[0].path.as_deref(), Some("src/main.rs"));
+        assert_eq!(discussions[0].line, Some(12));
+        assert_eq!(discussions[0].resolved, Some(true));
+        assert_eq!(discussions[0].comments.len(), 2);
+        assert_eq!(discussions[0].comments[1].author, "someone");
+        assert_eq!(discussions[1].comments[0].author, "reviewer");
+        assert_eq!(discussions[1].comments[0].body, "some nits");
+        assert_eq!(discussions[2].resolved, None);
+        assert_eq!(discussions[2].comments[0].body, "looks good");
+        assert_eq!(discussions[3].comments[0].body, "thanks");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_review_pull_request_without_body() -> Result<()> {
+        let m = mock("POST", "/repos/owner/repo/pulls/4/reviews")
+            .expect(0)
+            .create();
+
+        let opt = ReviewOpt {
+            event: ReviewEvent::RequestChanges,
+            comments: ReviewOpt::parse_inline_comments(&["src/main.rs:1", "typo"])?,
+            ..Default::default()
+        };
+        let err = get_repo().review_pull_request(4, opt).await.unwrap_err();
+        assert_eq!(err.to_string(), "body is required unless approving");
+        m.assert();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_pull_request_files() -> Result<()> {
+        let _m1 = mock("GET", "/repos/owner/repo/pulls/4/files")
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .with_header(
+                "link",
+                &format!(
+                    r#"<{}/repos/owner/repo/pulls/4/files?page=2>; rel="next""#,
+                    server_url()
+                ),
+            )
+            .with_body(
+                r#"[{
+                    "filename": "src/lib.rs",
+                    "previous_filename": "src/main.rs",
+                    "status": "renamed",
+                    "additions": 3,
+                    "deletions": 1
+                }]"#,
+            )
+            .create();
+        let _m2 = mock("GET", "/repos/owner/repo/pulls/4/files")
+            .match_query(Matcher::UrlEncoded("page".into(), "2".into()))
+            .with_body(
+                r#"[{
+                    "filename": "README.md",
+                    "status": "modified",
+                    "additions": 1,
+                    "deletions": 1
+                }]"#,
+            )
+            .create();
+
+        let files = get_repo().list_pull_request_files(4).await?;
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "src/lib.rs");
+        assert_eq!(files[0].old_path.as_deref(), Some("src/main.rs"));
+        assert_eq!(files[0].status, crate::structs::FileStatus::Renamed);
+        assert_eq!(files[0].additions, Some(3));
+        assert_eq!(files[1].path, "README.md");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_checks() -> Result<()> {
+        let _m1 = mock("GET", "/repos/owner/repo/commits/abc/check-runs")
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"{ "total_count": 2, "check_runs": [{
+                    "id": 1,
+                    "name": "build",
+                    "status": "completed",
+                    "conclusion": "success",
+                    "started_at": "2020-09-01T00:00:00Z",
+                    "completed_at": "2020-09-01T00:02:05Z",
+                    "html_url": "https://github.com/owner/repo/runs/1"
+                }, {
+                    "id": 2,
+                    "name": "test",
+                    "status": "in_progress",
+                    "conclusion": null,
+                    "started_at": "2020-09-01T00:00:00Z",
+                    "completed_at": null,
+                    "html_url": "https://github.com/owner/repo/runs/2"
+                }] }"#,
+            )
+            .create();
+        let _m2 = mock("GET", "/repos/owner/repo/commits/abc/status")
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"{ "state": "failure", "statuses": [{
+                    "id": 3,
+                    "context": "ci/jenkins",
+                    "state": "error",
+                    "target_url": null,
+                    "created_at": "2020-09-01T00:00:00Z",
+                    "updated_at": "2020-09-01T00:01:00Z"
+                }] }"#,
+            )
+            .create();
+
+        let checks = get_repo().list_checks("abc").await?;
+        assert_eq!(checks.len(), 3);
+        assert_eq!(checks[0].state, CheckState::Success);
+        assert_eq!(checks[0].duration, Some(125));
+        assert_eq!(checks[1].state, CheckState::Running);
+        assert_eq!(checks[2].name, "ci/jenkins");
+        assert_eq!(
+            CheckState::combine(checks.iter().map(|check| &check.state)),
+            CheckState::Failure
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_checks_of_branch() -> Result<()> {
+        let run = |id: u64| {
+            format!(
+                r#"{{
+                    "id": {},
+                    "name": "build",
+                    "status": "queued",
+                    "conclusion": null,
+                    "started_at": null,
+                    "completed_at": null,
+                    "html_url": "https://github.com/owner/repo/runs/{}"
+                }}"#,
+                id, id
+            )
+        };
+        let _m1 = mock("GET", "/repos/owner/repo/commits/fix%2F%2312/check-runs")
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .with_header(
+                "link",
+                &format!(
+                    r#"<{}/repos/owner/repo/commits/fix%2F%2312/check-runs?page=2>; rel="next""#,
+                    server_url()
+                ),
+            )
+            .with_body(format!(
+                r#"{{ "total_count": 2, "check_runs": [{}] }}"#,
+                run(1)
+            ))
+            .create();
+        let _m2 = mock("GET", "/repos/owner/repo/commits/fix%2F%2312/check-runs")
+            .match_query(Matcher::UrlEncoded("page".into(), "2".into()))
+            .with_body(format!(
+                r#"{{ "total_count": 2, "check_runs": [{}] }}"#,
+                run(2)
+            ))
+            .create();
+        let _m3 = mock("GET", "/repos/owner/repo/commits/fix%2F%2312/status")
+            .match_query(Matcher::Any)
+            .with_body(r#"{ "state": "pending", "statuses": [] }"#)
+            .create();
+
+        let checks = get_repo().list_checks("fix/#12").await?;
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[1].state, CheckState::Pending);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_job_log() -> Result<()> {
+        let _m1 = mock("GET", "/repos/owner/repo/actions/jobs/5/logs")
+            .with_body("2020-09-01T00:00:00.0000000Z hello\n")
+            .create();
+        let _m2 = mock("GET", "/repos/owner/repo/actions/jobs/6/logs")
+            .with_status(404)
+            .with_body(r#"{ "message": "Not Found" }"#)
+            .create();
+
+        let repo = get_repo();
+        assert!(repo.get_job_log(5).await?.ends_with("hello\n"));
+        assert_eq!(
+            repo.get_job_log(6).await.unwrap_err().to_string(),
+            "Not Found"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_pull_request() -> Result<()> {
+        let pull = r#"{
+                    "id": 1,
+                    "node_id": "PR_7",
+                    "html_url": "https://github.com/owner/repo/pull/7",
+                    "title": "fix",
+                    "user": { "login": "someone" },
+                    "number": 7,
+                    "state": "open",
+                    "draft": true,
+                    "requested_reviewers": [{ "login": "old" }],
+                    "created_at": "2020-09-01T00:00:00Z",
+                    "updated_at": "2020-09-01T00:00:00Z"
+                }"#;
+        let _m1 = mock("GET", "/repos/owner/repo/pulls/7")
+            .with_body(pull)
+            .create();
+        let m2 = mock("POST", "/graphql")
+            .match_body(Matcher::Regex("markPullRequestReadyForReview".to_string()))
+            .with_body(r#"{ "data": {} }"#)
+            .create();
+        let m3 = mock("DELETE", "/repos/owner/repo/pulls/7/requested_reviewers")
+            .match_body(Matcher::Json(serde_json::json!({ "reviewers": ["old"] })))
+            .with_body(pull)
+            .create();
+        let m4 = mock("POST", "/repos/owner/repo/pulls/7/requested_reviewers")
+            .match_body(Matcher::PartialJson(
+                serde_json::json!({ "reviewers": ["new"] }),
+            ))
+            .with_body(pull)
+            .create();
+
+        let opt = UpdatePullRequestOpt {
+            draft: Some(false),
+            reviewers: Some(vec!["new".to_string()]),
+            ..Default::default()
+        };
+        get_repo().update_pull_request(7, opt).await?;
+        m2.assert();
+        m3.assert();
+        m4.assert();
+        Ok(())
     }
 }
@@ -4,15 +4,17 @@ use std::fmt;
 
 use serde_derive::*;
 
-use crate::structs::{PaginationResult, PullRequest};
+use crate::structs::{
+    self, ChangedFile, Check, CheckState, Comment, FileStatus, IssueState, PaginationResult,
+    Pipeline, PullRequest, PullRequestState, Visibility,
+};
+use crate::utils;
 
 #[derive(Deserialize)]
 pub struct DeviceCode {
     pub device_code: String,
     pub user_code: String,
     pub verification_uri: String,
-    pub expires_in: usize,
-    pub interval: usize,
 }
 
 #[derive(Deserialize)]
@@ -34,10 +36,7 @@ pub struct AccessToken {
 #[serde(untagged)]
 pub enum GitHubResponse<T> {
     Ok(T),
-    Error {
-        error: Option<String>,
-        message: String,
-    },
+    Error { message: String },
 }
 
 impl<T> GitHubResponse<T>
@@ -51,7 +50,7 @@ where
     {
         match self {
             GitHubResponse::Ok(data) => f(data),
-            GitHubResponse::Error { error, message } => {
+            GitHubResponse::Error { message, .. } => {
                 debug!("found an error: {:#?}", self);
                 bail!(message.to_string())
             }
@@ -61,53 +60,479 @@ where
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct SearchResult<T> {
-    total_count: u64,
-    incomplete_results: bool,
+    pub total_count: u64,
     items: Vec<T>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct Pull {
-    id: u64,
+    pub node_id: String,
     html_url: String,
     title: String,
     user: User,
-    number: u64,
+    pub number: u64,
+    state: String,
+    draft: Option<bool>,
+    body: Option<String>,
+    #[serde(default)]
+    labels: Vec<Label>,
+    #[serde(default)]
+    assignees: Vec<User>,
+    #[serde(default)]
+    requested_reviewers: Vec<User>,
+    mergeable: Option<bool>,
+    commits: Option<u64>,
+    additions: Option<u64>,
+    deletions: Option<u64>,
+    changed_files: Option<u64>,
+    merged_at: Option<String>,
+    /// Only presents in search results, where pulls are listed as issues.
+    pull_request: Option<PullRequestLinks>,
     base: Option<Ref>,
-    head: Option<Ref>,
+    pub head: Option<Ref>,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Issue {
+    pub number: u64,
+    html_url: String,
+    title: String,
+    user: User,
+    state: String,
+    body: Option<String>,
+    #[serde(default)]
+    labels: Vec<Label>,
+    #[serde(default)]
+    assignees: Vec<User>,
+    milestone: Option<Milestone>,
+    comments: Option<u64>,
+    /// Pull requests are also listed as issues, with this field present.
+    pub pull_request: Option<serde_json::Value>,
+    created_at: String,
     updated_at: String,
+    closed_at: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct IssueComment {
+    pub id: u64,
+    html_url: String,
+    body: String,
+    user: User,
+    created_at: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PullFile {
+    filename: String,
+    previous_filename: Option<String>,
+    status: String,
+    additions: u64,
+    deletions: u64,
+}
+
+/// Check run of GitHub Apps, including jobs of GitHub Actions.
+#[derive(Deserialize, Debug, Clone)]
+pub struct CheckRun {
+    id: u64,
+    name: String,
+    status: String,
+    conclusion: Option<String>,
+    started_at: Option<String>,
+    completed_at: Option<String>,
+    html_url: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CheckRuns {
+    pub check_runs: Vec<CheckRun>,
+}
+
+impl From<CheckRuns> for Vec<CheckRun> {
+    fn from(runs: CheckRuns) -> Self {
+        runs.check_runs
+    }
+}
+
+/// Status reported through the legacy commit statuses API.
+#[derive(Deserialize, Debug, Clone)]
+pub struct CommitStatus {
+    id: u64,
+    context: String,
+    state: String,
+    target_url: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CombinedStatus {
+    pub statuses: Vec<CommitStatus>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct WorkflowRun {
+    id: u64,
+    name: Option<String>,
+    head_branch: Option<String>,
+    head_sha: String,
+    status: String,
+    conclusion: Option<String>,
+    created_at: String,
+    run_started_at: Option<String>,
+    updated_at: String,
+    html_url: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct WorkflowRuns {
+    pub workflow_runs: Vec<WorkflowRun>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Jobs {
+    pub jobs: Vec<CheckRun>,
+}
+
+impl From<Jobs> for Vec<CheckRun> {
+    fn from(jobs: Jobs) -> Self {
+        jobs.jobs
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Review {
+    pub id: u64,
+    html_url: String,
+    body: Option<String>,
+    user: User,
+    /// `None` for pending reviews.
+    submitted_at: Option<String>,
+}
+
+impl Review {
+    pub fn has_body(&self) -> bool {
+        self.body
+            .as_ref()
+            .is_some_and(|body| !body.trim().is_empty())
+    }
+}
+
+/// Comment on the diff of a pull request.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ReviewComment {
+    pub id: u64,
+    pub in_reply_to_id: Option<u64>,
+    pub path: String,
+    line: Option<u64>,
+    original_line: Option<u64>,
+    html_url: String,
+    body: String,
+    user: User,
+    created_at: String,
+}
+
+impl ReviewComment {
+    /// Line in the latest diff, or in the original one if it is outdated.
+    pub fn get_line(&self) -> Option<u64> {
+        self.line.or(self.original_line)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Label {
+    name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PullRequestLinks {
+    merged_at: Option<String>,
+}
+
+impl Pull {
+    fn get_merged_at(&self) -> Option<String> {
+        self.merged_at
+            .clone()
+            .or_else(|| self.pull_request.as_ref().and_then(|p| p.merged_at.clone()))
+    }
+
+    fn get_state(&self) -> PullRequestState {
+        match (self.state.as_ref(), self.get_merged_at()) {
+            ("open", _) => PullRequestState::Open,
+            (_, Some(_)) => PullRequestState::Merged,
+            _ => PullRequestState::Closed,
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct User {
-    login: String,
+    pub login: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Permissions {
+    pub push: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Parent {
+    pub full_name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Repo {
+    pub full_name: String,
+    pub description: Option<String>,
+    pub private: bool,
+    /// `internal` is only for organizations of enterprises.
+    pub visibility: Option<String>,
+    /// Only present for forks.
+    pub parent: Option<Parent>,
+    pub default_branch: Option<String>,
+    pub html_url: String,
+    pub clone_url: String,
+    pub ssh_url: Option<String>,
+    /// Only present for authenticated users.
+    pub permissions: Option<Permissions>,
+    pub stargazers_count: Option<u64>,
+    /// Including open pull requests.
+    pub open_issues_count: Option<u64>,
+}
+
+impl From<Repo> for structs::Repo {
+    fn from(repo: Repo) -> Self {
+        Self {
+            name: repo.full_name,
+            description: repo
+                .description
+                .filter(|description| !description.is_empty()),
+            visibility: repo
+                .visibility
+                .and_then(|visibility| visibility.parse().ok())
+                .unwrap_or(if repo.private {
+                    Visibility::Private
+                } else {
+                    Visibility::Public
+                }),
+            parent: repo.parent.map(|parent| parent.full_name),
+            default_branch: repo.default_branch,
+            url: repo.html_url,
+            clone_url: repo.clone_url,
+            ssh_url: repo.ssh_url,
+            stars: repo.stargazers_count,
+            open_issues: repo.open_issues_count,
+            open_pull_requests: None,
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct Ref {
     #[serde(rename = "ref")]
-    name: String,
+    pub name: String,
+    pub sha: Option<String>,
+    pub repo: Option<RefRepository>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RefRepository {
+    pub full_name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Milestone {
+    pub number: u64,
+    pub title: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MergeResult {
+    pub merged: bool,
+    pub message: String,
 }
 
 impl From<Pull> for PullRequest {
     fn from(pr: Pull) -> Self {
         Self {
+            state: pr.get_state(),
+            merged_at: pr.get_merged_at(),
             id: pr.number,
             title: pr.title,
+            draft: pr.draft.unwrap_or(false),
             author: pr.user.login,
+            body: pr.body,
+            labels: pr.labels.into_iter().map(|l| l.name).collect(),
+            assignees: pr.assignees.into_iter().map(|u| u.login).collect(),
+            reviewers: pr
+                .requested_reviewers
+                .into_iter()
+                .map(|u| u.login)
+                .collect(),
+            mergeable: pr.mergeable,
+            commits: pr.commits,
+            additions: pr.additions,
+            deletions: pr.deletions,
+            changed_files: pr.changed_files,
             base: pr.base.map(|r| r.name),
+            sha: pr.head.as_ref().and_then(|r| r.sha.clone()),
             head: pr.head.map(|r| r.name),
+            created_at: pr.created_at,
             updated_at: pr.updated_at,
             url: pr.html_url,
         }
     }
 }
 
+impl From<Issue> for structs::Issue {
+    fn from(issue: Issue) -> Self {
+        Self {
+            id: issue.number,
+            title: issue.title,
+            state: match issue.state.as_ref() {
+                "closed" => IssueState::Closed,
+                _ => IssueState::Open,
+            },
+            author: issue.user.login,
+            body: issue.body,
+            labels: issue.labels.into_iter().map(|l| l.name).collect(),
+            assignees: issue.assignees.into_iter().map(|u| u.login).collect(),
+            milestone: issue.milestone.map(|m| m.title),
+            comments: issue.comments,
+            created_at: issue.created_at,
+            updated_at: issue.updated_at,
+            closed_at: issue.closed_at,
+            url: issue.html_url,
+        }
+    }
+}
+
+impl From<IssueComment> for Comment {
+    fn from(comment: IssueComment) -> Self {
+        Self {
+            id: comment.id,
+            author: comment.user.login,
+            body: comment.body,
+            created_at: comment.created_at,
+            url: Some(comment.html_url),
+        }
+    }
+}
+
+impl From<Review> for Comment {
+    fn from(review: Review) -> Self {
+        Self {
+            id: review.id,
+            author: review.user.login,
+            body: review.body.unwrap_or_default(),
+            created_at: review.submitted_at.unwrap_or_default(),
+            url: Some(review.html_url),
+        }
+    }
+}
+
+impl From<ReviewComment> for Comment {
+    fn from(comment: ReviewComment) -> Self {
+        Self {
+            id: comment.id,
+            author: comment.user.login,
+            body: comment.body,
+            created_at: comment.created_at,
+            url: Some(comment.html_url),
+        }
+    }
+}
+
+impl From<PullFile> for ChangedFile {
+    fn from(file: PullFile) -> Self {
+        Self {
+            status: match file.status.as_ref() {
+                "added" => FileStatus::Added,
+                "removed" => FileStatus::Removed,
+                "renamed" => FileStatus::Renamed,
+                _ => FileStatus::Modified,
+            },
+            path: file.filename,
+            old_path: file.previous_filename,
+            additions: Some(file.additions),
+            deletions: Some(file.deletions),
+        }
+    }
+}
+
 impl<T: Clone, U: From<T>> From<SearchResult<T>> for PaginationResult<U> {
     fn from(result: SearchResult<T>) -> Self {
+        Self::new(
+            result.items.iter().map(|i| U::from(i.to_owned())).collect(),
+            Some(result.total_count),
+        )
+    }
+}
+
+/// Check runs, jobs and workflow runs share the same status and conclusion.
+fn get_check_state(status: &str, conclusion: Option<&str>) -> CheckState {
+    match (status, conclusion) {
+        ("completed", Some("success")) | ("completed", Some("neutral")) => CheckState::Success,
+        ("completed", Some("skipped")) => CheckState::Skipped,
+        ("completed", Some("cancelled")) => CheckState::Cancelled,
+        ("completed", _) => CheckState::Failure,
+        ("in_progress", _) => CheckState::Running,
+        _ => CheckState::Pending,
+    }
+}
+
+impl From<CheckRun> for Check {
+    fn from(run: CheckRun) -> Self {
+        Self {
+            id: run.id.to_string(),
+            state: get_check_state(&run.status, run.conclusion.as_deref()),
+            name: run.name,
+            duration: utils::get_duration(run.started_at.as_deref(), run.completed_at.as_deref()),
+            started_at: run.started_at,
+            finished_at: run.completed_at,
+            url: run.html_url,
+        }
+    }
+}
+
+impl From<CommitStatus> for Check {
+    fn from(status: CommitStatus) -> Self {
+        let state = match status.state.as_ref() {
+            "success" => CheckState::Success,
+            "failure" | "error" => CheckState::Failure,
+            _ => CheckState::Pending,
+        };
+        Self {
+            id: status.id.to_string(),
+            name: status.context,
+            finished_at: Some(status.updated_at).filter(|_| state.is_finished()),
+            started_at: Some(status.created_at),
+            state,
+            url: status.target_url,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<WorkflowRun> for Pipeline {
+    fn from(run: WorkflowRun) -> Self {
+        let state = get_check_state(&run.status, run.conclusion.as_deref());
         Self {
-            total: result.total_count,
-            result: result.items.iter().map(|i| U::from(i.to_owned())).collect(),
+            id: run.id,
+            name: run.name,
+            branch: run.head_branch,
+            sha: run.head_sha,
+            duration: if state.is_finished() {
+                utils::get_duration(run.run_started_at.as_deref(), Some(&run.updated_at))
+            } else {
+                None
+            },
+            state,
+            created_at: run.created_at,
+            url: run.html_url,
+            jobs: vec![],
         }
     }
 }
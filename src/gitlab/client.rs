@@ -1,46 +1,60 @@
-use anyhow::Result;
+use super::structs::Project;
+use crate::profile::Profile;
+use crate::utils::url_encode;
+use anyhow::{anyhow, Result};
 use log::debug;
 use reqwest::header::HeaderMap;
 use reqwest::{Client, Method, RequestBuilder, Url};
-use crate::utils::url_encode;
-use super::structs::Project;
 
 pub struct GitLabClient {
-  host: String,
-  client: reqwest::Client,
+    endpoint: String,
+    client: reqwest::Client,
 }
 
 impl GitLabClient {
-  pub fn build(host: &str, token: &str) -> Result<Self> {
-      let mut headers = HeaderMap::new();
-      headers.insert("Private-Token", token.parse()?);
-      debug!("default headers: {:?}", headers);
-      let client = Client::builder().default_headers(headers).build()?;
-
-      Ok(GitLabClient {
-          host: host.to_string(),
-          client: client,
-      })
-  }
-
-  pub fn call(&self, method: Method, uri: &str) -> RequestBuilder {
-      let mut url = Url::parse(&format!("https://{}", self.host)).unwrap();
-      url.set_path(uri);
-
-      self.client.request(method, url)
-  }
-
-  pub async fn get_project_id(&self, name: &str) -> Result<u64> {
-      let res = self
-          .call(
-              Method::GET,
-              &format!("/api/v4/projects/{}", url_encode(name)),
-          )
-          .send()
-          .await?;
-
-      let data = res.error_for_status()?.json::<Project>().await?;
-
-      Ok(data.id)
-  }
+    /// Build a client of the GitLab instance at `endpoint`, like
+    /// `https://gitlab.com`.
+    pub fn build(endpoint: &str, token: &str) -> Result<Self> {
+        Url::parse(endpoint)?;
+        let mut headers = HeaderMap::new();
+        headers.insert("Private-Token", token.parse()?);
+        debug!("default headers: {:?}", headers);
+        let client = Client::builder().default_headers(headers).build()?;
+
+        Ok(GitLabClient {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            client,
+        })
+    }
+
+    pub fn from_profile(profile: &Profile, host: &str) -> Result<Self> {
+        let token = profile.get_gitlab_token_by_host(host).ok_or_else(|| {
+            anyhow!(
+                "no GitLab profile for {}: Try `yag profile add` first",
+                host
+            )
+        })?;
+        Self::build(&format!("https://{}", host), &token)
+    }
+
+    pub fn call(&self, method: Method, uri: &str) -> RequestBuilder {
+        let mut url = Url::parse(&self.endpoint).unwrap();
+        url.set_path(&format!("{}{}", url.path().trim_end_matches('/'), uri));
+
+        self.client.request(method, url)
+    }
+
+    pub async fn get_project_id(&self, name: &str) -> Result<u64> {
+        let res = self
+            .call(
+                Method::GET,
+                &format!("/api/v4/projects/{}", url_encode(name)),
+            )
+            .send()
+            .await?;
+
+        let data = res.error_for_status()?.json::<Project>().await?;
+
+        Ok(data.id)
+    }
 }
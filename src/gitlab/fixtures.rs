@@ -0,0 +1,54 @@
+//! Clients and responses of GitLab shared by tests.
+
+use mockito::server_url;
+
+use super::client::GitLabClient;
+use super::repository::GitLabRepository;
+
+pub const MERGE_REQUEST: &str = r#"{
+    "id": 11,
+    "iid": 1,
+    "project_id": 1,
+    "title": "Draft: add something",
+    "description": "description",
+    "state": "opened",
+    "created_at": "2020-09-01T00:00:00Z",
+    "updated_at": "2020-09-02T00:00:00Z",
+    "target_branch": "main",
+    "source_branch": "feature",
+    "author": { "id": 1, "name": "Someone", "username": "someone" },
+    "assignees": [],
+    "reviewers": [{ "id": 2, "name": "Reviewer", "username": "reviewer" }],
+    "labels": ["bug"],
+    "draft": true,
+    "merge_status": "can_be_merged",
+    "changes_count": "1",
+    "merged_at": null,
+    "web_url": "https://gitlab.com/owner/repo/-/merge_requests/1",
+    "sha": "abc",
+    "diff_refs": { "base_sha": "def", "head_sha": "abc", "start_sha": "def" }
+}"#;
+
+pub const ISSUE: &str = r#"{
+    "iid": 1,
+    "title": "something wrong",
+    "description": null,
+    "state": "opened",
+    "author": { "id": 1, "name": "Someone", "username": "someone" },
+    "assignees": [],
+    "labels": ["bug"],
+    "milestone": { "id": 1, "iid": 1, "title": "v1.0" },
+    "user_notes_count": 3,
+    "created_at": "2020-09-01T00:00:00Z",
+    "updated_at": "2020-09-02T00:00:00Z",
+    "closed_at": null,
+    "web_url": "https://gitlab.com/owner/repo/-/issues/1"
+}"#;
+
+pub fn get_client() -> GitLabClient {
+    GitLabClient::build(&server_url(), "token").unwrap()
+}
+
+pub fn get_repo() -> GitLabRepository {
+    GitLabRepository::new(get_client(), 1)
+}
@@ -0,0 +1,115 @@
+use super::client::GitLabClient;
+use super::repository::get_header_number;
+use super::structs::{GitLabResponse, Namespace, Project};
+use crate::profile::Profile;
+use crate::repository::{self, CreateRepositoryOpt, Forge};
+use crate::structs::Repo;
+use crate::utils::url_encode;
+use anyhow::Result;
+use async_trait::async_trait;
+use log::debug;
+use reqwest::Method;
+use serde_json::json;
+
+pub struct GitLabForge {
+    host: String,
+    client: GitLabClient,
+}
+
+impl GitLabForge {
+    pub fn init(profile: &Profile, host: &str) -> Result<Self> {
+        Ok(GitLabForge {
+            host: host.to_string(),
+            client: GitLabClient::from_profile(profile, host)?,
+        })
+    }
+
+    async fn get_namespace(&self, path: &str) -> Result<Namespace> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/api/v4/namespaces/{}", url_encode(path)),
+            )
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        serde_json::from_str::<GitLabResponse<Namespace>>(&text)?
+            .map(|namespace| Ok(namespace.to_owned()))
+    }
+
+    async fn count_open_merge_requests(&self, project_id: u64) -> Result<Option<u64>> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/api/v4/projects/{}/merge_requests", project_id),
+            )
+            .query(&[("state", "opened"), ("per_page", "1")])
+            .send()
+            .await?;
+
+        // the total is omitted for too many merge requests
+        Ok(get_header_number(&res.error_for_status()?, "x-total"))
+    }
+}
+
+#[async_trait]
+impl Forge for GitLabForge {
+    async fn get_repository(&self, name: &str) -> Result<Repo> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/api/v4/projects/{}", url_encode(name)),
+            )
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        let project = serde_json::from_str::<GitLabResponse<Project>>(&text)?
+            .map(|project| Ok(project.to_owned()))?;
+
+        let mut repo = Repo::from(project.clone());
+        repo.open_pull_requests = self.count_open_merge_requests(project.id).await?;
+        Ok(repo)
+    }
+
+    async fn create_repository(&self, opt: CreateRepositoryOpt) -> Result<Repo> {
+        let mut body = json!({
+            "name": opt.name,
+            "path": opt.name,
+            "visibility": opt.visibility.as_str(),
+        });
+        if let Some(description) = opt.description {
+            body["description"] = json!(description);
+        }
+        if let Some(owner) = opt.owner.as_ref() {
+            body["namespace_id"] = json!(self.get_namespace(owner).await?.id);
+        }
+
+        let res = self
+            .client
+            .call(Method::POST, "/api/v4/projects")
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        serde_json::from_str::<GitLabResponse<Project>>(&text)?
+            .map(|project| Ok(Repo::from(project.to_owned())))
+    }
+
+    fn get_web_url(&self, name: &str, git_ref: &str, path: &str, line: Option<usize>) -> String {
+        let repo_url = format!("https://{}/{}/-", self.host, name);
+        repository::get_web_url(&repo_url, git_ref, path, line)
+    }
+}
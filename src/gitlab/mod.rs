@@ -1,4 +1,7 @@
 mod client;
+#[cfg(test)]
+mod fixtures;
+pub mod forge;
 pub mod profile;
 pub mod repository;
 mod structs;
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use colored::*;
 use serde_derive::*;
 
 use crate::{
@@ -7,6 +8,44 @@ use crate::{
     utils,
 };
 
+pub const GITLAB_HOST: &str = "gitlab.com";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GitLabConfig {
+    pub token: String,
+}
+
+impl ProfileConfig for GitLabConfig {
+    fn fill_profile(&self, profile: &mut Profile) {
+        profile.gitlab = Some(self.to_owned());
+    }
+}
+
+#[derive(Default)]
+pub struct GitLabPrompter;
+
+#[async_trait]
+impl Prompter for GitLabPrompter {
+    fn display_name(&self) -> String {
+        "GitLab".to_string()
+    }
+
+    async fn prompt(&self) -> Result<Box<dyn ProfileConfig>> {
+        let url = format!(
+            "https://{}/-/user_settings/personal_access_tokens?name=yag&scopes=api",
+            GITLAB_HOST
+        );
+        println!(
+            "Please create a personal access token with {} scope at {}",
+            "api".green().bold(),
+            url.bold(),
+        );
+        Ok(Box::new(GitLabConfig {
+            token: utils::user_input("token: ")?,
+        }))
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GitLabSelfHostedConfig {
     pub host: String,
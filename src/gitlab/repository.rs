@@ -1,15 +1,43 @@
 use super::client::GitLabClient;
-use super::structs::User;
-use super::structs::{GitLabResponse, MergeRequest};
-use crate::repository::Repository;
-use crate::structs::{PaginationResult, PullRequest};
+use super::structs::{Approvals, Discussion, DraftNote, GitLabResponse, Issue, MergeRequest};
+use super::structs::{Commit, Job, Milestone, Pipeline, Project, User};
+use super::structs::{MergeRequestChanges, Note};
+use crate::repository::{
+    paginate, parse_head, set_draft_prefix, CreateIssueOpt, CreatePullRequestOpt, ListIssueOpt,
+    MergeMethod, MergePullRequestOpt, PullRequestSort, Repository, ReviewEvent, ReviewOpt,
+    UpdatePullRequestOpt, FORK_POLL_INTERVAL, FORK_POLL_TIMES,
+};
+use crate::structs::{
+    self, ChangedFile, Check, Comment, IssueState, PaginationResult, PullRequest, PullRequestState,
+    Repo,
+};
+use crate::utils;
 use crate::{profile::load_profile, repository::ListPullRequestOpt};
 use anyhow::*;
 use async_trait::async_trait;
+use futures::TryStreamExt;
 use git_url_parse::GitUrl;
 use log::debug;
-use reqwest::Method;
+use reqwest::{Method, Response};
+use serde::de::DeserializeOwned;
 use serde_json::json;
+use std::fmt;
+use std::time::Duration;
+
+/// GitLab marks merge requests as draft by the prefix of their titles.
+const DRAFT_PREFIXES: &[&str] = &["Draft:", "[Draft]", "(Draft)", "WIP:", "[WIP]"];
+
+const REBASE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const REBASE_POLL_TIMES: usize = 120;
+
+/// Pagination headers like `x-total` and `x-next-page`, which are empty
+/// when there is no such page.
+pub fn get_header_number(res: &Response, name: &str) -> Option<u64> {
+    res.headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+}
 
 pub struct GitLabRepository {
     client: GitLabClient,
@@ -19,47 +47,41 @@ pub struct GitLabRepository {
 impl GitLabRepository {
     pub async fn init(host: &str, remote_url: &GitUrl) -> Result<Self> {
         let profile = load_profile().await?;
-        let token = profile
-            .get_gitlab_token_by_host(host)
-            .expect(&format!("unknown remote host: {}", host));
-        let client = GitLabClient::build(host, &token)?;
+        let client = GitLabClient::from_profile(&profile, host)?;
         let project_id = client.get_project_id(remote_url.fullname.as_ref()).await?;
-        Ok(GitLabRepository { client, project_id })
+        Ok(Self::new(client, project_id))
+    }
+
+    pub(crate) fn new(client: GitLabClient, project_id: u64) -> Self {
+        GitLabRepository { client, project_id }
     }
 }
 
 #[async_trait]
 impl Repository for GitLabRepository {
     async fn get_pull_request(&self, id: usize) -> Result<PullRequest> {
-        let res = self
-            .client
-            .call(
-                Method::GET,
-                &format!("/api/v4/projects/{}/merge_requests/{}", self.project_id, id),
-            )
-            .send()
-            .await?;
-
-        let text = res.text().await?;
-
-        debug!("{:#?}", text);
-
-        serde_json::from_str::<GitLabResponse<MergeRequest>>(&text)?
-            .map(|data| Ok(PullRequest::from(data.to_owned())))
+        self.get_merge_request(id).await.map(PullRequest::from)
     }
 
     async fn list_pull_requests(
         &self,
         opt: ListPullRequestOpt,
     ) -> Result<PaginationResult<PullRequest>> {
+        let page = opt.get_page().max(1);
         let mut req = self
             .client
             .call(
                 Method::GET,
                 &format!("/api/v4/projects/{}/merge_requests", self.project_id),
             )
-            .query(&[("state", "opened"), ("per_page", "10")])
-            .query(&[("page", opt.get_page())]);
+            .query(&[("per_page", opt.get_per_page()), ("page", page)]);
+
+        req = match opt.state {
+            Some(PullRequestState::Open) => req.query(&[("state", "opened")]),
+            Some(PullRequestState::Closed) => req.query(&[("state", "closed")]),
+            Some(PullRequestState::Merged) => req.query(&[("state", "merged")]),
+            None => req,
+        };
 
         if let Some(username) = opt.author {
             let user = self.get_user_by_username(&username).await?;
@@ -69,19 +91,42 @@ impl Repository for GitLabRepository {
         if opt.me {
             req = req.query(&[("scope", "created-by-me")]);
         }
+        if let Some(assignee) = opt.assignee.as_ref() {
+            req = req.query(&[("assignee_username", assignee)]);
+        }
+        if let Some(reviewer) = opt.reviewer.as_ref() {
+            req = req.query(&[("reviewer_username", reviewer)]);
+        }
+        if !opt.labels.is_empty() {
+            req = req.query(&[("labels", opt.labels.join(","))]);
+        }
+        if let Some(base) = opt.base.as_ref() {
+            req = req.query(&[("target_branch", base)]);
+        }
+        if let Some(head) = opt.head.as_ref() {
+            req = req.query(&[("source_branch", head)]);
+        }
+        if let Some(draft) = opt.draft {
+            req = req.query(&[("wip", if draft { "yes" } else { "no" })]);
+        }
+        if let Some(search) = opt.search.as_ref() {
+            req = req.query(&[("search", search)]);
+        }
+        match opt.sort {
+            Some(PullRequestSort::Created) | None => req = req.query(&[("order_by", "created_at")]),
+            Some(PullRequestSort::Updated) => req = req.query(&[("order_by", "updated_at")]),
+            Some(PullRequestSort::Comments) => {
+                bail!("sorting merge requests by comments is not supported on GitLab")
+            }
+        }
+        req = req.query(&[("sort", if opt.ascending { "asc" } else { "desc" })]);
 
         let res = req.send().await?;
 
         debug!("{:#?}", res);
 
-        let total = res
-            .headers()
-            .get("x-total")
-            .map(|v| v.to_str().ok())
-            .flatten()
-            .map(|v| v.parse::<u64>().ok())
-            .flatten()
-            .ok_or(anyhow!("fail to get total"))?;
+        let total = get_header_number(&res, "x-total").ok_or(anyhow!("fail to get total"))?;
+        let next_page = get_header_number(&res, "x-next-page").map(|page| page as usize);
 
         let text = res.text().await?;
         debug!("{:#?}", text);
@@ -95,30 +140,57 @@ impl Repository for GitLabRepository {
                     .collect())
             })?;
 
-        Ok(PaginationResult::new(result, total))
+        Ok(PaginationResult::new(result, Some(total)).with_page(page, next_page))
     }
 
-    async fn create_pull_request(
-        &self,
-        source_branch: &str,
-        target_branch: &str,
-        title: &str,
-    ) -> Result<PullRequest> {
+    async fn create_pull_request(&self, opt: CreatePullRequestOpt) -> Result<PullRequest> {
+        let title = if opt.draft {
+            set_draft_prefix(&opt.title, true, DRAFT_PREFIXES)
+        } else {
+            opt.title.to_owned()
+        };
+
+        // merge requests from forks are created in the source project, where
+        // forks keep the path of the project unless renamed
+        let (owner, source_branch) = parse_head(&opt.source_branch);
+        let source_project_id = match owner {
+            Some(owner) => {
+                let project = self.get_project(self.project_id).await?;
+                self.client
+                    .get_project_id(&format!("{}/{}", owner, project.path))
+                    .await?
+            }
+            None => self.project_id,
+        };
+
+        let mut body = json!({
+          "source_branch": source_branch,
+          "target_branch": opt.target_branch,
+          "target_project_id": self.project_id,
+          "title": title,
+          "description": opt.body,
+        });
+        if !opt.reviewers.is_empty() {
+            body["reviewer_ids"] = json!(self.get_user_ids(&opt.reviewers).await?);
+        }
+        if !opt.assignees.is_empty() {
+            body["assignee_ids"] = json!(self.get_user_ids(&opt.assignees).await?);
+        }
+        if !opt.labels.is_empty() {
+            body["labels"] = json!(opt.labels.join(","));
+        }
+        if let Some(milestone) = opt.milestone.as_ref() {
+            body["milestone_id"] = json!(self.get_milestone_id(milestone).await?);
+        }
+
         let res = self
             .client
             .call(
                 Method::POST,
-                &format!("/api/v4/projects/{}/merge_requests", self.project_id),
+                &format!("/api/v4/projects/{}/merge_requests", source_project_id),
             )
             .header("Content-Type", "application/json")
-            .body(
-                json!({
-                  "source_branch": source_branch,
-                  "target_branch": target_branch,
-                  "title": title,
-                })
-                .to_string(),
-            )
+            .body(body.to_string())
             .send()
             .await?;
 
@@ -131,19 +203,97 @@ impl Repository for GitLabRepository {
     }
 
     async fn close_pull_request(&self, id: usize) -> Result<PullRequest> {
+        self.update_merge_request(id, json!({ "state_event": "close" }))
+            .await
+    }
+
+    async fn reopen_pull_request(&self, id: usize) -> Result<PullRequest> {
+        self.update_merge_request(id, json!({ "state_event": "reopen" }))
+            .await
+    }
+
+    async fn update_pull_request(
+        &self,
+        id: usize,
+        opt: UpdatePullRequestOpt,
+    ) -> Result<PullRequest> {
+        let mut body = json!({});
+
+        let title = match (opt.title, opt.draft) {
+            (title, Some(draft)) => {
+                let title = match title {
+                    Some(title) => title,
+                    None => PullRequest::from(self.get_merge_request(id).await?).title,
+                };
+                Some(set_draft_prefix(&title, draft, DRAFT_PREFIXES))
+            }
+            (title, None) => title,
+        };
+        if let Some(title) = title {
+            body["title"] = json!(title);
+        }
+        if let Some(description) = opt.body.as_ref() {
+            body["description"] = json!(description);
+        }
+        if let Some(target_branch) = opt.target_branch.as_ref() {
+            body["target_branch"] = json!(target_branch);
+        }
+        if let Some(reviewers) = opt.reviewers.as_ref() {
+            body["reviewer_ids"] = json!(self.get_user_ids(reviewers).await?);
+        }
+        if let Some(assignees) = opt.assignees.as_ref() {
+            body["assignee_ids"] = json!(self.get_user_ids(assignees).await?);
+        }
+        if let Some(labels) = opt.labels.as_ref() {
+            body["labels"] = json!(labels.join(","));
+        }
+        if let Some(milestone) = opt.milestone.as_ref() {
+            body["milestone_id"] = json!(self.get_milestone_id(milestone).await?);
+        }
+
+        self.update_merge_request(id, body).await
+    }
+
+    async fn merge_pull_request(&self, id: usize, opt: MergePullRequestOpt) -> Result<PullRequest> {
+        let sha = if opt.method == MergeMethod::Rebase {
+            // the head is replaced by the rebased commits, which are not the
+            // ones of the sha
+            if opt.sha.is_some() {
+                bail!("--sha cannot be used with --rebase on GitLab")
+            }
+            self.rebase_merge_request(id).await?.sha
+        } else {
+            opt.sha.clone()
+        };
+
+        let squash = opt.method == MergeMethod::Squash;
+        let mut body = json!({
+            "squash": squash,
+            "should_remove_source_branch": opt.delete_branch,
+            "merge_when_pipeline_succeeds": opt.auto,
+        });
+        if let Some(message) = opt.get_commit_message() {
+            if squash {
+                body["squash_commit_message"] = json!(message);
+            } else {
+                body["merge_commit_message"] = json!(message);
+            }
+        }
+        if let Some(sha) = sha {
+            body["sha"] = json!(sha);
+        }
+
         let res = self
             .client
             .call(
                 Method::PUT,
-                &format!("/api/v4/projects/{}/merge_requests/{}", self.project_id, id),
+                &format!(
+                    "/api/v4/projects/{}/merge_requests/{}/merge",
+                    self.project_id, id
+                ),
             )
             .header("Content-Type", "application/json")
-            .body(
-                json!({
-                    "state_event": "close",
-                })
-                .to_string(),
-            )
+            .body(body.to_string())
             .send()
             .await?;
 
@@ -153,13 +303,701 @@ impl Repository for GitLabRepository {
         serde_json::from_str::<GitLabResponse<MergeRequest>>(&text)?
             .map(|data| Ok(PullRequest::from(data.to_owned())))
     }
+
+    fn get_pull_request_ref(&self, id: usize) -> String {
+        format!("refs/merge-requests/{}/head", id)
+    }
+
+    async fn list_pull_request_discussions(&self, id: usize) -> Result<Vec<structs::Discussion>> {
+        let discussions = self
+            .list_all::<Discussion>(&format!(
+                "/api/v4/projects/{}/merge_requests/{}/discussions",
+                self.project_id, id
+            ))
+            .await?;
+
+        Ok(discussions
+            .into_iter()
+            .filter_map(|discussion| discussion.into_discussion())
+            .collect())
+    }
+
+    async fn comment_pull_request(&self, id: usize, body: &str) -> Result<Comment> {
+        let res = self
+            .client
+            .call(
+                Method::POST,
+                &format!(
+                    "/api/v4/projects/{}/merge_requests/{}/notes",
+                    self.project_id, id
+                ),
+            )
+            .header("Content-Type", "application/json")
+            .body(json!({ "body": body }).to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        serde_json::from_str::<GitLabResponse<Note>>(&text)?
+            .map(|data| Ok(Comment::from(data.to_owned())))
+    }
+
+    async fn get_pull_request_diff(&self, id: usize) -> Result<String> {
+        let changes = self.get_merge_request_changes(id).await?;
+        Ok(changes
+            .changes
+            .iter()
+            .map(|change| change.get_diff())
+            .collect::<Vec<String>>()
+            .join(""))
+    }
+
+    async fn list_pull_request_files(&self, id: usize) -> Result<Vec<ChangedFile>> {
+        let changes = self.get_merge_request_changes(id).await?;
+        Ok(changes.changes.into_iter().map(ChangedFile::from).collect())
+    }
+
+    async fn review_pull_request(&self, id: usize, opt: ReviewOpt) -> Result<PullRequest> {
+        // comments are added as draft notes, then published together as a review
+        let mut notes = vec![];
+        if let Some(body) = opt.body.as_ref() {
+            notes.push(json!({ "note": body }));
+        }
+        if !opt.comments.is_empty() {
+            let diff_refs = self
+                .get_merge_request(id)
+                .await?
+                .diff_refs
+                .ok_or_else(|| anyhow!("cannot resolve diff of merge request !{}", id))?;
+            for comment in opt.comments.iter() {
+                notes.push(json!({
+                    "note": comment.body,
+                    "position": {
+                        "position_type": "text",
+                        "base_sha": diff_refs.base_sha,
+                        "start_sha": diff_refs.start_sha,
+                        "head_sha": diff_refs.head_sha,
+                        "old_path": comment.path,
+                        "new_path": comment.path,
+                        "new_line": comment.line,
+                    },
+                }));
+            }
+        }
+
+        // drafts are removed if the review cannot be published, so that they
+        // do not show up in the next review
+        let mut drafts = vec![];
+        for note in notes.iter() {
+            match self.create_draft_note(id, note).await {
+                Result::Ok(draft) => drafts.push(draft),
+                Err(err) => {
+                    self.delete_draft_notes(id, &drafts).await;
+                    return Err(err);
+                }
+            }
+        }
+        if !drafts.is_empty() {
+            if let Err(err) = self
+                .call_merge_request_action(id, "draft_notes/bulk_publish")
+                .await
+            {
+                self.delete_draft_notes(id, &drafts).await;
+                return Err(err);
+            }
+        }
+
+        // GitLab cannot request changes, so an approval is revoked at most
+        match opt.event {
+            ReviewEvent::Approve => self.call_merge_request_action(id, "approve").await?,
+            ReviewEvent::RequestChanges => {
+                if self.get_approvals(id).await?.user_has_approved {
+                    self.call_merge_request_action(id, "unapprove").await?
+                }
+            }
+            ReviewEvent::Comment => (),
+        }
+
+        self.get_pull_request(id).await
+    }
+
+    async fn get_issue(&self, id: usize) -> Result<structs::Issue> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/api/v4/projects/{}/issues/{}", self.project_id, id),
+            )
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        serde_json::from_str::<GitLabResponse<Issue>>(&text)?
+            .map(|data| Ok(structs::Issue::from(data.to_owned())))
+    }
+
+    async fn list_issues(&self, opt: ListIssueOpt) -> Result<PaginationResult<structs::Issue>> {
+        let page = opt.get_page().max(1);
+        let mut req = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/api/v4/projects/{}/issues", self.project_id),
+            )
+            .query(&[("per_page", opt.get_per_page()), ("page", page)]);
+
+        req = match opt.state {
+            Some(IssueState::Open) => req.query(&[("state", "opened")]),
+            Some(IssueState::Closed) => req.query(&[("state", "closed")]),
+            None => req,
+        };
+
+        if opt.me {
+            req = req.query(&[("scope", "created_by_me")]);
+        } else if let Some(author) = opt.author.as_ref() {
+            req = req.query(&[("author_username", author)]);
+        }
+        if let Some(assignee) = opt.assignee.as_ref() {
+            req = req.query(&[("assignee_username", assignee)]);
+        }
+        if !opt.labels.is_empty() {
+            req = req.query(&[("labels", opt.labels.join(","))]);
+        }
+        if let Some(milestone) = opt.milestone.as_ref() {
+            req = req.query(&[("milestone", milestone)]);
+        }
+
+        let res = req.send().await?;
+
+        debug!("{:#?}", res);
+
+        let total = get_header_number(&res, "x-total").ok_or(anyhow!("fail to get total"))?;
+        let next_page = get_header_number(&res, "x-next-page").map(|page| page as usize);
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        let result = serde_json::from_str::<GitLabResponse<Vec<Issue>>>(&text)?.map(|issues| {
+            Ok(issues
+                .iter()
+                .map(|issue| structs::Issue::from(issue.to_owned()))
+                .collect())
+        })?;
+
+        Ok(PaginationResult::new(result, Some(total)).with_page(page, next_page))
+    }
+
+    async fn create_issue(&self, opt: CreateIssueOpt) -> Result<structs::Issue> {
+        let mut body = json!({
+            "title": opt.title,
+            "description": opt.body,
+        });
+        if !opt.assignees.is_empty() {
+            body["assignee_ids"] = json!(self.get_user_ids(&opt.assignees).await?);
+        }
+        if !opt.labels.is_empty() {
+            body["labels"] = json!(opt.labels.join(","));
+        }
+        if let Some(milestone) = opt.milestone.as_ref() {
+            body["milestone_id"] = json!(self.get_milestone_id(milestone).await?);
+        }
+
+        let res = self
+            .client
+            .call(
+                Method::POST,
+                &format!("/api/v4/projects/{}/issues", self.project_id),
+            )
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        serde_json::from_str::<GitLabResponse<Issue>>(&text)?
+            .map(|data| Ok(structs::Issue::from(data.to_owned())))
+    }
+
+    async fn close_issue(&self, id: usize) -> Result<structs::Issue> {
+        self.update_issue_state(id, "close").await
+    }
+
+    async fn reopen_issue(&self, id: usize) -> Result<structs::Issue> {
+        self.update_issue_state(id, "reopen").await
+    }
+
+    async fn list_checks(&self, git_ref: &str) -> Result<Vec<Check>> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!(
+                    "/api/v4/projects/{}/repository/commits/{}",
+                    self.project_id,
+                    utils::encode_segment(git_ref)
+                ),
+            )
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        let commit = serde_json::from_str::<GitLabResponse<Commit>>(&text)?
+            .map(|data| Ok(data.to_owned()))?;
+        match commit.last_pipeline {
+            Some(pipeline) => self.list_pipeline_jobs(pipeline.id).await,
+            None => Ok(vec![]),
+        }
+    }
+
+    async fn list_pipelines(&self, branch: Option<&str>) -> Result<Vec<structs::Pipeline>> {
+        let mut query = vec![("per_page", "20")];
+        if let Some(branch) = branch {
+            query.push(("ref", branch));
+        }
+
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/api/v4/projects/{}/pipelines", self.project_id),
+            )
+            .query(&query)
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        serde_json::from_str::<GitLabResponse<Vec<Pipeline>>>(&text)?.map(|data| {
+            Ok(data
+                .iter()
+                .map(|pipeline| structs::Pipeline::from(pipeline.to_owned()))
+                .collect())
+        })
+    }
+
+    async fn get_pipeline(&self, id: u64) -> Result<structs::Pipeline> {
+        let mut pipeline = structs::Pipeline::from(self.get_gitlab_pipeline(id).await?);
+        pipeline.jobs = self.list_pipeline_jobs(id).await?;
+        Ok(pipeline)
+    }
+
+    /// GitLab only retries failed jobs of a pipeline, so that a new pipeline
+    /// is created for the same ref to run everything again.
+    async fn retry_pipeline(&self, id: u64, failed_only: bool) -> Result<structs::Pipeline> {
+        let pipeline = if failed_only {
+            self.call_pipeline_action(id, "retry").await?
+        } else {
+            let pipeline = self.get_gitlab_pipeline(id).await?;
+            let git_ref = pipeline
+                .git_ref
+                .ok_or_else(|| anyhow!("pipeline #{} has no ref", id))?;
+
+            let res = self
+                .client
+                .call(
+                    Method::POST,
+                    &format!("/api/v4/projects/{}/pipeline", self.project_id),
+                )
+                .query(&[("ref", git_ref)])
+                .send()
+                .await?;
+
+            let text = res.text().await?;
+            debug!("{:#?}", text);
+
+            serde_json::from_str::<GitLabResponse<Pipeline>>(&text)?
+                .map(|data| Ok(data.to_owned()))?
+        };
+        self.get_pipeline(pipeline.id).await
+    }
+
+    async fn cancel_pipeline(&self, id: u64) -> Result<structs::Pipeline> {
+        self.call_pipeline_action(id, "cancel").await?;
+        self.get_pipeline(id).await
+    }
+
+    async fn get_job(&self, id: u64) -> Result<Check> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/api/v4/projects/{}/jobs/{}", self.project_id, id),
+            )
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        serde_json::from_str::<GitLabResponse<Job>>(&text)?
+            .map(|data| Ok(Check::from(data.to_owned())))
+    }
+
+    async fn get_job_log(&self, id: u64) -> Result<String> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/api/v4/projects/{}/jobs/{}/trace", self.project_id, id),
+            )
+            .send()
+            .await?;
+
+        let status = res.status();
+        let text = res.text().await?;
+        if !status.is_success() {
+            debug!("{:#?}", text);
+            serde_json::from_str::<GitLabResponse<Job>>(&text)?.map(|_| Ok(()))?;
+        }
+        Ok(text)
+    }
+
+    async fn can_push(&self) -> Result<bool> {
+        Ok(self.get_project(self.project_id).await?.can_push())
+    }
+
+    async fn fork_repository(&self, owner: Option<&str>) -> Result<Repo> {
+        let mut body = json!({});
+        if let Some(owner) = owner {
+            body["namespace_path"] = json!(owner);
+        }
+
+        let res = self
+            .client
+            .call(
+                Method::POST,
+                &format!("/api/v4/projects/{}/fork", self.project_id),
+            )
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        let id = serde_json::from_str::<GitLabResponse<Project>>(&text)?
+            .map(|project| Ok(project.id))?;
+
+        // the repository is imported asynchronously
+        for _ in 0..FORK_POLL_TIMES {
+            let project = self.get_project(id).await?;
+            match project.import_status.as_deref() {
+                Some("failed") => bail!("failed to fork to {}", project.path_with_namespace),
+                Some("scheduled") | Some("started") => {
+                    debug!("fork is not ready: {:?}", project.import_status)
+                }
+                _ => return Ok(Repo::from(project)),
+            }
+            tokio::time::sleep(FORK_POLL_INTERVAL).await;
+        }
+        bail!("fork is not ready yet, try again later")
+    }
 }
 
 impl GitLabRepository {
+    /// Fetch a page of a list API, where the total is unknown.
+    async fn list_page<T>(&self, uri: &str, page: usize) -> Result<PaginationResult<T>>
+    where
+        T: DeserializeOwned + Clone + fmt::Debug,
+    {
+        let res = self
+            .client
+            .call(Method::GET, uri)
+            .query(&[("per_page", 100), ("page", page)])
+            .send()
+            .await?;
+        let next_page = get_header_number(&res, "x-next-page").map(|page| page as usize);
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        let result = serde_json::from_str::<GitLabResponse<Vec<T>>>(&text)?
+            .map(|items| Ok(items.to_owned()))?;
+        Ok(PaginationResult::new(result, None).with_page(page, next_page))
+    }
+
+    /// Fetch all items of a list API page by page.
+    async fn list_all<T>(&self, uri: &str) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + Clone + fmt::Debug + Send,
+    {
+        paginate(1, None, |page| self.list_page(uri, page))
+            .try_collect()
+            .await
+    }
+
+    async fn get_project(&self, id: u64) -> Result<Project> {
+        let res = self
+            .client
+            .call(Method::GET, &format!("/api/v4/projects/{}", id))
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        serde_json::from_str::<GitLabResponse<Project>>(&text)?
+            .map(|project| Ok(project.to_owned()))
+    }
+
+    async fn get_merge_request(&self, id: usize) -> Result<MergeRequest> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/api/v4/projects/{}/merge_requests/{}", self.project_id, id),
+            )
+            .query(&[("include_rebase_in_progress", true)])
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+
+        debug!("{:#?}", text);
+
+        serde_json::from_str::<GitLabResponse<MergeRequest>>(&text)?.map(|data| Ok(data.to_owned()))
+    }
+
+    async fn update_merge_request(
+        &self,
+        id: usize,
+        body: serde_json::Value,
+    ) -> Result<PullRequest> {
+        let res = self
+            .client
+            .call(
+                Method::PUT,
+                &format!("/api/v4/projects/{}/merge_requests/{}", self.project_id, id),
+            )
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        serde_json::from_str::<GitLabResponse<MergeRequest>>(&text)?
+            .map(|data| Ok(PullRequest::from(data.to_owned())))
+    }
+
+    /// Rebase the source branch onto the target branch and wait until GitLab
+    /// finishes it, since merge requests cannot be merged while rebasing.
+    async fn rebase_merge_request(&self, id: usize) -> Result<MergeRequest> {
+        let res = self
+            .client
+            .call(
+                Method::PUT,
+                &format!(
+                    "/api/v4/projects/{}/merge_requests/{}/rebase",
+                    self.project_id, id
+                ),
+            )
+            .send()
+            .await?;
+
+        debug!("{:#?}", res);
+        res.error_for_status()?;
+
+        for _ in 0..REBASE_POLL_TIMES {
+            tokio::time::sleep(REBASE_POLL_INTERVAL).await;
+            let mr = self.get_merge_request(id).await?;
+            if let Some(error) = mr.merge_error.as_ref() {
+                bail!(error.to_owned())
+            }
+            if !mr.rebase_in_progress.unwrap_or(false) {
+                return Ok(mr);
+            }
+        }
+        bail!(
+            "merge request !{} is still being rebased, try again later",
+            id
+        )
+    }
+
+    async fn get_merge_request_changes(&self, id: usize) -> Result<MergeRequestChanges> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!(
+                    "/api/v4/projects/{}/merge_requests/{}/changes",
+                    self.project_id, id
+                ),
+            )
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        serde_json::from_str::<GitLabResponse<MergeRequestChanges>>(&text)?
+            .map(|data| Ok(data.to_owned()))
+    }
+
+    async fn create_draft_note(&self, id: usize, note: &serde_json::Value) -> Result<DraftNote> {
+        let res = self
+            .client
+            .call(
+                Method::POST,
+                &format!(
+                    "/api/v4/projects/{}/merge_requests/{}/draft_notes",
+                    self.project_id, id
+                ),
+            )
+            .header("Content-Type", "application/json")
+            .body(note.to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        serde_json::from_str::<GitLabResponse<DraftNote>>(&text)?.map(|draft| Ok(draft.to_owned()))
+    }
+
+    /// Delete draft notes as much as possible, since it is only for cleanup.
+    async fn delete_draft_notes(&self, id: usize, drafts: &[DraftNote]) {
+        for draft in drafts {
+            let res = self
+                .client
+                .call(
+                    Method::DELETE,
+                    &format!(
+                        "/api/v4/projects/{}/merge_requests/{}/draft_notes/{}",
+                        self.project_id, id, draft.id
+                    ),
+                )
+                .send()
+                .await;
+            debug!("delete draft note {}: {:#?}", draft.id, res);
+        }
+    }
+
+    async fn get_approvals(&self, id: usize) -> Result<Approvals> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!(
+                    "/api/v4/projects/{}/merge_requests/{}/approvals",
+                    self.project_id, id
+                ),
+            )
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        serde_json::from_str::<GitLabResponse<Approvals>>(&text)?
+            .map(|approvals| Ok(approvals.to_owned()))
+    }
+
+    async fn call_merge_request_action(&self, id: usize, action: &str) -> Result<()> {
+        let res = self
+            .client
+            .call(
+                Method::POST,
+                &format!(
+                    "/api/v4/projects/{}/merge_requests/{}/{}",
+                    self.project_id, id, action
+                ),
+            )
+            .send()
+            .await?;
+
+        let status = res.status();
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        if !status.is_success() {
+            serde_json::from_str::<GitLabResponse<()>>(&text)
+                .map_err(|_| anyhow!("failed to {} merge request !{}: {}", action, id, status))?
+                .map(|_| Ok(()))?;
+            bail!("failed to {} merge request !{}: {}", action, id, status)
+        }
+        Ok(())
+    }
+
+    async fn list_pipeline_jobs(&self, id: u64) -> Result<Vec<Check>> {
+        let jobs = self
+            .list_all::<Job>(&format!(
+                "/api/v4/projects/{}/pipelines/{}/jobs",
+                self.project_id, id
+            ))
+            .await?;
+        Ok(jobs.into_iter().map(Check::from).collect())
+    }
+
+    async fn get_gitlab_pipeline(&self, id: u64) -> Result<Pipeline> {
+        let res = self
+            .client
+            .call(
+                Method::GET,
+                &format!("/api/v4/projects/{}/pipelines/{}", self.project_id, id),
+            )
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        serde_json::from_str::<GitLabResponse<Pipeline>>(&text)?.map(|data| Ok(data.to_owned()))
+    }
+
+    async fn call_pipeline_action(&self, id: u64, action: &str) -> Result<Pipeline> {
+        let res = self
+            .client
+            .call(
+                Method::POST,
+                &format!(
+                    "/api/v4/projects/{}/pipelines/{}/{}",
+                    self.project_id, id, action
+                ),
+            )
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        serde_json::from_str::<GitLabResponse<Pipeline>>(&text)?.map(|data| Ok(data.to_owned()))
+    }
+
+    async fn update_issue_state(&self, id: usize, state_event: &str) -> Result<structs::Issue> {
+        let res = self
+            .client
+            .call(
+                Method::PUT,
+                &format!("/api/v4/projects/{}/issues/{}", self.project_id, id),
+            )
+            .header("Content-Type", "application/json")
+            .body(json!({ "state_event": state_event }).to_string())
+            .send()
+            .await?;
+
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        serde_json::from_str::<GitLabResponse<Issue>>(&text)?
+            .map(|data| Ok(structs::Issue::from(data.to_owned())))
+    }
+
     async fn get_user_by_username(&self, username: &str) -> Result<User> {
         let res = self
             .client
-            .call(Method::GET, &format!("/api/users"))
+            .call(Method::GET, "/api/v4/users")
             .query(&[("username", username)])
             .send()
             .await?;
@@ -169,7 +1007,346 @@ impl GitLabRepository {
         serde_json::from_str::<GitLabResponse<Vec<User>>>(&text)?.map(|data| {
             data.first()
                 .cloned()
-                .ok_or(anyhow!("unexpected empty response"))
+                .ok_or_else(|| anyhow!("user not found: {}", username))
         })
     }
+
+    async fn get_user_ids(&self, usernames: &[String]) -> Result<Vec<u64>> {
+        let mut ids = vec![];
+        for username in usernames {
+            ids.push(self.get_user_by_username(username).await?.id);
+        }
+        Ok(ids)
+    }
+
+    async fn get_milestone_id(&self, milestone: &str) -> Result<u64> {
+        let mut req = self.client.call(
+            Method::GET,
+            &format!("/api/v4/projects/{}/milestones", self.project_id),
+        );
+        req = match milestone.parse::<u64>().ok() {
+            Some(iid) => req.query(&[("iids[]", iid)]),
+            None => req.query(&[("title", milestone)]),
+        };
+
+        let res = req.send().await?;
+        let text = res.text().await?;
+        debug!("{:#?}", text);
+
+        serde_json::from_str::<GitLabResponse<Vec<Milestone>>>(&text)?.map(|data| {
+            data.first()
+                .map(|m| m.id)
+                .ok_or_else(|| anyhow!("milestone not found: {}", milestone))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mockito::{mock, Matcher};
+
+    use crate::gitlab::fixtures::{get_repo, ISSUE, MERGE_REQUEST};
+
+    #[tokio::test]
+    async fn test_get_pull_request() -> Result<()> {
+        let _m = mock("GET", "/api/v4/projects/1/merge_requests/1")
+            .match_query(Matcher::Any)
+            .match_header("private-token", "token")
+            .with_body(MERGE_REQUEST)
+            .create();
+
+        let pr = get_repo().get_pull_request(1).await?;
+        assert_eq!(pr.id, 1);
+        assert_eq!(pr.state, PullRequestState::Open);
+        assert!(pr.draft);
+        assert_eq!(pr.mergeable, Some(true));
+        assert_eq!(pr.changed_files, Some(1));
+        assert_eq!(pr.reviewers, vec!["reviewer"]);
+        assert_eq!(pr.head.unwrap(), "feature");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_pull_requests_by_state() -> Result<()> {
+        let cases = vec![
+            (PullRequestState::Open, "opened"),
+            (PullRequestState::Closed, "closed"),
+            (PullRequestState::Merged, "merged"),
+        ];
+        for (state, query) in cases {
+            let m = mock("GET", "/api/v4/projects/1/merge_requests")
+                .match_query(Matcher::UrlEncoded("state".into(), query.into()))
+                .with_header("x-total", "0")
+                .with_header("x-next-page", "")
+                .with_body("[]")
+                .create();
+
+            let mut opt = ListPullRequestOpt::default();
+            opt.state = Some(state);
+            get_repo().list_pull_requests(opt).await?;
+            m.assert();
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_pull_requests_with_filters() -> Result<()> {
+        let _m = mock("GET", "/api/v4/projects/1/merge_requests")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("reviewer_username".into(), "reviewer".into()),
+                Matcher::UrlEncoded("labels".into(), "bug,help wanted".into()),
+                Matcher::UrlEncoded("wip".into(), "yes".into()),
+                Matcher::UrlEncoded("order_by".into(), "updated_at".into()),
+                Matcher::UrlEncoded("sort".into(), "asc".into()),
+            ]))
+            .with_header("x-total", "3")
+            .with_header("x-next-page", "2")
+            .with_body(format!("[{}]", MERGE_REQUEST))
+            .create();
+
+        let mut opt = ListPullRequestOpt::default();
+        opt.reviewer = Some("reviewer".to_string());
+        opt.labels = vec!["bug".to_string(), "help wanted".to_string()];
+        opt.draft = Some(true);
+        opt.sort = Some(PullRequestSort::Updated);
+        opt.ascending = true;
+        let result = get_repo().list_pull_requests(opt).await?;
+        assert_eq!(result.total, Some(3));
+        assert_eq!(result.next_page, Some(2));
+        assert_eq!(result.result[0].id, 1);
+        _m.assert();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_pull_request_discussions() -> Result<()> {
+        let _m1 = mock("GET", "/api/v4/projects/1/merge_requests/1/discussions")
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .with_header("x-next-page", "2")
+            .with_body(
+                r#"[{ "id": "a", "notes": [{
+                    "id": 1,
+                    "body": "looks good",
+                    "author": { "id": 1, "name": "Someone", "username": "someone" },
+                    "created_at": "2020-09-03T00:00:00Z",
+                    "system": false
+                }] }]"#,
+            )
+            .create();
+        let _m2 = mock("GET", "/api/v4/projects/1/merge_requests/1/discussions")
+            .match_query(Matcher::UrlEncoded("page".into(), "2".into()))
+            .with_header("x-next-page", "")
+            .with_body(
+                r#"[{ "id": "b", "notes": [{
+                    "id": 2,
+                    "body": "fix this",
+                    "author": { "id": 2, "name": "Reviewer", "username": "reviewer" },
+                    "created_at": "2020-09-04T00:00:00Z",
+                    "resolvable": true,
+                    "resolved": false,
+                    "position": { "new_path": "src/main.rs", "new_line": 3 }
+                }] }, { "id": "c", "notes": [{
+                    "id": 3,
+                    "body": "added 1 commit",
+                    "author": { "id": 1, "name": "Someone", "username": "someone" },
+                    "created_at": "2020-09-05T00:00:00Z",
+                    "system": true
+                }] }]"#,
+            )
+            .create();
+
+        let discussions = get_repo().list_pull_request_discussions(1).await?;
+        assert_eq!(discussions.len(), 2);
+        assert_eq!(discussions[0].resolved, None);
+        assert_eq!(discussions[1].path.as_deref(), Some("src/main.rs"));
+        assert_eq!(discussions[1].line, Some(3));
+        assert_eq!(discussions[1].resolved, Some(false));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_issues() -> Result<()> {
+        let _m = mock("GET", "/api/v4/projects/1/issues")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("state".into(), "closed".into()),
+                Matcher::UrlEncoded("author_username".into(), "someone".into()),
+                Matcher::UrlEncoded("milestone".into(), "v1.0".into()),
+            ]))
+            .with_header("x-total", "1")
+            .with_header("x-next-page", "")
+            .with_body(format!("[{}]", ISSUE))
+            .create();
+
+        let mut opt = ListIssueOpt::default();
+        opt.state = Some(IssueState::Closed);
+        opt.author = Some("someone".to_string());
+        opt.milestone = Some("v1.0".to_string());
+        let result = get_repo().list_issues(opt).await?;
+        assert_eq!(result.total, Some(1));
+        assert_eq!(result.next_page, None);
+        let issue = &result.result[0];
+        assert_eq!(issue.id, 1);
+        assert_eq!(issue.milestone.as_deref(), Some("v1.0"));
+        assert_eq!(issue.comments, Some(3));
+        _m.assert();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_review_pull_request() -> Result<()> {
+        let m1 = mock("POST", "/api/v4/projects/1/merge_requests/1/draft_notes")
+            .match_body(Matcher::PartialJsonString(
+                r#"{ "note": "looks good" }"#.to_string(),
+            ))
+            .with_status(201)
+            .with_body(r#"{ "id": 1 }"#)
+            .create();
+        let m2 = mock(
+            "POST",
+            "/api/v4/projects/1/merge_requests/1/draft_notes/bulk_publish",
+        )
+        .with_status(204)
+        .create();
+        let m3 = mock("POST", "/api/v4/projects/1/merge_requests/1/approve")
+            .with_status(201)
+            .with_body("{}")
+            .create();
+        let _m4 = mock("GET", "/api/v4/projects/1/merge_requests/1")
+            .match_query(Matcher::Any)
+            .with_body(MERGE_REQUEST)
+            .create();
+
+        let opt = ReviewOpt {
+            event: ReviewEvent::Approve,
+            body: Some("looks good".to_string()),
+            comments: vec![],
+        };
+        get_repo().review_pull_request(1, opt).await?;
+        m1.assert();
+        m2.assert();
+        m3.assert();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_request_changes_without_approval() -> Result<()> {
+        let _m1 = mock("POST", "/api/v4/projects/1/merge_requests/1/draft_notes")
+            .with_status(201)
+            .with_body(r#"{ "id": 1 }"#)
+            .create();
+        let _m2 = mock(
+            "POST",
+            "/api/v4/projects/1/merge_requests/1/draft_notes/bulk_publish",
+        )
+        .with_status(204)
+        .create();
+        let _m3 = mock("GET", "/api/v4/projects/1/merge_requests/1/approvals")
+            .with_body(r#"{ "user_has_approved": false }"#)
+            .create();
+        let m4 = mock("POST", "/api/v4/projects/1/merge_requests/1/unapprove")
+            .with_status(404)
+            .expect(0)
+            .create();
+        let _m5 = mock("GET", "/api/v4/projects/1/merge_requests/1")
+            .match_query(Matcher::Any)
+            .with_body(MERGE_REQUEST)
+            .create();
+
+        let opt = ReviewOpt {
+            event: ReviewEvent::RequestChanges,
+            body: Some("please fix".to_string()),
+            comments: vec![],
+        };
+        get_repo().review_pull_request(1, opt).await?;
+        m4.assert();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_review_pull_request_with_failed_draft() -> Result<()> {
+        let _m1 = mock("POST", "/api/v4/projects/1/merge_requests/1/draft_notes")
+            .match_body(Matcher::PartialJsonString(
+                r#"{ "note": "summary" }"#.to_string(),
+            ))
+            .with_status(201)
+            .with_body(r#"{ "id": 7 }"#)
+            .create();
+        let _m2 = mock("POST", "/api/v4/projects/1/merge_requests/1/draft_notes")
+            .match_body(Matcher::PartialJsonString(
+                r#"{ "note": "inline" }"#.to_string(),
+            ))
+            .with_status(400)
+            .with_body(r#"{ "message": "line is not in the diff" }"#)
+            .create();
+        let _m3 = mock("GET", "/api/v4/projects/1/merge_requests/1")
+            .match_query(Matcher::Any)
+            .with_body(MERGE_REQUEST)
+            .create();
+        let m4 = mock(
+            "DELETE",
+            "/api/v4/projects/1/merge_requests/1/draft_notes/7",
+        )
+        .with_status(204)
+        .create();
+        let m5 = mock(
+            "POST",
+            "/api/v4/projects/1/merge_requests/1/draft_notes/bulk_publish",
+        )
+        .expect(0)
+        .create();
+
+        let opt = ReviewOpt {
+            event: ReviewEvent::Comment,
+            body: Some("summary".to_string()),
+            comments: ReviewOpt::parse_inline_comments(&["src/main.rs:3", "inline"])?,
+        };
+        let err = get_repo().review_pull_request(1, opt).await.unwrap_err();
+        assert_eq!(err.to_string(), "line is not in the diff");
+        m4.assert();
+        m5.assert();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_checks() -> Result<()> {
+        let _m1 = mock("GET", "/api/v4/projects/1/repository/commits/fix%2F%2312")
+            .with_body(
+                r#"{
+                    "id": "abc",
+                    "last_pipeline": {
+                        "id": 2,
+                        "ref": "fix/#12",
+                        "sha": "abc",
+                        "status": "success",
+                        "created_at": "2020-09-01T00:00:00Z",
+                        "web_url": "https://gitlab.com/owner/repo/-/pipelines/2"
+                    }
+                }"#,
+            )
+            .create();
+        let _m2 = mock("GET", "/api/v4/projects/1/pipelines/2/jobs")
+            .match_query(Matcher::Any)
+            .with_header("x-next-page", "")
+            .with_body(
+                r#"[{
+                    "id": 3,
+                    "name": "build",
+                    "stage": "build",
+                    "status": "success",
+                    "started_at": "2020-09-01T00:00:00Z",
+                    "finished_at": "2020-09-01T00:01:00Z",
+                    "duration": 60.0,
+                    "web_url": "https://gitlab.com/owner/repo/-/jobs/3"
+                }]"#,
+            )
+            .create();
+
+        let checks = get_repo().list_checks("fix/#12").await?;
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].name, "build: build");
+        assert_eq!(checks[0].state, structs::CheckState::Success);
+        Ok(())
+    }
 }
@@ -5,11 +5,90 @@ use log::debug;
 use serde_derive::*;
 use serde_json::Value;
 
-use crate::structs::PullRequest;
+use crate::diff;
+use crate::structs::{
+    self, ChangedFile, Check, CheckState, Comment, FileStatus, IssueState, PullRequest,
+    PullRequestState,
+};
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ForkedFrom {
+    pub path_with_namespace: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Project {
     pub id: u64,
+    pub path: String,
+    pub path_with_namespace: String,
+    pub description: Option<String>,
+    pub visibility: Option<String>,
+    pub forked_from_project: Option<ForkedFrom>,
+    pub default_branch: Option<String>,
+    pub web_url: String,
+    pub http_url_to_repo: String,
+    pub ssh_url_to_repo: Option<String>,
+    /// `started` until a fork is ready, and then `finished`.
+    pub import_status: Option<String>,
+    pub permissions: Option<Permissions>,
+    pub star_count: Option<u64>,
+    /// Absent if issues are disabled.
+    pub open_issues_count: Option<u64>,
+}
+
+impl From<Project> for structs::Repo {
+    fn from(project: Project) -> Self {
+        Self {
+            name: project.path_with_namespace,
+            description: project
+                .description
+                .filter(|description| !description.is_empty()),
+            visibility: project
+                .visibility
+                .and_then(|visibility| visibility.parse().ok())
+                .unwrap_or_default(),
+            parent: project
+                .forked_from_project
+                .map(|parent| parent.path_with_namespace),
+            default_branch: project.default_branch,
+            url: project.web_url,
+            clone_url: project.http_url_to_repo,
+            ssh_url: project.ssh_url_to_repo,
+            stars: project.star_count,
+            open_issues: project.open_issues_count,
+            open_pull_requests: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Namespace {
+    pub id: u64,
+}
+
+/// Developers and roles above can push branches.
+pub const DEVELOPER_ACCESS: u64 = 30;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Access {
+    pub access_level: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Permissions {
+    pub project_access: Option<Access>,
+    pub group_access: Option<Access>,
+}
+
+impl Project {
+    pub fn can_push(&self) -> bool {
+        self.permissions.as_ref().is_some_and(|permissions| {
+            [&permissions.project_access, &permissions.group_access]
+                .iter()
+                .filter_map(|access| access.as_ref())
+                .any(|access| access.access_level >= DEVELOPER_ACCESS)
+        })
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -19,6 +98,13 @@ pub struct User {
     pub username: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Milestone {
+    pub id: u64,
+    pub iid: u64,
+    pub title: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MergeRequest {
     id: u64,
@@ -32,9 +118,144 @@ pub struct MergeRequest {
     target_branch: String,
     source_branch: String,
     author: User,
+    #[serde(default)]
+    assignees: Vec<User>,
+    #[serde(default)]
+    reviewers: Vec<User>,
+    #[serde(default)]
+    labels: Vec<String>,
+    draft: Option<bool>,
+    work_in_progress: Option<bool>,
+    merge_status: Option<String>,
+    changes_count: Option<String>,
+    merged_at: Option<String>,
+    web_url: String,
+    pub sha: Option<String>,
+    pub diff_refs: Option<DiffRefs>,
+    pub rebase_in_progress: Option<bool>,
+    pub merge_error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Change {
+    old_path: String,
+    new_path: String,
+    new_file: bool,
+    renamed_file: bool,
+    deleted_file: bool,
+    diff: String,
+}
+
+impl Change {
+    /// The diff of the file with headers, since GitLab only gives hunks.
+    pub fn get_diff(&self) -> String {
+        let old_path = Some(self.old_path.as_str()).filter(|_| !self.new_file);
+        let new_path = Some(self.new_path.as_str()).filter(|_| !self.deleted_file);
+        format!("{}{}", diff::file_header(old_path, new_path), self.diff)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MergeRequestChanges {
+    pub changes: Vec<Change>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Pipeline {
+    pub id: u64,
+    name: Option<String>,
+    #[serde(rename = "ref")]
+    pub git_ref: Option<String>,
+    sha: String,
+    status: String,
+    created_at: String,
+    duration: Option<u64>,
+    web_url: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Job {
+    id: u64,
+    name: String,
+    stage: String,
+    status: String,
+    started_at: Option<String>,
+    finished_at: Option<String>,
+    duration: Option<f64>,
     web_url: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Commit {
+    pub id: String,
+    pub last_pipeline: Option<Pipeline>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DiffRefs {
+    pub base_sha: String,
+    pub head_sha: String,
+    pub start_sha: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Issue {
+    iid: u64,
+    title: String,
+    description: Option<String>,
+    state: String,
+    author: User,
+    #[serde(default)]
+    assignees: Vec<User>,
+    #[serde(default)]
+    labels: Vec<String>,
+    milestone: Option<Milestone>,
+    user_notes_count: Option<u64>,
+    created_at: String,
+    updated_at: String,
+    closed_at: Option<String>,
+    web_url: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Position {
+    new_path: Option<String>,
+    new_line: Option<u64>,
+    old_path: Option<String>,
+    old_line: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Note {
+    id: u64,
+    body: String,
+    author: User,
+    created_at: String,
+    #[serde(default)]
+    system: bool,
+    #[serde(default)]
+    resolvable: bool,
+    resolved: Option<bool>,
+    position: Option<Position>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Discussion {
+    id: String,
+    notes: Vec<Note>,
+}
+
+/// Note of a review which is not published yet.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DraftNote {
+    pub id: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Approvals {
+    pub user_has_approved: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum GitLabResponse<T> {
@@ -85,12 +306,12 @@ where
 
 impl<T> fmt::Display for GitLabResponse<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> fmt::Result {
-        match &*self {
+        match self {
             GitLabResponse::Error { error, message } => {
                 let msg = message
                     .as_ref()
-                    .and_then(|m| Some(format!("{:#}", m)))
-                    .or_else(|| error.as_ref().and_then(|err| Some(err.join("\n"))))
+                    .map(|m| format!("{:#}", m))
+                    .or_else(|| error.as_ref().map(|err| err.join("\n")))
                     .unwrap_or("unknown error".to_string());
                 write!(f, "{}", msg)
             }
@@ -104,11 +325,161 @@ impl From<MergeRequest> for PullRequest {
         Self {
             id: mr.iid,
             title: mr.title,
+            state: match mr.state.as_ref() {
+                "merged" => PullRequestState::Merged,
+                "closed" => PullRequestState::Closed,
+                _ => PullRequestState::Open,
+            },
+            draft: mr.draft.or(mr.work_in_progress).unwrap_or(false),
             author: mr.author.username,
+            body: mr.description,
+            labels: mr.labels,
+            assignees: mr.assignees.into_iter().map(|u| u.username).collect(),
+            reviewers: mr.reviewers.into_iter().map(|u| u.username).collect(),
+            mergeable: match mr.merge_status.as_deref() {
+                Some("can_be_merged") => Some(true),
+                Some("cannot_be_merged") => Some(false),
+                _ => None,
+            },
+            // `changes_count` is a string since it might be something like "1000+"
+            changed_files: mr.changes_count.and_then(|c| c.parse::<u64>().ok()),
             base: Some(mr.target_branch),
             head: Some(mr.source_branch),
+            sha: mr.sha,
+            created_at: mr.created_at,
             updated_at: mr.updated_at,
+            merged_at: mr.merged_at,
             url: mr.web_url,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<Issue> for structs::Issue {
+    fn from(issue: Issue) -> Self {
+        Self {
+            id: issue.iid,
+            title: issue.title,
+            state: match issue.state.as_ref() {
+                "closed" => IssueState::Closed,
+                _ => IssueState::Open,
+            },
+            author: issue.author.username,
+            body: issue.description,
+            labels: issue.labels,
+            assignees: issue.assignees.into_iter().map(|u| u.username).collect(),
+            milestone: issue.milestone.map(|m| m.title),
+            comments: issue.user_notes_count,
+            created_at: issue.created_at,
+            updated_at: issue.updated_at,
+            closed_at: issue.closed_at,
+            url: issue.web_url,
+        }
+    }
+}
+
+impl From<Note> for Comment {
+    fn from(note: Note) -> Self {
+        Self {
+            id: note.id,
+            author: note.author.username,
+            body: note.body,
+            created_at: note.created_at,
+            url: None,
+        }
+    }
+}
+
+impl Discussion {
+    /// Discussions made up of system notes only, like pushes and label
+    /// changes, are not counted as conversation.
+    pub fn into_discussion(self) -> Option<structs::Discussion> {
+        let notes = self
+            .notes
+            .into_iter()
+            .filter(|note| !note.system)
+            .collect::<Vec<Note>>();
+        let first = notes.first()?;
+        let position = first.position.as_ref();
+
+        Some(structs::Discussion {
+            id: self.id,
+            path: position.and_then(|p| p.new_path.clone().or_else(|| p.old_path.clone())),
+            line: position.and_then(|p| p.new_line.or(p.old_line)),
+            resolved: if first.resolvable {
+                first.resolved
+            } else {
+                None
+            },
+            comments: notes.into_iter().map(Comment::from).collect(),
+        })
+    }
+}
+
+impl From<Change> for ChangedFile {
+    fn from(change: Change) -> Self {
+        let (additions, deletions) = diff::count_changes(&change.get_diff());
+        Self {
+            status: if change.new_file {
+                FileStatus::Added
+            } else if change.deleted_file {
+                FileStatus::Removed
+            } else if change.renamed_file {
+                FileStatus::Renamed
+            } else {
+                FileStatus::Modified
+            },
+            path: change.new_path,
+            old_path: if change.renamed_file {
+                Some(change.old_path)
+            } else {
+                None
+            },
+            additions: Some(additions),
+            deletions: Some(deletions),
+        }
+    }
+}
+
+/// Manual jobs wait for someone to play them, so they are treated as skipped
+/// instead of pending forever.
+fn get_check_state(status: &str) -> CheckState {
+    match status {
+        "running" => CheckState::Running,
+        "success" => CheckState::Success,
+        "failed" => CheckState::Failure,
+        "canceled" => CheckState::Cancelled,
+        "skipped" | "manual" => CheckState::Skipped,
+        _ => CheckState::Pending,
+    }
+}
+
+impl From<Pipeline> for structs::Pipeline {
+    fn from(pipeline: Pipeline) -> Self {
+        Self {
+            id: pipeline.id,
+            name: pipeline.name,
+            branch: pipeline.git_ref,
+            sha: pipeline.sha,
+            state: get_check_state(&pipeline.status),
+            created_at: pipeline.created_at,
+            duration: pipeline.duration,
+            url: pipeline.web_url,
+            jobs: vec![],
+        }
+    }
+}
+
+impl From<Job> for Check {
+    fn from(job: Job) -> Self {
+        Self {
+            id: job.id.to_string(),
+            name: format!("{}: {}", job.stage, job.name),
+            state: get_check_state(&job.status),
+            started_at: job.started_at,
+            finished_at: job.finished_at,
+            duration: job.duration.map(|duration| duration as u64),
+            url: Some(job.web_url),
         }
     }
 }
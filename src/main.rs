@@ -1,7 +1,13 @@
+mod bitbucket;
 mod command;
-mod gitlab;
+mod diff;
+mod git;
+mod gitea;
 mod github;
+mod gitlab;
 mod logger;
+mod markdown;
+mod output;
 mod profile;
 mod repository;
 mod structs;
@@ -0,0 +1,116 @@
+use colored::*;
+
+/// Render Markdown into plain lines of text decorated with terminal colors.
+///
+/// It only handles the most common syntax used in descriptions of pull
+/// requests: headings, lists, quotes, code blocks and inline emphasis.
+pub fn render(text: &str) -> String {
+    let mut lines = vec![];
+    let mut in_code_block = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+
+        if in_code_block {
+            lines.push(format!("  {}", line).dimmed().to_string());
+            continue;
+        }
+
+        let rendered = if let Some(heading) = strip_heading(trimmed) {
+            render_inline(heading).bold().underline().to_string()
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+            .or_else(|| trimmed.strip_prefix("+ "))
+        {
+            let indent = &line[..line.len() - trimmed.len()];
+            format!("{}{} {}", indent, "•".bold(), render_inline(item))
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            format!(
+                "{} {}",
+                "│".dimmed(),
+                render_inline(quote.trim_start()).italic()
+            )
+        } else if is_thematic_break(trimmed) {
+            "─".repeat(40).dimmed().to_string()
+        } else {
+            render_inline(line)
+        };
+
+        lines.push(rendered);
+    }
+
+    lines.join("\n")
+}
+
+fn strip_heading(line: &str) -> Option<&str> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&level) {
+        line[level..].strip_prefix(' ').map(|s| s.trim())
+    } else {
+        None
+    }
+}
+
+fn is_thematic_break(line: &str) -> bool {
+    let line = line.replace(' ', "");
+    line.len() >= 3
+        && ["-", "*", "_"]
+            .iter()
+            .any(|c| line.chars().all(|x| x.to_string() == *c))
+}
+
+fn render_inline(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        if let Some((content, remain)) = take_delimited(rest, "`") {
+            result.push_str(&content.cyan().to_string());
+            rest = remain;
+        } else if let Some((content, remain)) =
+            take_delimited(rest, "**").or_else(|| take_delimited(rest, "__"))
+        {
+            result.push_str(&content.bold().to_string());
+            rest = remain;
+        } else if let Some((content, remain)) = take_delimited(rest, "~~") {
+            result.push_str(&content.strikethrough().to_string());
+            rest = remain;
+        } else {
+            let c = rest.chars().next().unwrap();
+            result.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    result
+}
+
+fn take_delimited<'a>(text: &'a str, delimiter: &str) -> Option<(&'a str, &'a str)> {
+    let inner = text.strip_prefix(delimiter)?;
+    let end = inner.find(delimiter)?;
+    if end == 0 {
+        return None;
+    }
+    Some((&inner[..end], &inner[end + delimiter.len()..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        colored::control::set_override(false);
+        let text = "# Title\n\nSome `code` and **bold**\n- item\n  * nested\n> quote\n```\n# not heading\n```";
+        assert_eq!(
+            render(text),
+            "Title\n\nSome code and bold\n• item\n  • nested\n│ quote\n  # not heading"
+        );
+    }
+}
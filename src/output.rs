@@ -0,0 +1,137 @@
+use std::fmt::Display;
+use std::io::{stdout, ErrorKind, Write};
+use std::str::FromStr;
+
+use anyhow::{bail, Error, Result};
+use clap::ArgMatches;
+use futures::{pin_mut, Stream, StreamExt, TryStreamExt};
+use serde::Serialize;
+
+use crate::structs::List;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Human,
+    Json,
+    Yaml,
+    Tsv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(OutputFormat::Human),
+            "json" => Ok(OutputFormat::Json),
+            "yaml" => Ok(OutputFormat::Yaml),
+            "tsv" => Ok(OutputFormat::Tsv),
+            _ => bail!("unknown output format: {}", s),
+        }
+    }
+}
+
+impl<'a> From<&ArgMatches<'a>> for OutputFormat {
+    fn from(matches: &ArgMatches<'a>) -> Self {
+        matches
+            .value_of("output")
+            .and_then(|s| s.parse::<OutputFormat>().ok())
+            .unwrap_or(OutputFormat::Human)
+    }
+}
+
+/// Data which can be printed by every output format.
+pub trait Output: Serialize + Display {
+    /// Fields of each line in TSV output.
+    fn rows(&self) -> Vec<Vec<String>>;
+}
+
+impl OutputFormat {
+    pub fn print<T: Output>(&self, data: &T) -> Result<()> {
+        match self {
+            OutputFormat::Human => {
+                let text = format!("{:#}", data);
+                println!("{}", text.trim_end_matches('\n'));
+            }
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(data)?),
+            OutputFormat::Yaml => print!("{}", serde_yaml::to_string(data)?),
+            OutputFormat::Tsv => {
+                for row in data.rows() {
+                    println!("{}", format_row(&row));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Print items one by one as soon as they are fetched, except for JSON
+    /// and YAML which need all of them to be a list.
+    pub async fn print_stream<T, S>(&self, stream: S) -> Result<()>
+    where
+        T: Output,
+        S: Stream<Item = Result<T>>,
+    {
+        pin_mut!(stream);
+        if let OutputFormat::Json | OutputFormat::Yaml = self {
+            let items = stream.try_collect::<Vec<T>>().await?;
+            return self.print(&List(items));
+        }
+
+        while let Some(item) = stream.next().await {
+            let item = item?;
+            let text = match self {
+                OutputFormat::Tsv => item
+                    .rows()
+                    .iter()
+                    .map(|row| format!("{}\n", format_row(row)))
+                    .collect::<String>(),
+                _ => format!("{}\n", item),
+            };
+            match stdout().write_all(text.as_bytes()) {
+                // stop fetching when the reader like `head` has enough
+                Err(err) if err.kind() == ErrorKind::BrokenPipe => return Ok(()),
+                result => result?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn format_row(row: &[String]) -> String {
+    row.iter()
+        .map(|field| field.replace(['\t', '\n', '\r'], " "))
+        .collect::<Vec<String>>()
+        .join("\t")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::structs::{PullRequest, PullRequestState};
+
+    #[test]
+    fn test_pull_request_output() -> Result<()> {
+        let pr = PullRequest {
+            id: 1,
+            title: "fix\tsomething".to_string(),
+            state: PullRequestState::Merged,
+            base: Some("master".to_string()),
+            head: None,
+            author: "someone".to_string(),
+            updated_at: "2020-09-01T00:00:00Z".to_string(),
+            url: "https://github.com/cnwangjie/yag/pull/1".to_string(),
+            ..Default::default()
+        };
+
+        let json = serde_json::to_value(&pr)?;
+        assert_eq!(json["state"], "merged");
+        assert_eq!(json["head"], serde_json::Value::Null);
+
+        let rows = pr.rows();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][0], "1");
+        assert_eq!(rows[0][5], "");
+        Ok(())
+    }
+}
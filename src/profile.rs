@@ -1,5 +1,11 @@
-use crate::github::profile::{GitHubConfig, GitHubPrompter};
-use crate::gitlab::profile::{GitLabSelfHostedConfig, GitLabSelfHostedPrompter};
+use crate::bitbucket::profile::{BitbucketConfig, BitbucketPrompter};
+use crate::gitea::profile::{GiteaConfig, GiteaPrompter};
+use crate::github::profile::{
+    GitHubConfig, GitHubEnterpriseConfig, GitHubEnterprisePrompter, GitHubPrompter, GITHUB_HOST,
+};
+use crate::gitlab::profile::{
+    GitLabConfig, GitLabPrompter, GitLabSelfHostedConfig, GitLabSelfHostedPrompter, GITLAB_HOST,
+};
 use crate::utils;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
@@ -22,24 +28,104 @@ pub trait Prompter {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Profile {
     pub gitlab_self_hosted: Option<Vec<GitLabSelfHostedConfig>>,
+    pub gitlab: Option<GitLabConfig>,
     pub github: Option<GitHubConfig>,
+    pub github_enterprise: Option<Vec<GitHubEnterpriseConfig>>,
+    pub gitea: Option<Vec<GiteaConfig>>,
+    pub bitbucket: Option<Vec<BitbucketConfig>>,
+}
+
+/// Type of the service which hosts the remote repository.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Provider {
+    GitHub,
+    GitLab,
+    Gitea,
+    Bitbucket,
 }
 
 impl Profile {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             gitlab_self_hosted: None,
+            gitlab: None,
             github: None,
+            github_enterprise: None,
+            gitea: None,
+            bitbucket: None,
         }
     }
 
-    pub fn get_gitlab_token_by_host(&self, host: &str) -> Option<String> {
-        self.gitlab_self_hosted.as_ref().and_then(|configs| {
-            configs
+    /// Find out the provider by matching the host against configured profiles.
+    pub fn get_provider_by_host(&self, host: &str) -> Option<Provider> {
+        if host == GITHUB_HOST
+            || self
+                .github_enterprise
+                .iter()
+                .flatten()
+                .any(|config| config.host == host)
+        {
+            Some(Provider::GitHub)
+        } else if host == GITLAB_HOST
+            || self
+                .gitlab_self_hosted
                 .iter()
-                .find(|config| config.host.eq(host))
-                .and_then(|config| Some(config.token.clone()))
-        })
+                .flatten()
+                .any(|config| config.host == host)
+        {
+            Some(Provider::GitLab)
+        } else if self
+            .gitea
+            .iter()
+            .flatten()
+            .any(|config| config.host == host)
+        {
+            Some(Provider::Gitea)
+        } else if self
+            .bitbucket
+            .iter()
+            .flatten()
+            .any(|config| config.host == host)
+        {
+            Some(Provider::Bitbucket)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_gitea_by_host(&self, host: &str) -> Option<&GiteaConfig> {
+        self.gitea
+            .as_ref()
+            .and_then(|configs| configs.iter().find(|config| config.host.eq(host)))
+    }
+
+    pub fn get_bitbucket_by_host(&self, host: &str) -> Option<&BitbucketConfig> {
+        self.bitbucket
+            .as_ref()
+            .and_then(|configs| configs.iter().find(|config| config.host.eq(host)))
+    }
+
+    pub fn get_github_enterprise_by_host(&self, host: &str) -> Option<&GitHubEnterpriseConfig> {
+        self.github_enterprise
+            .as_ref()
+            .and_then(|configs| configs.iter().find(|config| config.host.eq(host)))
+    }
+
+    pub fn get_gitlab_token_by_host(&self, host: &str) -> Option<String> {
+        self.gitlab_self_hosted
+            .as_ref()
+            .and_then(|configs| {
+                configs
+                    .iter()
+                    .find(|config| config.host.eq(host))
+                    .map(|config| config.token.clone())
+            })
+            .or_else(|| {
+                self.gitlab
+                    .as_ref()
+                    .filter(|_| host == GITLAB_HOST)
+                    .map(|config| config.token.clone())
+            })
     }
 }
 
@@ -59,7 +145,7 @@ pub async fn write_profile(profile: &Profile) -> Result<()> {
 
 fn migrate_profile(value: &mut toml::Value) {
     // gitlab_self_hosted map to array
-    value.as_table_mut().map(|v| {
+    if let Some(v) = value.as_table_mut() {
         if let Some(config) = v.get("gitlab_self_hosted") {
             if !config.is_array() {
                 let config = config.clone();
@@ -69,7 +155,7 @@ fn migrate_profile(value: &mut toml::Value) {
                 );
             }
         }
-    });
+    }
 }
 
 pub async fn load_profile() -> Result<Profile> {
@@ -97,8 +183,12 @@ pub async fn load_profile() -> Result<Profile> {
 
 pub async fn prompt_add_profile(profile: &mut Profile) -> Result<()> {
     let prompters: Vec<Box<dyn Prompter>> = vec![
-        Box::new(GitLabSelfHostedPrompter::default()),
-        Box::new(GitHubPrompter::default()),
+        Box::new(GitLabSelfHostedPrompter),
+        Box::new(GitHubPrompter),
+        Box::new(GitLabPrompter),
+        Box::new(GitHubEnterprisePrompter),
+        Box::new(GiteaPrompter),
+        Box::new(BitbucketPrompter),
     ];
     for (i, prompter) in prompters.iter().enumerate() {
         println!("{:>3}: {}", i + 1, prompter.display_name());
@@ -112,7 +202,7 @@ pub async fn prompt_add_profile(profile: &mut Profile) -> Result<()> {
     ))?
     .parse::<usize>()
     .ok()
-    .filter(|index| choice_range.contains(&index))
+    .filter(|index| choice_range.contains(index))
     .and_then(|index| prompters.get(index - 1))
     .ok_or(anyhow!("invalid choice"))?;
 
@@ -149,10 +239,10 @@ mod tests {
         let mut writer = unsafe { File::from_raw_fd(fds[1]) };
         writer.write_all(content.as_bytes())?;
         writer.flush()?;
+        drop(writer);
         unsafe {
             libc::dup2(STDIN_FILENO, original);
             libc::close(fds[0]);
-            libc::close(fds[1]);
         }
         Ok(())
     }
@@ -165,6 +255,55 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_get_gitlab_token_by_host() {
+        let mut profile = Profile::new();
+        profile.gitlab = Some(GitLabConfig {
+            token: "a".to_string(),
+        });
+        profile.gitlab_self_hosted = Some(vec![GitLabSelfHostedConfig {
+            host: "gitlab.example.com".to_string(),
+            token: "b".to_string(),
+        }]);
+        assert_eq!(profile.get_gitlab_token_by_host("gitlab.com").unwrap(), "a");
+        assert_eq!(
+            profile
+                .get_gitlab_token_by_host("gitlab.example.com")
+                .unwrap(),
+            "b"
+        );
+        assert!(profile.get_gitlab_token_by_host("example.com").is_none());
+    }
+
+    #[test]
+    fn test_get_provider_by_host() {
+        let mut profile = Profile::new();
+        profile.github_enterprise = Some(vec![GitHubEnterpriseConfig {
+            host: "github.example.com".to_string(),
+            token: "a".to_string(),
+            api_endpoint: None,
+            graphql_endpoint: None,
+        }]);
+        profile.gitlab_self_hosted = Some(vec![GitLabSelfHostedConfig {
+            host: "gitlab.example.com".to_string(),
+            token: "b".to_string(),
+        }]);
+        let provider = |host| profile.get_provider_by_host(host);
+        assert_eq!(provider("github.com"), Some(Provider::GitHub));
+        assert_eq!(provider("github.example.com"), Some(Provider::GitHub));
+        assert_eq!(provider("gitlab.com"), Some(Provider::GitLab));
+        assert_eq!(provider("gitlab.example.com"), Some(Provider::GitLab));
+        assert_eq!(provider("example.com"), None);
+
+        let config = profile
+            .get_github_enterprise_by_host("github.example.com")
+            .unwrap();
+        assert_eq!(
+            config.get_api_endpoint(),
+            "https://github.example.com/api/v3"
+        );
+    }
+
     #[tokio::test]
     async fn test_prompt_add_profile() -> Result<()> {
         let mut profile = Profile::new();
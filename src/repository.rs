@@ -1,37 +1,573 @@
+use crate::bitbucket::repository::BitbucketRepository;
+use crate::git::Git;
+use crate::gitea::repository::GiteaRepository;
+use crate::github::forge::GitHubForge;
 use crate::github::repository::GitHubRepository;
+use crate::gitlab::forge::GitLabForge;
 use crate::gitlab::repository::GitLabRepository;
-use crate::structs::{PaginationResult, PullRequest};
-use crate::utils::spawn;
+use crate::profile::{load_profile, Profile, Provider};
+use crate::structs::{
+    ChangedFile, Check, Comment, Discussion, Issue, IssueState, PaginationResult, Pipeline,
+    PullRequest, PullRequestState, Repo, Visibility,
+};
+use crate::utils;
 use anyhow::*;
 use async_trait::async_trait;
 use clap::ArgMatches;
+use futures::future::{self, Future};
+use futures::stream::{self, Stream, StreamExt};
 use git_url_parse::GitUrl;
 use log::debug;
+use std::convert::TryFrom;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::Duration;
 
-#[derive(Debug, Default)]
+/// Interval and times to check whether a fork is ready, since forks are
+/// created asynchronously on GitHub and GitLab.
+pub const FORK_POLL_INTERVAL: Duration = Duration::from_secs(2);
+pub const FORK_POLL_TIMES: usize = 60;
+
+const DEFAULT_PER_PAGE: usize = 10;
+/// The most items per page allowed by GitHub and GitLab.
+const MAX_PER_PAGE: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PullRequestSort {
+    Created,
+    Updated,
+    Comments,
+}
+
+impl FromStr for PullRequestSort {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "created" => Ok(PullRequestSort::Created),
+            "updated" => Ok(PullRequestSort::Updated),
+            "comments" => Ok(PullRequestSort::Comments),
+            _ => bail!("unknown sort field: {}", s),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
 pub struct ListPullRequestOpt {
     pub author: Option<String>,
+    pub assignee: Option<String>,
+    /// User who is requested to review.
+    pub reviewer: Option<String>,
+    pub labels: Vec<String>,
+    pub base: Option<String>,
+    pub head: Option<String>,
+    /// `None` means both drafts and pull requests ready for review.
+    pub draft: Option<bool>,
+    pub search: Option<String>,
+    /// `None` means the default order of the platform, which is usually the
+    /// newest first.
+    pub sort: Option<PullRequestSort>,
+    pub ascending: bool,
     page: Option<usize>,
+    per_page: Option<usize>,
     pub me: bool,
+    /// `None` means pull requests in any state.
+    pub state: Option<PullRequestState>,
 }
 
 impl<'a> From<ArgMatches<'a>> for ListPullRequestOpt {
     fn from(matches: ArgMatches<'a>) -> Self {
         debug!("matches: {:#?}", matches);
         Self {
-            author: matches.value_of("author").and_then(|s| Some(s.to_string())),
+            author: matches.value_of("author").map(|s| s.to_string()),
+            assignee: matches.value_of("assignee").map(|s| s.to_string()),
+            reviewer: matches.value_of("reviewer").map(|s| s.to_string()),
+            labels: get_list(&matches, "label"),
+            base: matches.value_of("base").map(|s| s.to_string()),
+            head: matches.value_of("head").map(|s| s.to_string()),
+            draft: match (matches.is_present("draft"), matches.is_present("ready")) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            },
+            search: matches.value_of("search").map(|s| s.to_string()),
+            sort: matches
+                .value_of("sort")
+                .and_then(|s| s.parse::<PullRequestSort>().ok()),
+            ascending: matches.value_of("order") == Some("asc"),
             page: matches
                 .value_of("page")
                 .and_then(|s| s.parse::<usize>().ok()),
+            per_page: get_per_page(&matches),
             me: matches.is_present("me"),
+            state: match matches.value_of("status") {
+                Some("all") => None,
+                Some(status) => status.parse::<PullRequestState>().ok(),
+                None => Some(PullRequestState::Open),
+            },
         }
     }
 }
 
 impl ListPullRequestOpt {
+    /// Names of the given filters, to reject those unsupported by the
+    /// platform instead of ignoring them silently.
+    pub fn get_filters(&self) -> Vec<&'static str> {
+        [
+            ("assignee", self.assignee.is_some()),
+            ("reviewer", self.reviewer.is_some()),
+            ("label", !self.labels.is_empty()),
+            ("base", self.base.is_some()),
+            ("head", self.head.is_some()),
+            ("draft", self.draft.is_some()),
+            ("search", self.search.is_some()),
+        ]
+        .iter()
+        .filter(|(_, given)| *given)
+        .map(|(name, _)| *name)
+        .collect()
+    }
+
     pub fn get_page(&self) -> usize {
         self.page.unwrap_or(0)
     }
+
+    pub fn get_per_page(&self) -> usize {
+        self.per_page.unwrap_or(DEFAULT_PER_PAGE)
+    }
+
+    pub fn with_page(mut self, page: usize) -> Self {
+        self.page = Some(page);
+        self
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CreatePullRequestOpt {
+    pub source_branch: String,
+    pub target_branch: String,
+    pub title: String,
+    pub body: Option<String>,
+    pub draft: bool,
+    pub reviewers: Vec<String>,
+    pub assignees: Vec<String>,
+    pub labels: Vec<String>,
+    pub milestone: Option<String>,
+}
+
+impl<'a> From<ArgMatches<'a>> for CreatePullRequestOpt {
+    /// Branches and title are not included since they need to be resolved by
+    /// the command.
+    fn from(matches: ArgMatches<'a>) -> Self {
+        debug!("matches: {:#?}", matches);
+        Self {
+            body: matches.value_of("body").map(|s| s.to_string()),
+            draft: matches.is_present("draft"),
+            reviewers: get_list(&matches, "reviewer"),
+            assignees: get_list(&matches, "assignee"),
+            labels: get_list(&matches, "label"),
+            milestone: matches.value_of("milestone").map(|s| s.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Items to fetch per page, where `--all` and `--limit` fetch as many as
+/// needed at once unless `--per-page` is given.
+fn get_per_page(matches: &ArgMatches) -> Option<usize> {
+    let limit = matches
+        .value_of("limit")
+        .and_then(|s| s.parse::<usize>().ok());
+    matches
+        .value_of("per-page")
+        .and_then(|s| s.parse::<usize>().ok())
+        .or_else(|| limit.map(|limit| limit.min(MAX_PER_PAGE)))
+        .or_else(|| Some(MAX_PER_PAGE).filter(|_| matches.is_present("all")))
+}
+
+/// Fetch items lazily page by page from `page`, until there is no next page
+/// or `limit` items are yielded.
+pub fn paginate<'a, T, F, Fut>(
+    page: usize,
+    limit: Option<usize>,
+    fetch: F,
+) -> impl Stream<Item = Result<T>> + 'a
+where
+    T: 'a,
+    F: Fn(usize) -> Fut + 'a,
+    Fut: Future<Output = Result<PaginationResult<T>>> + 'a,
+{
+    stream::unfold((fetch, Some(page)), |(fetch, page)| async move {
+        let result = fetch(page?).await;
+        let next_page = result.as_ref().ok().and_then(|result| result.next_page);
+        Some((result, (fetch, next_page)))
+    })
+    .flat_map(|result| match result {
+        Result::Ok(result) => stream::iter(result.result.into_iter().map(Ok)).left_stream(),
+        Err(err) => stream::once(future::ready(Err(err))).right_stream(),
+    })
+    .take(limit.unwrap_or(usize::MAX))
+}
+
+/// Split heads like `owner:branch`, which are branches of forks owned by
+/// others.
+pub fn parse_head(head: &str) -> (Option<&str>, &str) {
+    match head.split_once(':') {
+        Some((owner, branch)) => (Some(owner), branch),
+        None => (None, head),
+    }
+}
+
+/// Validator of arguments like `--limit`, which must be a positive number.
+pub fn validate_positive_number(value: String) -> std::result::Result<(), String> {
+    match value.parse::<usize>() {
+        Result::Ok(n) if n > 0 => Result::Ok(()),
+        _ => Err(format!("{} is not a positive number", value)),
+    }
+}
+
+/// Id of pull requests, issues, pipelines or jobs in the argument.
+pub fn get_id<T: FromStr>(matches: &ArgMatches, name: &str) -> Result<T> {
+    let value = matches.value_of(name).unwrap_or_default();
+    value
+        .parse::<T>()
+        .map_err(|_| anyhow!("invalid id: {}", value))
+}
+
+/// Values of an argument which can be specified multiple times or separated by
+/// commas.
+pub fn get_list(matches: &ArgMatches, name: &str) -> Vec<String> {
+    matches
+        .values_of(name)
+        .map(|values| {
+            values
+                .flat_map(|value| value.split(','))
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum MergeMethod {
+    #[default]
+    Merge,
+    Squash,
+    Rebase,
+}
+
+/// Error of setting reviewers, assignees, labels or milestone after the pull
+/// request is created, which should not make users create it again.
+#[derive(Debug)]
+pub struct IncompletePullRequest {
+    pub pr: PullRequest,
+    pub errors: Vec<Error>,
+}
+
+impl std::fmt::Display for IncompletePullRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "pull request #{} is created, but ", self.pr.id)?;
+        let errors = self.errors.iter().map(|err| err.to_string());
+        write!(f, "{}", errors.collect::<Vec<String>>().join(", "))
+    }
+}
+
+impl std::error::Error for IncompletePullRequest {}
+
+#[derive(Debug, Default)]
+pub struct MergePullRequestOpt {
+    pub method: MergeMethod,
+    pub delete_branch: bool,
+    pub auto: bool,
+    pub title: Option<String>,
+    pub message: Option<String>,
+    pub sha: Option<String>,
+}
+
+impl<'a> From<ArgMatches<'a>> for MergePullRequestOpt {
+    fn from(matches: ArgMatches<'a>) -> Self {
+        debug!("matches: {:#?}", matches);
+        let method = if matches.is_present("squash") {
+            MergeMethod::Squash
+        } else if matches.is_present("rebase") {
+            MergeMethod::Rebase
+        } else {
+            MergeMethod::Merge
+        };
+        Self {
+            method,
+            delete_branch: matches.is_present("delete-branch"),
+            auto: matches.is_present("auto"),
+            title: matches.value_of("title").map(|s| s.to_string()),
+            message: matches.value_of("message").map(|s| s.to_string()),
+            sha: matches.value_of("sha").map(|s| s.to_string()),
+        }
+    }
+}
+
+impl MergePullRequestOpt {
+    /// Commit title and message joined in the form of a full commit message.
+    pub fn get_commit_message(&self) -> Option<String> {
+        match (&self.title, &self.message) {
+            (Some(title), Some(message)) => Some(format!("{}\n\n{}", title, message)),
+            (Some(title), None) => Some(title.to_owned()),
+            (None, Some(message)) => Some(message.to_owned()),
+            (None, None) => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ReviewEvent {
+    Approve,
+    RequestChanges,
+    #[default]
+    Comment,
+}
+
+/// Comment on a line of the new version of a file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InlineComment {
+    pub path: String,
+    pub line: u64,
+    pub body: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ReviewOpt {
+    pub event: ReviewEvent,
+    pub body: Option<String>,
+    pub comments: Vec<InlineComment>,
+}
+
+impl ReviewOpt {
+    /// Parse inline comments given as pairs of `path:line` and body.
+    pub fn parse_inline_comments(values: &[&str]) -> Result<Vec<InlineComment>> {
+        values
+            .chunks(2)
+            .map(|pair| {
+                let (location, body) = match pair {
+                    [location, body] => (location, body),
+                    _ => bail!("missing body of inline comment: {}", pair[0]),
+                };
+                let (path, line) = location
+                    .rsplit_once(':')
+                    .and_then(|(path, line)| Some((path, line.parse::<u64>().ok()?)))
+                    .filter(|(path, _)| !path.is_empty())
+                    .ok_or_else(|| anyhow!("invalid location, expect path:line: {}", location))?;
+                Ok(InlineComment {
+                    path: path.to_string(),
+                    line,
+                    body: body.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Reviews other than approvals are rejected without a body, even with
+    /// inline comments.
+    pub fn needs_body(&self) -> bool {
+        self.event != ReviewEvent::Approve && self.body.as_deref().map_or(true, str::is_empty)
+    }
+}
+
+impl<'a> TryFrom<ArgMatches<'a>> for ReviewOpt {
+    type Error = Error;
+
+    /// The body is not included since it might need to be composed.
+    fn try_from(matches: ArgMatches<'a>) -> Result<Self> {
+        debug!("matches: {:#?}", matches);
+        let event = if matches.is_present("approve") {
+            ReviewEvent::Approve
+        } else if matches.is_present("request-changes") {
+            ReviewEvent::RequestChanges
+        } else {
+            ReviewEvent::Comment
+        };
+        let inline = matches
+            .values_of("inline")
+            .map(|values| values.collect::<Vec<&str>>())
+            .unwrap_or_default();
+        Ok(Self {
+            event,
+            body: matches.value_of("body").map(|s| s.to_string()),
+            comments: Self::parse_inline_comments(&inline)?,
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ListIssueOpt {
+    pub author: Option<String>,
+    pub assignee: Option<String>,
+    pub labels: Vec<String>,
+    pub milestone: Option<String>,
+    page: Option<usize>,
+    per_page: Option<usize>,
+    pub me: bool,
+    /// `None` means issues in any state.
+    pub state: Option<IssueState>,
+}
+
+impl<'a> From<ArgMatches<'a>> for ListIssueOpt {
+    fn from(matches: ArgMatches<'a>) -> Self {
+        debug!("matches: {:#?}", matches);
+        Self {
+            author: matches.value_of("author").map(|s| s.to_string()),
+            assignee: matches.value_of("assignee").map(|s| s.to_string()),
+            labels: get_list(&matches, "label"),
+            milestone: matches.value_of("milestone").map(|s| s.to_string()),
+            page: matches
+                .value_of("page")
+                .and_then(|s| s.parse::<usize>().ok()),
+            per_page: get_per_page(&matches),
+            me: matches.is_present("me"),
+            state: match matches.value_of("status") {
+                Some("all") => None,
+                Some(status) => status.parse::<IssueState>().ok(),
+                None => Some(IssueState::Open),
+            },
+        }
+    }
+}
+
+impl ListIssueOpt {
+    pub fn get_page(&self) -> usize {
+        self.page.unwrap_or(0)
+    }
+
+    pub fn get_per_page(&self) -> usize {
+        self.per_page.unwrap_or(DEFAULT_PER_PAGE)
+    }
+
+    pub fn with_page(mut self, page: usize) -> Self {
+        self.page = Some(page);
+        self
+    }
+}
+
+/// Fields which are `None` are left as they are, while given lists replace
+/// the existing ones.
+#[derive(Debug, Default)]
+pub struct UpdatePullRequestOpt {
+    pub title: Option<String>,
+    pub body: Option<String>,
+    pub target_branch: Option<String>,
+    pub draft: Option<bool>,
+    pub reviewers: Option<Vec<String>>,
+    pub assignees: Option<Vec<String>>,
+    pub labels: Option<Vec<String>>,
+    pub milestone: Option<String>,
+}
+
+impl<'a> From<ArgMatches<'a>> for UpdatePullRequestOpt {
+    fn from(matches: ArgMatches<'a>) -> Self {
+        debug!("matches: {:#?}", matches);
+        let get_list = |name| Some(get_list(&matches, name)).filter(|_| matches.is_present(name));
+        Self {
+            title: matches.value_of("title").map(|s| s.to_string()),
+            body: matches.value_of("body").map(|s| s.to_string()),
+            target_branch: matches.value_of("base").map(|s| s.to_string()),
+            draft: match (matches.is_present("draft"), matches.is_present("ready")) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            },
+            reviewers: get_list("reviewer"),
+            assignees: get_list("assignee"),
+            labels: get_list("label"),
+            milestone: matches.value_of("milestone").map(|s| s.to_string()),
+        }
+    }
+}
+
+impl UpdatePullRequestOpt {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.body.is_none()
+            && self.target_branch.is_none()
+            && self.draft.is_none()
+            && self.reviewers.is_none()
+            && self.assignees.is_none()
+            && self.labels.is_none()
+            && self.milestone.is_none()
+    }
+}
+
+/// Add or remove the prefix which marks a pull request as draft by its
+/// title, where the first one of `prefixes` is the one to add.
+pub fn set_draft_prefix(title: &str, draft: bool, prefixes: &[&str]) -> String {
+    let mut title = title.trim_start();
+    while let Some(prefix) = prefixes.iter().find(|prefix| {
+        title
+            .get(..prefix.len())
+            .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+    }) {
+        title = title[prefix.len()..].trim_start();
+    }
+
+    match prefixes.first() {
+        Some(prefix) if draft => format!("{} {}", prefix, title),
+        _ => title.to_string(),
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CreateIssueOpt {
+    pub title: String,
+    pub body: Option<String>,
+    pub assignees: Vec<String>,
+    pub labels: Vec<String>,
+    pub milestone: Option<String>,
+}
+
+impl<'a> From<ArgMatches<'a>> for CreateIssueOpt {
+    fn from(matches: ArgMatches<'a>) -> Self {
+        debug!("matches: {:#?}", matches);
+        Self {
+            title: matches.value_of("title").unwrap_or_default().to_string(),
+            body: matches.value_of("body").map(|s| s.to_string()),
+            assignees: get_list(&matches, "assignee"),
+            labels: get_list(&matches, "label"),
+            milestone: matches.value_of("milestone").map(|s| s.to_string()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CreateRepositoryOpt {
+    /// Organization or group to create in, or else the current user.
+    pub owner: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    pub visibility: Visibility,
+}
+
+impl CreateRepositoryOpt {
+    /// Options of `repo create`, where the path is the name argument without
+    /// the host.
+    pub fn new(path: &str, matches: &ArgMatches) -> Self {
+        debug!("matches: {:#?}", matches);
+        let (owner, name) = match path.rsplit_once('/') {
+            Some((owner, name)) => (Some(owner.to_string()), name.to_string()),
+            None => (None, path.to_string()),
+        };
+        let visibility = if matches.is_present("private") {
+            Visibility::Private
+        } else if matches.is_present("internal") {
+            Visibility::Internal
+        } else {
+            Visibility::Public
+        };
+        Self {
+            owner,
+            name,
+            description: matches.value_of("description").map(|s| s.to_string()),
+            visibility,
+        }
+    }
 }
 
 #[async_trait]
@@ -41,35 +577,438 @@ pub trait Repository {
         &self,
         opt: ListPullRequestOpt,
     ) -> Result<PaginationResult<PullRequest>>;
-    async fn create_pull_request(
+    async fn create_pull_request(&self, opt: CreatePullRequestOpt) -> Result<PullRequest>;
+    async fn close_pull_request(&self, id: usize) -> Result<PullRequest>;
+    async fn reopen_pull_request(&self, id: usize) -> Result<PullRequest>;
+    async fn update_pull_request(
         &self,
-        source_branch: &str,
-        target_branch: &str,
-        title: &str,
+        id: usize,
+        opt: UpdatePullRequestOpt,
     ) -> Result<PullRequest>;
-    async fn close_pull_request(&self, id: usize) -> Result<PullRequest>;
+    async fn merge_pull_request(&self, id: usize, opt: MergePullRequestOpt) -> Result<PullRequest>;
+    /// The ref on the remote which always points to the head of the pull
+    /// request, even if it comes from a fork.
+    fn get_pull_request_ref(&self, id: usize) -> String;
+    /// Comments on the conversation and the diff, in the order of creation.
+    async fn list_pull_request_discussions(&self, id: usize) -> Result<Vec<Discussion>>;
+    async fn comment_pull_request(&self, id: usize, body: &str) -> Result<Comment>;
+    /// Unified diff of all changes of the pull request.
+    async fn get_pull_request_diff(&self, id: usize) -> Result<String>;
+    async fn list_pull_request_files(&self, id: usize) -> Result<Vec<ChangedFile>>;
+    /// Submit a review at once, along with its inline comments.
+    async fn review_pull_request(&self, id: usize, opt: ReviewOpt) -> Result<PullRequest>;
+
+    async fn get_issue(&self, id: usize) -> Result<Issue>;
+    async fn list_issues(&self, opt: ListIssueOpt) -> Result<PaginationResult<Issue>>;
+    async fn create_issue(&self, opt: CreateIssueOpt) -> Result<Issue>;
+    async fn close_issue(&self, id: usize) -> Result<Issue>;
+    async fn reopen_issue(&self, id: usize) -> Result<Issue>;
+
+    /// Check runs, commit statuses or jobs of the latest pipeline of the
+    /// commit, which can also be given as a branch.
+    async fn list_checks(&self, git_ref: &str) -> Result<Vec<Check>>;
+    async fn list_pipelines(&self, branch: Option<&str>) -> Result<Vec<Pipeline>>;
+    /// Pipeline along with its jobs.
+    async fn get_pipeline(&self, id: u64) -> Result<Pipeline>;
+    /// Run the whole pipeline again, or only its failed jobs.
+    async fn retry_pipeline(&self, id: u64, failed_only: bool) -> Result<Pipeline>;
+    async fn cancel_pipeline(&self, id: u64) -> Result<Pipeline>;
+    async fn get_job(&self, id: u64) -> Result<Check>;
+    /// Log of the job so far, which might be unavailable until the job
+    /// finishes on some forges.
+    async fn get_job_log(&self, id: u64) -> Result<String>;
+
+    /// Whether the current user can push branches to the repository.
+    async fn can_push(&self) -> Result<bool>;
+    /// Fork the repository to the user, or to the organization or group if
+    /// `owner` is given, and wait until the fork is ready.
+    async fn fork_repository(&self, owner: Option<&str>) -> Result<Repo>;
 }
 
-pub fn get_remote_url() -> Result<GitUrl> {
-    let remote_url = spawn("git remote get-url origin")?;
-    Ok(GitUrl::parse(remote_url.trim())?)
+/// Operations on repositories of a forge, which work without a remote of
+/// current repository.
+#[async_trait]
+pub trait Forge {
+    /// Repository along with its stars and counts of open issues and pull
+    /// requests.
+    async fn get_repository(&self, name: &str) -> Result<Repo>;
+    async fn create_repository(&self, opt: CreateRepositoryOpt) -> Result<Repo>;
+    /// Link to the file at the ref on the web, where the line is highlighted
+    /// if given. Paths of directories end with `/`, and the empty path is the
+    /// root directory.
+    fn get_web_url(&self, name: &str, git_ref: &str, path: &str, line: Option<usize>) -> String;
 }
 
-pub async fn get_repo() -> Result<Box<dyn Repository>> {
-    let remote_url: GitUrl = get_remote_url()
-        .ok()
-        .ok_or(anyhow!("no remote is set for current repository"))?;
+/// Link to the file at the ref under the web URL of the repository, in the
+/// form of `{repo_url}/{tree|blob}/{ref}/{path}` shared by forges.
+pub fn get_web_url(repo_url: &str, git_ref: &str, path: &str, line: Option<usize>) -> String {
+    let kind = if path.is_empty() || path.ends_with('/') {
+        "tree"
+    } else {
+        "blob"
+    };
+    let url = format!(
+        "{}/{}/{}/{}",
+        repo_url,
+        kind,
+        utils::encode_path(git_ref),
+        utils::encode_path(path)
+    );
+    let url = url.trim_end_matches('/');
+    match line {
+        Some(line) => format!("{}#L{}", url, line),
+        None => url.to_string(),
+    }
+}
 
-    let remote_host = remote_url
-        .clone()
+/// The global `--remote` and `--repo` options, which are set once before
+/// running the command.
+#[derive(Debug, Default)]
+pub struct RemoteOpt {
+    pub remote: Option<String>,
+    /// `owner/name` or `host/owner/name`.
+    pub repo: Option<String>,
+}
+
+impl<'a> From<&ArgMatches<'a>> for RemoteOpt {
+    fn from(matches: &ArgMatches<'a>) -> Self {
+        Self {
+            remote: matches.value_of("remote").map(|s| s.to_string()),
+            repo: matches.value_of("repo").map(|s| s.to_string()),
+        }
+    }
+}
+
+static REMOTE_OPT: OnceLock<RemoteOpt> = OnceLock::new();
+
+pub fn set_remote_opt(opt: RemoteOpt) {
+    debug!("remote opt: {:#?}", opt);
+    REMOTE_OPT.set(opt).ok();
+}
+
+fn get_remote_opt() -> &'static RemoteOpt {
+    REMOTE_OPT.get_or_init(RemoteOpt::default)
+}
+
+/// Remote of the repository to work on, which is given by `--remote` or
+/// `yag.remote`, or else `upstream` for fork workflows and then `origin`.
+pub fn get_remote_name(git: &Git) -> Result<String> {
+    if let Some(remote) = get_remote_opt().remote.clone() {
+        return Ok(remote);
+    }
+    if let Result::Ok(remote) = git.get_config("yag.remote") {
+        return Ok(remote);
+    }
+
+    let remotes = git.get_remotes()?;
+    ["upstream", "origin"]
+        .iter()
+        .map(|remote| remote.to_string())
+        .find(|remote| remotes.contains(remote))
+        .or_else(|| remotes.into_iter().next())
+        .ok_or_else(|| anyhow!("no remote is set for current repository"))
+}
+
+/// Host of the url to look up profiles by, which keeps the port only if a
+/// profile is configured with it, like `git.example.com:3000`.
+pub fn get_host(profile: &Profile, url: &GitUrl) -> Result<String> {
+    let host = url
         .host
-        .ok_or(Error::msg("cannot resolve host of remote url"))?;
+        .clone()
+        .ok_or_else(|| anyhow!("cannot resolve host of remote url"))?;
+    Ok(match url.port {
+        Some(port) => {
+            let host_with_port = format!("{}:{}", host, port);
+            if profile.get_provider_by_host(&host_with_port).is_some() {
+                host_with_port
+            } else {
+                host
+            }
+        }
+        None => host,
+    })
+}
 
-    let repo: Box<dyn Repository> = match remote_host.as_ref() {
-        "github.com" => Box::new(GitHubRepository::init(&remote_url).await?),
-        "gitlab.com" => bail!("WIP: unsupported repo type"),
-        _ => Box::new(GitLabRepository::init(&remote_host, &remote_url).await?),
+/// Host of the remote of current repository, or GitHub outside of
+/// repositories.
+fn get_default_host(profile: &Profile) -> String {
+    Git::current()
+        .and_then(|git| {
+            let remote = get_remote_name(&git)?;
+            get_host(profile, &GitUrl::parse(&git.get_remote_url(&remote)?)?)
+        })
+        .unwrap_or("github.com".to_string())
+}
+
+/// Whether the host, optionally with a port, is `localhost` or an IP address,
+/// which need no profile to be told apart from groups or owners.
+fn is_local_host(host: &str) -> bool {
+    let host = match host.rsplit_once(':') {
+        Some((host, port)) if port.parse::<u16>().is_ok() => host,
+        _ => host,
+    };
+    host == "localhost" || host.parse::<IpAddr>().is_ok()
+}
+
+/// Split `[host/]path` into the host and the path, where the host defaults to
+/// the one of current repository. The first segment is only taken as the host
+/// if a profile is configured for it, so that groups like `foo.bar` work.
+pub fn split_host(profile: &Profile, repo: &str) -> (String, String) {
+    let repo = repo.trim_matches('/');
+    match repo.split_once('/') {
+        Some((host, path))
+            if profile.get_provider_by_host(host).is_some() || is_local_host(host) =>
+        {
+            (host.to_string(), path.to_string())
+        }
+        _ => (get_default_host(profile), repo.to_string()),
+    }
+}
+
+/// Parse `owner/name` or `host/owner/name`.
+pub fn parse_repo(profile: &Profile, repo: &str) -> Result<GitUrl> {
+    let (host, path) = split_host(profile, repo);
+    if !path.contains('/') {
+        bail!(
+            "invalid repository: {}, which should be like owner/name",
+            repo
+        )
+    }
+    Ok(GitUrl::parse(&format!("https://{}/{}", host, path))?)
+}
+
+pub fn get_remote_url(profile: &Profile) -> Result<GitUrl> {
+    if let Some(repo) = get_remote_opt().repo.as_ref() {
+        return parse_repo(profile, repo);
+    }
+
+    let git = Git::current()?;
+    let remote = get_remote_name(&git)?;
+    Ok(GitUrl::parse(&git.get_remote_url(&remote)?)?)
+}
+
+pub async fn get_repo() -> Result<Box<dyn Repository>> {
+    let profile = load_profile().await?;
+    let remote_url: GitUrl = get_remote_url(&profile)?;
+    let remote_host = get_host(&profile, &remote_url)?;
+
+    let repo: Box<dyn Repository> = match profile.get_provider_by_host(&remote_host) {
+        Some(Provider::GitHub) => {
+            Box::new(GitHubRepository::init(&remote_host, &remote_url).await?)
+        }
+        Some(Provider::GitLab) => {
+            Box::new(GitLabRepository::init(&remote_host, &remote_url).await?)
+        }
+        Some(Provider::Gitea) => Box::new(GiteaRepository::init(&remote_host, &remote_url).await?),
+        Some(Provider::Bitbucket) => {
+            Box::new(BitbucketRepository::init(&remote_host, &remote_url).await?)
+        }
+        None => bail!(
+            "no profile for remote host {}: Try `yag profile add` first",
+            remote_host
+        ),
     };
 
     Ok(repo)
 }
+
+pub async fn get_forge(host: &str) -> Result<Box<dyn Forge>> {
+    let profile = load_profile().await?;
+    let forge: Box<dyn Forge> = match profile.get_provider_by_host(host) {
+        Some(Provider::GitHub) => Box::new(GitHubForge::init(&profile, host)?),
+        Some(Provider::GitLab) => Box::new(GitLabForge::init(&profile, host)?),
+        Some(provider) => bail!("managing repositories of {:?} is not supported", provider),
+        None => bail!("no profile for host {}: Try `yag profile add` first", host),
+    };
+
+    Ok(forge)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gitea::profile::GiteaConfig;
+    use futures::TryStreamExt;
+
+    #[tokio::test]
+    async fn test_paginate() {
+        let fetch = |page: usize| async move {
+            let next_page = if page < 3 { Some(page + 1) } else { None };
+            Ok(
+                PaginationResult::new(vec![page * 10, page * 10 + 1], Some(6))
+                    .with_page(page, next_page),
+            )
+        };
+
+        let all: Vec<usize> = paginate(1, None, fetch).try_collect().await.unwrap();
+        assert_eq!(all, vec![10, 11, 20, 21, 30, 31]);
+
+        let limited: Vec<usize> = paginate(2, Some(3), fetch).try_collect().await.unwrap();
+        assert_eq!(limited, vec![20, 21, 30]);
+    }
+
+    #[test]
+    fn test_validate_positive_number() {
+        assert!(validate_positive_number("10".to_string()).is_ok());
+        assert!(validate_positive_number("0".to_string()).is_err());
+        assert!(validate_positive_number("abc".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_get_id() {
+        let app = clap::App::new("test").arg(clap::Arg::with_name("id"));
+        let matches = app.clone().get_matches_from(vec!["test", "12"]);
+        assert_eq!(get_id::<usize>(&matches, "id").unwrap(), 12);
+        let matches = app.get_matches_from(vec!["test", "abc"]);
+        assert_eq!(
+            get_id::<usize>(&matches, "id").unwrap_err().to_string(),
+            "invalid id: abc"
+        );
+    }
+
+    #[test]
+    fn test_get_web_url() {
+        let repo_url = "https://gitlab.com/group/repo/-";
+        assert_eq!(
+            get_web_url(repo_url, "main", "", None),
+            "https://gitlab.com/group/repo/-/tree/main"
+        );
+        assert_eq!(
+            get_web_url(repo_url, "feature/x", "docs/", None),
+            "https://gitlab.com/group/repo/-/tree/feature/x/docs"
+        );
+        assert_eq!(
+            get_web_url(repo_url, "v1.0#rc", "docs/a b?.md", Some(3)),
+            "https://gitlab.com/group/repo/-/blob/v1.0%23rc/docs/a%20b%3F.md#L3"
+        );
+    }
+
+    #[test]
+    fn test_parse_head() {
+        assert_eq!(parse_head("someone:fix"), (Some("someone"), "fix"));
+        assert_eq!(parse_head("fix"), (None, "fix"));
+    }
+
+    #[test]
+    fn test_parse_repo() {
+        let mut profile = Profile::new();
+        profile.gitea = Some(vec![GiteaConfig {
+            host: "git.example.com:3000".to_string(),
+            token: "token".to_string(),
+        }]);
+
+        let url = parse_repo(&profile, "gitlab.com/group/project").unwrap();
+        assert_eq!(url.host.as_deref(), Some("gitlab.com"));
+        assert_eq!(url.fullname, "group/project");
+
+        let url = parse_repo(&profile, "localhost:3000/owner/repo").unwrap();
+        assert_eq!(url.host.as_deref(), Some("localhost"));
+        assert_eq!(url.fullname, "owner/repo");
+
+        let url = parse_repo(&profile, "git.example.com:3000/owner/repo").unwrap();
+        assert_eq!(get_host(&profile, &url).unwrap(), "git.example.com:3000");
+        assert_eq!(url.fullname, "owner/repo");
+
+        // dotted groups are not hosts unless configured
+        let (_, path) = split_host(&profile, "foo.bar/repo");
+        assert_eq!(path, "foo.bar/repo");
+        let (host, path) = split_host(&profile, "127.0.0.1/owner/repo");
+        assert_eq!((host.as_str(), path.as_str()), ("127.0.0.1", "owner/repo"));
+
+        assert!(parse_repo(&profile, "repo").is_err());
+        assert!(parse_repo(&profile, "github.com/repo").is_err());
+    }
+
+    #[test]
+    fn test_get_host() {
+        let mut profile = Profile::new();
+        profile.gitea = Some(vec![GiteaConfig {
+            host: "git.example.com:3000".to_string(),
+            token: "token".to_string(),
+        }]);
+
+        let url = GitUrl::parse("https://git.example.com:3000/owner/repo.git").unwrap();
+        assert_eq!(get_host(&profile, &url).unwrap(), "git.example.com:3000");
+        // the port of ssh is not part of profiles
+        let url = GitUrl::parse("ssh://git@github.com:22/owner/repo.git").unwrap();
+        assert_eq!(get_host(&profile, &url).unwrap(), "github.com");
+    }
+
+    #[test]
+    fn test_get_commit_message() {
+        let mut opt = MergePullRequestOpt::default();
+        assert_eq!(opt.get_commit_message(), None);
+
+        opt.title = Some("title".to_string());
+        assert_eq!(opt.get_commit_message().unwrap(), "title");
+
+        opt.message = Some("message".to_string());
+        assert_eq!(opt.get_commit_message().unwrap(), "title\n\nmessage");
+    }
+
+    #[test]
+    fn test_needs_body() {
+        let mut opt = ReviewOpt {
+            event: ReviewEvent::Approve,
+            ..Default::default()
+        };
+        assert!(!opt.needs_body());
+
+        opt.event = ReviewEvent::RequestChanges;
+        opt.comments = vec![InlineComment {
+            path: "src/main.rs".to_string(),
+            line: 1,
+            body: "typo".to_string(),
+        }];
+        assert!(opt.needs_body());
+        opt.body = Some(String::new());
+        assert!(opt.needs_body());
+        opt.body = Some("please fix".to_string());
+        assert!(!opt.needs_body());
+    }
+
+    #[test]
+    fn test_parse_inline_comments() -> Result<()> {
+        let comments =
+            ReviewOpt::parse_inline_comments(&["src/main.rs:12", "typo", "C:\\a.rs:3", "ok"])?;
+        assert_eq!(
+            comments[0],
+            InlineComment {
+                path: "src/main.rs".to_string(),
+                line: 12,
+                body: "typo".to_string(),
+            }
+        );
+        assert_eq!(comments[1].path, "C:\\a.rs");
+        assert!(ReviewOpt::parse_inline_comments(&["src/main.rs", "typo"]).is_err());
+        assert!(ReviewOpt::parse_inline_comments(&[":1", "typo"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_draft_prefix() {
+        let prefixes = ["Draft:", "[Draft]", "WIP:"];
+        assert_eq!(set_draft_prefix("fix", true, &prefixes), "Draft: fix");
+        assert_eq!(set_draft_prefix("wip: fix", true, &prefixes), "Draft: fix");
+        assert_eq!(
+            set_draft_prefix("[draft] WIP: fix", false, &prefixes),
+            "fix"
+        );
+        assert_eq!(set_draft_prefix("fix: typo", false, &prefixes), "fix: typo");
+    }
+
+    #[test]
+    fn test_get_list() {
+        let matches = clap::App::new("test")
+            .arg(
+                clap::Arg::with_name("label")
+                    .long("label")
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1),
+            )
+            .get_matches_from(vec!["test", "--label", "a,b", "--label", "c"]);
+        assert_eq!(get_list(&matches, "label"), vec!["a", "b", "c"]);
+        assert!(get_list(&matches, "assignee").is_empty());
+    }
+}
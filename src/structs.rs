@@ -1,19 +1,40 @@
 use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::{bail, Error};
+use serde_derive::*;
+
+use crate::markdown;
+use crate::output::Output;
+use crate::utils;
 
 use colored::*;
 
+#[derive(Serialize)]
 pub struct PaginationResult<T> {
-    pub total: u64,
+    /// Total count of all pages, which is `None` when it is unknown.
+    pub total: Option<u64>,
+    /// Current page, starting from 1.
+    pub page: usize,
+    pub next_page: Option<usize>,
     pub result: Vec<T>,
 }
 
 impl<T> PaginationResult<T> {
-    pub fn new(result: Vec<T>, total: u64) -> Self {
+    pub fn new(result: Vec<T>, total: Option<u64>) -> Self {
         PaginationResult {
-            total: total,
-            result: result,
+            total,
+            page: 1,
+            next_page: None,
+            result,
         }
     }
+
+    pub fn with_page(mut self, page: usize, next_page: Option<usize>) -> Self {
+        self.page = page.max(1);
+        self.next_page = next_page;
+        self
+    }
 }
 
 impl<T: Display> Display for PaginationResult<T> {
@@ -21,31 +42,90 @@ impl<T: Display> Display for PaginationResult<T> {
         for item in self.result.iter() {
             writeln!(f, "{}", item)?;
         }
-        writeln!(f, "  {} {}", "total:".purple(), self.total)?;
+        match self.total {
+            Some(total) => writeln!(f, "  {} {}", "total:".purple(), total)?,
+            None => writeln!(f, "  {} unknown", "total:".purple())?,
+        }
+        if let Some(next_page) = self.next_page {
+            writeln!(f, "  {} {}", "next page:".purple(), next_page)?;
+        }
         Ok(())
     }
 }
 
-impl<T> PaginationResult<T> {
-    #[inline]
-    pub fn map<R, F>(&self, f: F) -> PaginationResult<R>
-    where
-        F: FnMut(&T) -> R,
-    {
-        PaginationResult {
-            total: self.total,
-            result: self.result.iter().map(f).collect::<Vec<R>>(),
+impl<T: Output> Output for PaginationResult<T> {
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.result.iter().flat_map(|item| item.rows()).collect()
+    }
+}
+
+#[derive(Serialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PullRequestState {
+    #[default]
+    Open,
+    Closed,
+    Merged,
+}
+
+impl FromStr for PullRequestState {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" | "opened" => Ok(PullRequestState::Open),
+            "closed" => Ok(PullRequestState::Closed),
+            "merged" => Ok(PullRequestState::Merged),
+            _ => bail!("unknown pull request state: {}", s),
         }
     }
 }
 
+impl Display for PullRequestState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = match self {
+            PullRequestState::Open => "open",
+            PullRequestState::Closed => "closed",
+            PullRequestState::Merged => "merged",
+        };
+        write!(f, "{}", state)
+    }
+}
+
+impl PullRequestState {
+    fn colored(&self) -> ColoredString {
+        let state = self.to_string();
+        match self {
+            PullRequestState::Open => state.green(),
+            PullRequestState::Closed => state.red(),
+            PullRequestState::Merged => state.purple(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Default)]
 pub struct PullRequest {
     pub id: u64,
     pub title: String,
+    pub state: PullRequestState,
+    pub draft: bool,
     pub base: Option<String>,
     pub head: Option<String>,
+    /// Commit at the head of the pull request.
+    pub sha: Option<String>,
     pub author: String,
+    pub body: Option<String>,
+    pub labels: Vec<String>,
+    pub assignees: Vec<String>,
+    pub reviewers: Vec<String>,
+    pub mergeable: Option<bool>,
+    pub commits: Option<u64>,
+    pub additions: Option<u64>,
+    pub deletions: Option<u64>,
+    pub changed_files: Option<u64>,
+    pub created_at: String,
     pub updated_at: String,
+    pub merged_at: Option<String>,
     pub url: String,
 }
 
@@ -60,10 +140,666 @@ impl Display for PullRequest {
             .map(|head| format!("[{}]", head).cyan())
             .unwrap_or_default();
 
-        write!(f, "{:>6} {} {} {}", id, title, author, head)?;
+        let state = if self.draft && self.state == PullRequestState::Open {
+            format!("[{}]", "draft".dimmed())
+        } else {
+            format!("[{}]", self.state.colored())
+        };
+
+        write!(f, "{:>6} {} {} {} {}", id, state, title, author, head)?;
+        if f.alternate() {
+            write!(f, "\n    {} {}", "link:".bold(), self.url)?;
+            self.fmt_detail(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl PullRequest {
+    fn fmt_detail(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let (Some(base), Some(head)) = (&self.base, &self.head) {
+            write!(f, "\n    {} {} <- {}", "branch:".bold(), base, head)?;
+        }
+        if !self.created_at.is_empty() {
+            write!(f, "\n    {} {}", "created:".bold(), self.created_at)?;
+        }
+        write!(f, "\n    {} {}", "updated:".bold(), self.updated_at)?;
+        if let Some(merged_at) = &self.merged_at {
+            write!(f, "\n    {} {}", "merged:".bold(), merged_at)?;
+        }
+
+        let lists = [
+            ("labels:", &self.labels),
+            ("assignees:", &self.assignees),
+            ("reviewers:", &self.reviewers),
+        ];
+        for (name, list) in lists.iter() {
+            if !list.is_empty() {
+                write!(f, "\n    {} {}", name.bold(), list.join(", "))?;
+            }
+        }
+
+        if self.state == PullRequestState::Open {
+            let mergeable = match self.mergeable {
+                Some(true) => "yes".green(),
+                Some(false) => "no".red(),
+                None => "unknown".normal(),
+            };
+            write!(f, "\n    {} {}", "mergeable:".bold(), mergeable)?;
+        }
+
+        let mut stats = vec![];
+        if let Some(commits) = self.commits {
+            stats.push(format!("{} commits", commits));
+        }
+        if let Some(changed_files) = self.changed_files {
+            stats.push(format!("{} files", changed_files));
+        }
+        if let (Some(additions), Some(deletions)) = (self.additions, self.deletions) {
+            stats.push(format!(
+                "{} {}",
+                format!("+{}", additions).green(),
+                format!("-{}", deletions).red()
+            ));
+        }
+        if !stats.is_empty() {
+            write!(f, "\n    {} {}", "changes:".bold(), stats.join(", "))?;
+        }
+
+        if let Some(body) = self.body.as_ref().filter(|body| !body.trim().is_empty()) {
+            writeln!(f)?;
+            for line in markdown::render(body).lines() {
+                write!(f, "\n    {}", line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Output for PullRequest {
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.id.to_string(),
+            self.state.to_string(),
+            self.title.to_owned(),
+            self.author.to_owned(),
+            self.base.to_owned().unwrap_or_default(),
+            self.head.to_owned().unwrap_or_default(),
+            self.updated_at.to_owned(),
+            self.url.to_owned(),
+        ]]
+    }
+}
+
+#[derive(Serialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueState {
+    #[default]
+    Open,
+    Closed,
+}
+
+impl FromStr for IssueState {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" | "opened" => Ok(IssueState::Open),
+            "closed" => Ok(IssueState::Closed),
+            _ => bail!("unknown issue state: {}", s),
+        }
+    }
+}
+
+impl Display for IssueState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = match self {
+            IssueState::Open => "open",
+            IssueState::Closed => "closed",
+        };
+        write!(f, "{}", state)
+    }
+}
+
+impl IssueState {
+    fn colored(&self) -> ColoredString {
+        let state = self.to_string();
+        match self {
+            IssueState::Open => state.green(),
+            IssueState::Closed => state.red(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct Issue {
+    pub id: u64,
+    pub title: String,
+    pub state: IssueState,
+    pub author: String,
+    pub body: Option<String>,
+    pub labels: Vec<String>,
+    pub assignees: Vec<String>,
+    pub milestone: Option<String>,
+    pub comments: Option<u64>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub closed_at: Option<String>,
+    pub url: String,
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let id = format!("#{}", self.id).green().bold();
+        let state = format!("[{}]", self.state.colored());
+        let title = self.title.white();
+        let author = format!("<{}>", self.author).blue().bold();
+
+        write!(f, "{:>6} {} {} {}", id, state, title, author)?;
+        if f.alternate() {
+            write!(f, "\n    {} {}", "link:".bold(), self.url)?;
+            self.fmt_detail(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Issue {
+    fn fmt_detail(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.created_at.is_empty() {
+            write!(f, "\n    {} {}", "created:".bold(), self.created_at)?;
+        }
+        write!(f, "\n    {} {}", "updated:".bold(), self.updated_at)?;
+        if let Some(closed_at) = &self.closed_at {
+            write!(f, "\n    {} {}", "closed:".bold(), closed_at)?;
+        }
+
+        let lists = [("labels:", &self.labels), ("assignees:", &self.assignees)];
+        for (name, list) in lists.iter() {
+            if !list.is_empty() {
+                write!(f, "\n    {} {}", name.bold(), list.join(", "))?;
+            }
+        }
+        if let Some(milestone) = &self.milestone {
+            write!(f, "\n    {} {}", "milestone:".bold(), milestone)?;
+        }
+        if let Some(comments) = self.comments {
+            write!(f, "\n    {} {}", "comments:".bold(), comments)?;
+        }
+
+        if let Some(body) = self.body.as_ref().filter(|body| !body.trim().is_empty()) {
+            writeln!(f)?;
+            for line in markdown::render(body).lines() {
+                write!(f, "\n    {}", line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Output for Issue {
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.id.to_string(),
+            self.state.to_string(),
+            self.title.to_owned(),
+            self.author.to_owned(),
+            self.labels.join(","),
+            self.updated_at.to_owned(),
+            self.url.to_owned(),
+        ]]
+    }
+}
+
+/// Plain list of items which are not paginated.
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct List<T>(pub Vec<T>);
+
+impl<T: Display> Display for List<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for item in self.0.iter() {
+            if f.alternate() {
+                writeln!(f, "{:#}", item)?;
+            } else {
+                writeln!(f, "{}", item)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: Output> Output for List<T> {
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0.iter().flat_map(|item| item.rows()).collect()
+    }
+}
+
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct Comment {
+    pub id: u64,
+    pub author: String,
+    pub body: String,
+    pub created_at: String,
+    pub url: Option<String>,
+}
+
+impl Display for Comment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            self.author.blue().bold(),
+            self.created_at.dimmed()
+        )?;
+        for line in markdown::render(self.body.trim()).lines() {
+            write!(f, "\n  {}", line)?;
+        }
+        Ok(())
+    }
+}
+
+impl Output for Comment {
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.id.to_string(),
+            self.author.to_owned(),
+            self.created_at.to_owned(),
+            self.body.to_owned(),
+        ]]
+    }
+}
+
+/// A thread of comments. Comments on the conversation are discussions with a
+/// single comment, while comments on the diff carry the file and the line.
+#[derive(Serialize, Debug, Default)]
+pub struct Discussion {
+    pub id: String,
+    pub path: Option<String>,
+    pub line: Option<u64>,
+    /// `None` if the discussion cannot be resolved.
+    pub resolved: Option<bool>,
+    pub comments: Vec<Comment>,
+}
+
+impl Display for Discussion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut header = vec![];
+        if let Some(path) = &self.path {
+            let location = match self.line {
+                Some(line) => format!("{}:{}", path, line),
+                None => path.to_owned(),
+            };
+            header.push(location.cyan().to_string());
+        }
+        match self.resolved {
+            Some(true) => header.push(format!("[{}]", "resolved".green())),
+            Some(false) => header.push(format!("[{}]", "unresolved".yellow())),
+            None => (),
+        }
+        if !header.is_empty() {
+            writeln!(f, "{}", header.join(" "))?;
+        }
+
+        for (i, comment) in self.comments.iter().enumerate() {
+            let indent = if i == 0 { "" } else { "  ↳ " };
+            for (j, line) in comment.to_string().lines().enumerate() {
+                if j == 0 {
+                    writeln!(f, "{}{}", indent, line)?;
+                } else {
+                    writeln!(f, "{}{}", " ".repeat(indent.chars().count()), line)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Output for Discussion {
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.comments
+            .iter()
+            .map(|comment| {
+                vec![
+                    self.id.to_owned(),
+                    comment.id.to_string(),
+                    comment.author.to_owned(),
+                    self.path.to_owned().unwrap_or_default(),
+                    self.line.map(|line| line.to_string()).unwrap_or_default(),
+                    self.resolved
+                        .map(|resolved| resolved.to_string())
+                        .unwrap_or_default(),
+                    comment.created_at.to_owned(),
+                    comment.body.to_owned(),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileStatus {
+    Added,
+    #[default]
+    Modified,
+    Removed,
+    Renamed,
+}
+
+impl Display for FileStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            FileStatus::Added => "added",
+            FileStatus::Modified => "modified",
+            FileStatus::Removed => "removed",
+            FileStatus::Renamed => "renamed",
+        };
+        write!(f, "{}", status)
+    }
+}
+
+/// File changed by a pull request. Numbers of changed lines are unknown for
+/// forges which do not count them.
+#[derive(Serialize, Debug, Default)]
+pub struct ChangedFile {
+    pub path: String,
+    pub old_path: Option<String>,
+    pub status: FileStatus,
+    pub additions: Option<u64>,
+    pub deletions: Option<u64>,
+}
+
+impl Display for ChangedFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = format!("{:>8}", self.status.to_string());
+        let status = match self.status {
+            FileStatus::Added => status.green(),
+            FileStatus::Modified => status.yellow(),
+            FileStatus::Removed => status.red(),
+            FileStatus::Renamed => status.cyan(),
+        };
+        write!(f, "{} ", status)?;
+        match &self.old_path {
+            Some(old_path) if *old_path != self.path => write!(f, "{} -> {}", old_path, self.path)?,
+            _ => write!(f, "{}", self.path)?,
+        }
+        if let (Some(additions), Some(deletions)) = (self.additions, self.deletions) {
+            write!(
+                f,
+                " {} {}",
+                format!("+{}", additions).green(),
+                format!("-{}", deletions).red()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Output for ChangedFile {
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.status.to_string(),
+            self.path.to_owned(),
+            self.old_path.to_owned().unwrap_or_default(),
+            self.additions.map(|n| n.to_string()).unwrap_or_default(),
+            self.deletions.map(|n| n.to_string()).unwrap_or_default(),
+        ]]
+    }
+}
+
+#[derive(Serialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckState {
+    #[default]
+    Pending,
+    Running,
+    Success,
+    Failure,
+    Cancelled,
+    Skipped,
+}
+
+impl Display for CheckState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = match self {
+            CheckState::Pending => "pending",
+            CheckState::Running => "running",
+            CheckState::Success => "success",
+            CheckState::Failure => "failure",
+            CheckState::Cancelled => "cancelled",
+            CheckState::Skipped => "skipped",
+        };
+        write!(f, "{}", state)
+    }
+}
+
+impl CheckState {
+    fn paint(&self, text: &str) -> ColoredString {
+        match self {
+            CheckState::Pending | CheckState::Running => text.yellow(),
+            CheckState::Success => text.green(),
+            CheckState::Failure => text.red(),
+            CheckState::Cancelled | CheckState::Skipped => text.dimmed(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, CheckState::Pending | CheckState::Running)
+    }
+
+    pub fn is_failed(&self) -> bool {
+        matches!(self, CheckState::Failure | CheckState::Cancelled)
+    }
+
+    /// Overall state of several checks, where any failure fails all of them.
+    pub fn combine<'a, I: IntoIterator<Item = &'a CheckState>>(states: I) -> CheckState {
+        let states = states.into_iter().collect::<Vec<&CheckState>>();
+        if states.iter().any(|state| state.is_failed()) {
+            CheckState::Failure
+        } else if states.iter().any(|state| **state == CheckState::Running) {
+            CheckState::Running
+        } else if states.iter().any(|state| **state == CheckState::Pending) {
+            CheckState::Pending
+        } else if states.iter().all(|state| **state == CheckState::Skipped) {
+            CheckState::Skipped
+        } else {
+            CheckState::Success
+        }
+    }
+}
+
+/// Check run, commit status or CI job.
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct Check {
+    pub id: String,
+    pub name: String,
+    pub state: CheckState,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    /// In seconds.
+    pub duration: Option<u64>,
+    pub url: Option<String>,
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.paint(&format!("{:>9}", self.state.to_string()));
+        write!(f, "{} {}", state, self.name)?;
+        if let Some(duration) = self.duration {
+            write!(f, " {}", utils::format_duration(duration).dimmed())?;
+        }
+        if let Some(url) = &self.url {
+            write!(f, " {}", url.dimmed())?;
+        }
+        Ok(())
+    }
+}
+
+impl Output for Check {
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.id.to_owned(),
+            self.name.to_owned(),
+            self.state.to_string(),
+            self.duration.map(|n| n.to_string()).unwrap_or_default(),
+            self.url.to_owned().unwrap_or_default(),
+        ]]
+    }
+}
+
+/// Pipeline of GitLab, or workflow run of GitHub Actions.
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct Pipeline {
+    pub id: u64,
+    pub name: Option<String>,
+    pub branch: Option<String>,
+    pub sha: String,
+    pub state: CheckState,
+    pub created_at: String,
+    /// In seconds.
+    pub duration: Option<u64>,
+    pub url: String,
+    pub jobs: Vec<Check>,
+}
+
+impl Display for Pipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let id = format!("#{}", self.id).green().bold();
+        let state = format!("[{}]", self.state.paint(&self.state.to_string()));
+        let sha = self.sha.get(..8).unwrap_or(&self.sha).yellow();
+        write!(f, "{} {} {}", id, state, sha)?;
+        if let Some(name) = &self.name {
+            write!(f, " {}", name)?;
+        }
+        if let Some(branch) = &self.branch {
+            write!(f, " {}", format!("[{}]", branch).cyan())?;
+        }
+        if let Some(duration) = self.duration {
+            write!(f, " {}", utils::format_duration(duration).dimmed())?;
+        }
+        if f.alternate() {
+            write!(f, "\n    {} {}", "link:".bold(), self.url)?;
+            write!(f, "\n    {} {}", "created:".bold(), self.created_at)?;
+            for job in self.jobs.iter() {
+                write!(f, "\n    {}", job)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Output for Pipeline {
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.id.to_string(),
+            self.state.to_string(),
+            self.name.to_owned().unwrap_or_default(),
+            self.branch.to_owned().unwrap_or_default(),
+            self.sha.to_owned(),
+            self.duration.map(|n| n.to_string()).unwrap_or_default(),
+            self.url.to_owned(),
+        ]]
+    }
+}
+
+#[derive(Serialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    #[default]
+    Public,
+    Private,
+    /// Visible to all signed-in users, which is only for GitLab and GitHub
+    /// organizations of enterprises.
+    Internal,
+}
+
+impl Visibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Visibility::Public => "public",
+            Visibility::Private => "private",
+            Visibility::Internal => "internal",
+        }
+    }
+}
+
+impl FromStr for Visibility {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "public" => Ok(Visibility::Public),
+            "private" => Ok(Visibility::Private),
+            "internal" => Ok(Visibility::Internal),
+            _ => bail!("unknown visibility: {}", s),
+        }
+    }
+}
+
+/// Repository of GitHub, Gitea and Bitbucket, or project of GitLab.
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct Repo {
+    /// Full name like `owner/name`.
+    pub name: String,
+    pub description: Option<String>,
+    pub visibility: Visibility,
+    /// Full name of the repository which it is forked from.
+    pub parent: Option<String>,
+    pub default_branch: Option<String>,
+    pub url: String,
+    pub clone_url: String,
+    pub ssh_url: Option<String>,
+    /// Counts which are only present when the repository is viewed.
+    pub stars: Option<u64>,
+    pub open_issues: Option<u64>,
+    pub open_pull_requests: Option<u64>,
+}
+
+impl Display for Repo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name.green().bold())?;
+        if self.visibility != Visibility::Public {
+            write!(f, " {}", format!("[{}]", self.visibility.as_str()).yellow())?;
+        }
+        if let Some(parent) = &self.parent {
+            write!(f, " {}", format!("forked from {}", parent).dimmed())?;
+        }
         if f.alternate() {
+            if let Some(description) = &self.description {
+                write!(f, "\n    {}", description)?;
+            }
             write!(f, "\n    {} {}", "link:".bold(), self.url)?;
+            write!(f, "\n    {} {}", "clone:".bold(), self.clone_url)?;
+            if let Some(ssh_url) = &self.ssh_url {
+                write!(f, "\n    {} {}", "ssh:".bold(), ssh_url)?;
+            }
+            if let Some(branch) = &self.default_branch {
+                write!(f, "\n    {} {}", "default branch:".bold(), branch)?;
+            }
+            if let Some(stars) = self.stars {
+                write!(f, "\n    {} {}", "stars:".bold(), stars)?;
+            }
+            if let Some(count) = self.open_issues {
+                write!(f, "\n    {} {}", "open issues:".bold(), count)?;
+            }
+            if let Some(count) = self.open_pull_requests {
+                write!(f, "\n    {} {}", "open pull requests:".bold(), count)?;
+            }
         }
         Ok(())
     }
 }
+
+impl Output for Repo {
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.name.to_owned(),
+            self.visibility.as_str().to_string(),
+            self.description.to_owned().unwrap_or_default(),
+            self.parent.to_owned().unwrap_or_default(),
+            self.url.to_owned(),
+            self.clone_url.to_owned(),
+        ]]
+    }
+}
@@ -1,56 +1,188 @@
+use crate::git::{self, Git};
 use anyhow::{bail, Result};
+use chrono::{DateTime, SecondsFormat};
 use log::debug;
 use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
-use std::{ffi::OsStr, io::{Write, stdin, stdout}, process::Command};
+use reqwest::Url;
+use std::{
+    convert::TryFrom,
+    env, fs,
+    io::{stdin, stdout, IsTerminal, Read, Write},
+    process::{Command, Stdio},
+};
 
 const FRAGMENT: &AsciiSet = &CONTROLS.add(b'/');
 
+/// Characters to encode in segments of URL paths.
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Characters to encode in a single segment of URL paths, including `/`.
+const SEGMENT: &AsciiSet = &PATH_SEGMENT.add(b'/');
+
 pub fn url_encode(component: &str) -> String {
     utf8_percent_encode(component, FRAGMENT).to_string()
 }
 
-pub fn spawn(command: &str) -> Result<String> {
-    let mut parts = command.split(' ');
-    let program = parts.next().unwrap();
-    let args: Vec<&OsStr> = parts.map(OsStr::new).collect();
-    let mut cmd = Command::new(program);
+/// Encode a git ref or name as a single segment of URL paths.
+pub fn encode_segment(segment: &str) -> String {
+    utf8_percent_encode(segment, SEGMENT).to_string()
+}
+
+/// Encode each segment of the path, keeping `/` between them.
+pub fn encode_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| utf8_percent_encode(segment, PATH_SEGMENT).to_string())
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+pub fn get_editor() -> String {
+    env::var("GIT_EDITOR")
+        .ok()
+        .or_else(|| git::get_config("core.editor").ok())
+        .or_else(|| env::var("VISUAL").ok())
+        .or_else(|| env::var("EDITOR").ok())
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or("vi".to_string())
+}
+
+/// Open the editor with the initial content and return the edited content.
+/// The file is placed in the git directory like `COMMIT_EDITMSG`.
+pub fn edit_text(filename: &str, initial: &str) -> Result<String> {
+    let path = Git::current()?.get_git_dir()?.join(filename);
+    fs::write(&path, initial)?;
 
-    cmd.args(args);
+    let editor = get_editor();
+    debug!("editor: {}", editor);
+    // the editor may contain arguments, so let the shell handle it as git does
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$@\"", editor))
+        .arg(&editor)
+        .arg(&path)
+        .status()?;
+    if !status.success() {
+        bail!("editor exited with {}", status)
+    }
+
+    Ok(fs::read_to_string(&path)?)
+}
 
-    debug!("executing: {}", command);
-    let output = cmd.output()?;
+/// Remove lines starting with the comment char and surrounding blank lines.
+pub fn strip_comments(text: &str, comment_char: char) -> String {
+    text.lines()
+        .filter(|line| !line.starts_with(comment_char))
+        .collect::<Vec<&str>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
 
-    if !output.status.success() {
-        debug!("stderr: {}", String::from_utf8(output.stderr)?);
-        bail!(format!("Failed to execute {}", command))
+/// Read whole content from the file, or from stdin if the path is `-`.
+pub fn read_file_or_stdin(path: &str) -> Result<String> {
+    if path == "-" {
+        let mut content = String::new();
+        stdin().read_to_string(&mut content)?;
+        Ok(content)
+    } else {
+        Ok(fs::read_to_string(path)?)
     }
+}
+
+/// Format milliseconds since the unix epoch in RFC 3339 like other forges do.
+pub fn format_timestamp_millis(millis: u64) -> String {
+    i64::try_from(millis)
+        .ok()
+        .and_then(DateTime::from_timestamp_millis)
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Secs, true)
+}
 
-    let result = String::from_utf8(output.stdout)?;
-    debug!("result: {}", result);
-    Ok(result)
+/// Get the next page from a `Link` header like
+/// `<https://host/items?page=2>; rel="next", <https://host/items?page=5>; rel="last"`.
+pub fn get_next_page(link: &str) -> Option<usize> {
+    let end = link.find("rel=\"next\"")?;
+    let link = &link[..end];
+    // the header might be malformed like `>; rel="next"`
+    let url = link.get(link.rfind('<')? + 1..link.rfind('>')?)?;
+    Url::parse(url)
+        .ok()?
+        .query_pairs()
+        .find(|(key, _)| key == "page")
+        .and_then(|(_, page)| page.parse::<usize>().ok())
 }
 
-pub fn get_current_branch() -> Result<String> {
-    spawn("git rev-parse --abbrev-ref HEAD").map(|x| x.trim().to_string())
+/// Parse an RFC 3339 timestamp into seconds since the unix epoch.
+pub fn parse_timestamp(timestamp: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|datetime| datetime.timestamp())
 }
 
-pub fn get_rev(rev: &str) -> Result<String> {
-    spawn(&format!("git rev-list {}", rev)).map(|x| x.trim().to_string())
+/// Seconds between two RFC 3339 timestamps.
+pub fn get_duration(start: Option<&str>, end: Option<&str>) -> Option<u64> {
+    let start = parse_timestamp(start?)?;
+    let end = parse_timestamp(end?)?;
+    u64::try_from(end - start).ok()
 }
 
-pub fn get_latest_commit_message() -> Result<String> {
-    spawn("git rev-list --format=%B --max-count=1 HEAD").map(|x| {
-        x.trim()
-            .split("\n")
-            .skip(1)
-            .collect::<Vec<&str>>()
-            .join("\n")
-            .to_string()
-    })
+pub fn format_duration(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+    }
 }
 
-pub fn get_git_config(key: &str) -> Result<String> {
-    spawn(&format!("git config --get {}", key)).map(|x| x.trim().to_string())
+pub fn get_pager() -> String {
+    env::var("GIT_PAGER")
+        .ok()
+        .or_else(|| git::get_config("core.pager").ok())
+        .or_else(|| env::var("PAGER").ok())
+        .filter(|pager| !pager.trim().is_empty())
+        .unwrap_or("less".to_string())
+}
+
+pub fn is_stdout_tty() -> bool {
+    stdout().is_terminal()
+}
+
+pub fn is_stdin_tty() -> bool {
+    stdin().is_terminal()
+}
+
+/// Show the text with the pager if stdout is a TTY, or print it directly.
+pub fn page_text(text: &str) -> Result<()> {
+    let pager = get_pager();
+    debug!("pager: {}", pager);
+    if !is_stdout_tty() || pager == "cat" {
+        println!("{}", text);
+        return Ok(());
+    }
+
+    // keep colors and quit if the text fits in one screen as git does
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(&pager)
+        .env("LESS", env::var("LESS").unwrap_or("FRX".to_string()))
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // the pager may quit before reading all of the text
+        let _ = writeln!(stdin, "{}", text);
+    }
+    child.wait()?;
+    Ok(())
 }
 
 pub fn user_input(prompt: &str) -> Result<String> {
@@ -66,12 +198,49 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_spawn() {
-        assert_eq!(spawn("echo 123").ok().unwrap(), "123\n")
+    fn test_format_timestamp_millis() {
+        assert_eq!(format_timestamp_millis(0), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            format_timestamp_millis(1598918400000),
+            "2020-09-01T00:00:00Z"
+        );
+        assert_eq!(
+            format_timestamp_millis(951826332000),
+            "2000-02-29T12:12:12Z"
+        );
+    }
+
+    #[test]
+    fn test_get_next_page() {
+        let link =
+            "<https://api.github.com/repositories/1/issues?per_page=10&page=3>; rel=\"next\", \
+            <https://api.github.com/repositories/1/issues?per_page=10&page=9>; rel=\"last\"";
+        assert_eq!(get_next_page(link), Some(3));
+        let link = "<https://gitea.com/api/v1/repos/a/b/pulls?page=1>; rel=\"first\"";
+        assert_eq!(get_next_page(link), None);
+        assert_eq!(get_next_page("https://host?page=2>; <; rel=\"next\""), None);
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(parse_timestamp("2000-02-29T12:12:12Z"), Some(951826332));
+        assert_eq!(
+            parse_timestamp("2020-09-01T08:00:00.123+08:00"),
+            Some(1598918400)
+        );
+        assert_eq!(parse_timestamp("yesterday"), None);
+        assert_eq!(
+            get_duration(Some("2020-09-01T00:00:00Z"), Some("2020-09-01T00:01:30Z")),
+            Some(90)
+        );
+        assert_eq!(format_duration(90), "1m 30s");
     }
 
     #[test]
-    fn test_get_latest_commit_message() {
-        println!("result=[{}]", get_latest_commit_message().ok().unwrap())
+    fn test_strip_comments() {
+        let text = "\n# comment\ntitle\n\nbody\n#comment\n\n";
+        assert_eq!(strip_comments(text, '#'), "title\n\nbody");
+        assert_eq!(strip_comments("#title\n; comment", ';'), "#title");
     }
 }